@@ -2,7 +2,9 @@ use std::{fs::File, io::*, path::*};
 use thiserror::Error;
 use zip::{read::ZipFile, ZipArchive};
 
-fn visit_dirs(dir: &Path, files: &mut Vec<PathBuf>) -> std::io::Result<()> {
+use crate::layered::LayeredArchive;
+
+pub(crate) fn visit_dirs(dir: &Path, files: &mut Vec<PathBuf>) -> std::io::Result<()> {
     if dir.is_dir() {
         for entry in std::fs::read_dir(dir)? {
             let entry = entry?;
@@ -25,6 +27,8 @@ pub enum FileArchiveError {
     Zip(#[from] zip::result::ZipError),
     #[error("directory not found")]
     NotFound,
+    #[error("file not found: {0:?}")]
+    FileNotFound(PathBuf),
 }
 
 pub type FileArchiveResult<T> = core::result::Result<T, FileArchiveError>;
@@ -33,6 +37,7 @@ pub enum FileArchive {
     ZipFile(ZipArchive<File>),
     ZipMemory(ZipArchive<Cursor<Vec<u8>>>),
     Dir(PathBuf),
+    Layered(LayeredArchive),
 }
 
 impl FileArchive {
@@ -79,8 +84,22 @@ impl FileArchive {
             .collect()
     }
 
+    pub fn contains(&self, path: &Path) -> bool {
+        match self {
+            FileArchive::ZipFile(archive) => {
+                archive.file_names().any(|name| Path::new(name) == path)
+            }
+            FileArchive::ZipMemory(archive) => {
+                archive.file_names().any(|name| Path::new(name) == path)
+            }
+            FileArchive::Dir(root) => root.join(path).is_file(),
+            FileArchive::Layered(archive) => archive.contains(path),
+        }
+    }
+
     pub fn files_in(&self, dir: &Path) -> FileArchiveResult<Vec<PathBuf>> {
         match self {
+            FileArchive::Layered(archive) => archive.files_in(dir),
             FileArchive::ZipFile(archive) => Ok(Self::files_in_zip(archive, dir)),
             FileArchive::ZipMemory(archive) => Ok(Self::files_in_zip(archive, dir)),
             FileArchive::Dir(root) => {
@@ -131,6 +150,7 @@ impl FileArchive {
                 let file = std::fs::File::open(&fname).map_err(FileArchiveError::Io)?;
                 Ok(FileArchiveReader::File(file))
             }
+            FileArchive::Layered(archive) => archive.open(path),
         }
     }
 
@@ -140,3 +160,17 @@ impl FileArchive {
         Ok(str)
     }
 }
+
+/// Builds an in-memory zip archive from (name, contents) pairs
+#[cfg(test)]
+pub(crate) fn zip_archive(files: &[(&str, &str)]) -> FileArchive {
+    use zip::{write::FileOptions, CompressionMethod, ZipWriter};
+    let mut writer = ZipWriter::new(Cursor::new(Vec::new()));
+    let options = FileOptions::default().compression_method(CompressionMethod::Stored);
+    for (name, contents) in files {
+        writer.start_file(*name, options).unwrap();
+        writer.write_all(contents.as_bytes()).unwrap();
+    }
+    let cursor = writer.finish().unwrap();
+    FileArchive::from_memory(Cursor::new(cursor.into_inner())).unwrap()
+}
@@ -1,4 +1,12 @@
-use std::path::Path;
+use std::{collections::HashMap, io::Read, path::Path, sync::Arc};
+
+use sp_math::fnv::fnv1a64;
+
+use crate::{
+    archive::{FileArchive, FileArchiveError},
+    loader::{AssetError, AssetResult, Loader},
+    watch::{AssetChange, AssetChangeKind},
+};
 
 fn canonicalize_path(path: &Path) -> String {
     path.to_string_lossy().to_string().replace('\\', "/")
@@ -8,26 +16,8 @@ fn canonicalize_path(path: &Path) -> String {
 pub struct AssetId(pub u64);
 
 impl AssetId {
-    // fnv1a64
-    const PRIME: u64 = 0x100000001b3;
-    const SEED: u64 = 0xcbf29ce484222325;
-
-    const fn combine(hash: u64, value: u64) -> u64 {
-        (hash ^ value).wrapping_mul(Self::PRIME)
-    }
-
-    const fn hash(value: &[u8]) -> u64 {
-        let mut h = Self::SEED;
-        let mut i = 0;
-        while i < value.len() {
-            h = Self::combine(h, value[i] as u64);
-            i += 1;
-        }
-        h
-    }
-
     pub const fn from_str(input: &str) -> Self {
-        Self(Self::hash(input.as_bytes()))
+        Self(fnv1a64::hash_bytes(input.as_bytes()))
     }
 
     pub fn from_path(path: &Path) -> Self {
@@ -63,3 +53,322 @@ impl AssetRef {
         Self::from_str(&canonicalize_path(path))
     }
 }
+
+struct CacheEntry<T> {
+    asset: Arc<T>,
+    size: usize,
+    last_used: u64,
+}
+
+/// Lazily loaded assets of one type, shared as reference counted handles.
+/// Assets nobody else references are evicted, least recently used first,
+/// when the cache exceeds its memory budget.
+pub struct AssetCache<T> {
+    loader: Box<dyn Loader<T>>,
+    entries: HashMap<AssetId, CacheEntry<T>>,
+    // Error messages of failed loads, so they aren't retried every request
+    failed: HashMap<AssetId, String>,
+    placeholder: Option<Arc<T>>,
+    size_of: Box<dyn Fn(&T) -> usize + Send + Sync>,
+    budget: usize,
+    used: usize,
+    tick: u64,
+}
+
+impl<T: 'static> AssetCache<T> {
+    pub fn new(loader: impl Loader<T>) -> Self {
+        Self {
+            loader: Box::new(loader),
+            entries: HashMap::new(),
+            failed: HashMap::new(),
+            placeholder: None,
+            size_of: Box::new(|_| std::mem::size_of::<T>()),
+            budget: usize::MAX,
+            used: 0,
+            tick: 0,
+        }
+    }
+
+    /// Asset returned by get() when loading fails
+    pub fn set_placeholder(&mut self, placeholder: Option<T>) {
+        self.placeholder = placeholder.map(Arc::new);
+    }
+
+    pub fn placeholder(&self) -> Option<Arc<T>> {
+        self.placeholder.clone()
+    }
+
+    /// Memory budget in bytes, as measured by the size function
+    pub fn set_budget(&mut self, budget: usize) {
+        self.budget = budget;
+        self.evict();
+    }
+
+    /// Estimates the memory used by an asset, defaulting to size_of::<T>()
+    pub fn set_size_fn(&mut self, size_of: impl Fn(&T) -> usize + Send + Sync + 'static) {
+        self.size_of = Box::new(size_of);
+        self.used = 0;
+        for entry in self.entries.values_mut() {
+            entry.size = (self.size_of)(&entry.asset);
+            self.used += entry.size;
+        }
+        self.evict();
+    }
+
+    pub fn len(&self) -> usize {
+        self.entries.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.entries.is_empty()
+    }
+
+    pub fn memory_used(&self) -> usize {
+        self.used
+    }
+
+    pub fn contains(&self, id: AssetId) -> bool {
+        self.entries.contains_key(&id)
+    }
+
+    /// Error message of a failed load, until the file changes
+    pub fn error(&self, id: AssetId) -> Option<&str> {
+        self.failed.get(&id).map(|message| message.as_str())
+    }
+
+    /// Number of handles held outside the cache
+    pub fn ref_count(&self, id: AssetId) -> usize {
+        self.entries
+            .get(&id)
+            .map_or(0, |entry| Arc::strong_count(&entry.asset) - 1)
+    }
+
+    fn next_tick(&mut self) -> u64 {
+        self.tick += 1;
+        self.tick
+    }
+
+    /// Cached asset without loading
+    pub fn cached(&mut self, id: AssetId) -> Option<Arc<T>> {
+        let tick = self.next_tick();
+        let entry = self.entries.get_mut(&id)?;
+        entry.last_used = tick;
+        Some(entry.asset.clone())
+    }
+
+    /// Returns the cached asset, loading it on first request. Failed loads
+    /// are not retried until apply_changes() reports the file changed.
+    pub fn try_get(&mut self, archive: &mut FileArchive, path: &Path) -> AssetResult<Arc<T>> {
+        let id = AssetId::from_path(path);
+        if let Some(asset) = self.cached(id) {
+            return Ok(asset);
+        }
+        if let Some(message) = self.failed.get(&id) {
+            return Err(AssetError::Failed(message.clone()));
+        }
+        self.load(archive, path)
+    }
+
+    /// Like try_get(), but returns the placeholder on failure, logging only
+    /// the first failure
+    pub fn get(&mut self, archive: &mut FileArchive, path: &Path) -> Option<Arc<T>> {
+        match self.try_get(archive, path) {
+            Ok(asset) => Some(asset),
+            Err(AssetError::Failed(_)) => self.placeholder(),
+            Err(err) => {
+                log::warn!("Could not load {:?}: {}", path, err);
+                self.placeholder()
+            }
+        }
+    }
+
+    /// Cached asset by id, or the placeholder when it isn't loaded
+    pub fn get_or_placeholder(&mut self, id: AssetId) -> Option<Arc<T>> {
+        self.cached(id).or_else(|| self.placeholder())
+    }
+
+    /// Loads an asset even if cached, replacing the cached version. Existing
+    /// handles keep the previous version.
+    pub fn load(&mut self, archive: &mut FileArchive, path: &Path) -> AssetResult<Arc<T>> {
+        let id = AssetId::from_path(path);
+        match self.read_and_load(archive, path) {
+            Ok(asset) => {
+                log::debug!("Loaded asset {:?}", path);
+                self.failed.remove(&id);
+                Ok(self.insert(id, asset))
+            }
+            Err(err) => {
+                self.failed.insert(id, err.to_string());
+                Err(err)
+            }
+        }
+    }
+
+    fn read_and_load(&self, archive: &mut FileArchive, path: &Path) -> AssetResult<T> {
+        let mut bytes = Vec::new();
+        archive
+            .open(path)?
+            .read_to_end(&mut bytes)
+            .map_err(FileArchiveError::Io)?;
+        self.loader.load(&bytes, path)
+    }
+
+    pub fn insert(&mut self, id: AssetId, asset: T) -> Arc<T> {
+        let asset = Arc::new(asset);
+        let size = (self.size_of)(&asset);
+        let last_used = self.next_tick();
+        let entry = CacheEntry {
+            asset: asset.clone(),
+            size,
+            last_used,
+        };
+        if let Some(prev) = self.entries.insert(id, entry) {
+            self.used -= prev.size;
+        }
+        self.used += size;
+        self.evict();
+        asset
+    }
+
+    pub fn remove(&mut self, id: AssetId) -> Option<Arc<T>> {
+        let entry = self.entries.remove(&id)?;
+        self.used -= entry.size;
+        Some(entry.asset)
+    }
+
+    pub fn clear(&mut self) {
+        self.entries.clear();
+        self.failed.clear();
+        self.used = 0;
+    }
+
+    /// Reloads cached assets whose files were modified and drops removed
+    /// ones, keeping the previous version if a reload fails. Failed loads of
+    /// added or modified files are retried on the next request.
+    pub fn apply_changes(&mut self, archive: &mut FileArchive, changes: &[AssetChange]) {
+        for change in changes.iter() {
+            match change.kind {
+                AssetChangeKind::Removed => {
+                    self.remove(change.id);
+                }
+                AssetChangeKind::Added | AssetChangeKind::Modified => {
+                    self.failed.remove(&change.id);
+                    if !self.contains(change.id) {
+                        continue;
+                    }
+                    if let Err(err) = self.load(archive, &change.path) {
+                        log::warn!("Could not reload {:?}: {}", change.path, err);
+                    }
+                }
+            }
+        }
+    }
+
+    /// Drops unreferenced assets, least recently used first, until within
+    /// budget. Returns the number evicted.
+    pub fn evict(&mut self) -> usize {
+        if self.used <= self.budget {
+            return 0;
+        }
+        let mut unused = self
+            .entries
+            .iter()
+            .filter(|(_, entry)| Arc::strong_count(&entry.asset) == 1)
+            .map(|(id, entry)| (entry.last_used, *id))
+            .collect::<Vec<_>>();
+        unused.sort_unstable();
+        let mut count = 0;
+        for (_, id) in unused {
+            if self.used <= self.budget {
+                break;
+            }
+            self.remove(id);
+            count += 1;
+        }
+        count
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::archive::zip_archive;
+
+    fn load_text(bytes: &[u8], path: &Path) -> AssetResult<String> {
+        String::from_utf8(bytes.to_vec()).map_err(|err| AssetError::decode(path, err))
+    }
+
+    #[test]
+    fn load_lazily_and_share() {
+        let mut archive = zip_archive(&[("a.txt", "aaaa")]);
+        let mut cache = AssetCache::new(load_text);
+        assert!(cache.is_empty());
+        let a = cache.get(&mut archive, Path::new("a.txt")).unwrap();
+        let a2 = cache.try_get(&mut archive, Path::new("a.txt")).unwrap();
+        assert!(Arc::ptr_eq(&a, &a2));
+        let id = AssetId::from_str("a.txt");
+        assert_eq!(cache.ref_count(id), 2);
+        drop(a2);
+        assert_eq!(cache.ref_count(id), 1);
+
+        assert!(matches!(
+            cache.try_get(&mut archive, Path::new("missing.txt")),
+            Err(AssetError::Archive(_))
+        ));
+        assert!(cache.get(&mut archive, Path::new("missing.txt")).is_none());
+        cache.set_placeholder(Some("?".to_string()));
+        assert_eq!(cache.get(&mut archive, Path::new("missing.txt")).as_deref().unwrap(), "?");
+        assert_eq!(cache.get_or_placeholder(AssetId::from_str("b.txt")).as_deref().unwrap(), "?");
+        assert_eq!(cache.len(), 1);
+    }
+
+    #[test]
+    fn remember_failures_until_changed() {
+        let mut archive = zip_archive(&[("a.txt", "aaaa")]);
+        let mut cache = AssetCache::new(load_text);
+        cache.set_placeholder(Some("?".to_string()));
+        let path = Path::new("b.txt");
+        let id = AssetId::from_str("b.txt");
+        assert_eq!(cache.get(&mut archive, path).as_deref().unwrap(), "?");
+        assert!(cache.error(id).is_some());
+
+        // Later requests don't touch the archive
+        let mut other = zip_archive(&[("b.txt", "bbbb")]);
+        assert!(matches!(cache.try_get(&mut other, path), Err(AssetError::Failed(_))));
+        assert_eq!(cache.get(&mut other, path).as_deref().unwrap(), "?");
+
+        let change = AssetChange {
+            id,
+            path: path.to_path_buf(),
+            kind: AssetChangeKind::Added,
+        };
+        cache.apply_changes(&mut other, &[change]);
+        assert!(cache.error(id).is_none());
+        assert_eq!(cache.get(&mut other, path).as_deref().unwrap(), "bbbb");
+    }
+
+    #[test]
+    fn evict_unreferenced_over_budget() {
+        let mut archive = zip_archive(&[("a.txt", "aaaa"), ("b.txt", "bbbb"), ("c.txt", "cccc")]);
+        let mut cache = AssetCache::new(load_text);
+        cache.set_size_fn(|s: &String| s.len());
+        cache.set_budget(8);
+        let a = cache.get(&mut archive, Path::new("a.txt")).unwrap();
+        cache.get(&mut archive, Path::new("b.txt"));
+        assert_eq!(cache.memory_used(), 8);
+        // b is unreferenced and least recently used apart from held a
+        cache.get(&mut archive, Path::new("c.txt"));
+        assert!(cache.contains(AssetId::from_str("a.txt")));
+        assert!(!cache.contains(AssetId::from_str("b.txt")));
+        assert!(cache.contains(AssetId::from_str("c.txt")));
+        assert_eq!(cache.memory_used(), 8);
+
+        // Referenced assets are kept even over budget
+        cache.set_budget(0);
+        assert_eq!(cache.len(), 1);
+        assert_eq!(cache.memory_used(), 4);
+        drop(a);
+        assert_eq!(cache.evict(), 1);
+        assert!(cache.is_empty());
+    }
+}
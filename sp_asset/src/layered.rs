@@ -0,0 +1,235 @@
+use std::{
+    collections::HashSet,
+    io::Read,
+    path::{Path, PathBuf},
+};
+
+use crate::archive::{FileArchive, FileArchiveError, FileArchiveReader, FileArchiveResult};
+
+/// Prefix of whiteout entries. A file named '.wh.name' in a layer hides
+/// 'name' in the layers below it, and hides everything under 'name' if it
+/// is a directory.
+pub const WHITEOUT_PREFIX: &str = ".wh.";
+
+fn whiteout_target(path: &Path) -> Option<PathBuf> {
+    let name = path.file_name()?.to_str()?;
+    let target = name.strip_prefix(WHITEOUT_PREFIX)?;
+    Some(path.with_file_name(target))
+}
+
+pub struct Layer {
+    name: String,
+    priority: i32,
+    archive: FileArchive,
+    whiteouts: HashSet<PathBuf>,
+}
+
+impl Layer {
+    pub fn name(&self) -> &str {
+        &self.name
+    }
+
+    pub fn priority(&self) -> i32 {
+        self.priority
+    }
+
+    pub fn archive(&self) -> &FileArchive {
+        &self.archive
+    }
+
+    fn scan_whiteouts(&mut self) -> FileArchiveResult<()> {
+        self.whiteouts = self
+            .archive
+            .files_in(Path::new(""))?
+            .iter()
+            .filter_map(|path| whiteout_target(path))
+            .collect();
+        Ok(())
+    }
+
+    /// Whether this layer hides path or one of its parent directories
+    fn hides(&self, path: &Path) -> bool {
+        !self.whiteouts.is_empty() && path.ancestors().any(|p| self.whiteouts.contains(p))
+    }
+}
+
+/// Stack of archives, such as a base pack with patch packs and mod
+/// directories on top. Files resolve from the highest priority layer that
+/// has them, unless a whiteout in a higher layer hides them.
+#[derive(Default)]
+pub struct LayeredArchive {
+    // Highest priority first
+    layers: Vec<Layer>,
+}
+
+impl LayeredArchive {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Stacks every existing path as a layer named after it, with later
+    /// paths above earlier ones
+    pub fn from_paths(paths: &[PathBuf]) -> FileArchiveResult<Self> {
+        let mut archive = Self::new();
+        for path in paths.iter().filter(|path| path.exists()) {
+            log::info!("Adding layer {:?}", path.to_string_lossy());
+            archive.push(&path.to_string_lossy(), FileArchive::from_path(path)?)?;
+        }
+        if archive.layers.is_empty() {
+            Err(FileArchiveError::NotFound)
+        } else {
+            Ok(archive)
+        }
+    }
+
+    /// Adds a layer above all existing ones
+    pub fn push(&mut self, name: &str, archive: FileArchive) -> FileArchiveResult<()> {
+        let priority = self.layers.first().map_or(0, |layer| layer.priority.saturating_add(1));
+        self.add_layer(name, priority, archive)
+    }
+
+    /// Adds a layer above layers of lower or equal priority
+    pub fn add_layer(
+        &mut self,
+        name: &str,
+        priority: i32,
+        archive: FileArchive,
+    ) -> FileArchiveResult<()> {
+        let mut layer = Layer {
+            name: name.to_string(),
+            priority,
+            archive,
+            whiteouts: HashSet::new(),
+        };
+        layer.scan_whiteouts()?;
+        let index = self.layers.partition_point(|l| l.priority > priority);
+        self.layers.insert(index, layer);
+        Ok(())
+    }
+
+    /// Rescans whiteout entries, e.g. after files in a directory layer changed
+    pub fn refresh(&mut self) -> FileArchiveResult<()> {
+        for layer in self.layers.iter_mut() {
+            layer.scan_whiteouts()?;
+        }
+        Ok(())
+    }
+
+    /// Layers from highest to lowest priority
+    pub fn layers(&self) -> &[Layer] {
+        &self.layers
+    }
+
+    /// Index of the layer providing path, if any. Whiteouts only hide files
+    /// in lower layers.
+    pub fn provider_index(&self, path: &Path) -> Option<usize> {
+        for (i, layer) in self.layers.iter().enumerate() {
+            if layer.archive.contains(path) {
+                return Some(i);
+            }
+            if layer.hides(path) {
+                return None;
+            }
+        }
+        None
+    }
+
+    /// Name of the layer providing path, if any
+    pub fn provider(&self, path: &Path) -> Option<&str> {
+        self.provider_index(path).map(|i| self.layers[i].name())
+    }
+
+    pub fn contains(&self, path: &Path) -> bool {
+        self.provider_index(path).is_some()
+    }
+
+    /// Files from all layers, without duplicates, whiteouts or hidden files
+    pub fn files_in(&self, dir: &Path) -> FileArchiveResult<Vec<PathBuf>> {
+        let mut seen = HashSet::new();
+        let mut files = Vec::new();
+        for (i, layer) in self.layers.iter().enumerate() {
+            let above = &self.layers[..i];
+            for path in layer.archive.files_in(dir)? {
+                if whiteout_target(&path).is_some()
+                    || above.iter().any(|l| l.hides(&path))
+                    || !seen.insert(path.clone())
+                {
+                    continue;
+                }
+                files.push(path);
+            }
+        }
+        files.sort();
+        Ok(files)
+    }
+
+    pub fn open<'a>(&'a mut self, path: &Path) -> FileArchiveResult<FileArchiveReader<'a>> {
+        match self.provider_index(path) {
+            Some(i) => self.layers[i].archive.open(path),
+            None => Err(FileArchiveError::FileNotFound(path.to_path_buf())),
+        }
+    }
+
+    pub fn read_string(&mut self, path: &Path) -> FileArchiveResult<String> {
+        let mut str = String::new();
+        self.open(path)?.read_to_string(&mut str)?;
+        Ok(str)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::archive::zip_archive;
+
+    #[test]
+    fn resolve_from_top_layer() {
+        let mut archive = LayeredArchive::new();
+        archive
+            .push(
+                "base",
+                zip_archive(&[
+                    ("defs/a.txt", "base a"),
+                    ("defs/b.txt", "base b"),
+                    ("defs/old/c.txt", "base c"),
+                    ("sounds/d.wav", "base d"),
+                ]),
+            )
+            .unwrap();
+        archive
+            .push(
+                "patch",
+                zip_archive(&[
+                    ("defs/a.txt", "patch a"),
+                    ("defs/.wh.b.txt", ""),
+                    ("defs/.wh.old", ""),
+                    ("defs/e.txt", "patch e"),
+                    ("defs/.wh.e.txt", ""),
+                ]),
+            )
+            .unwrap();
+        archive
+            .add_layer("fallback", -1, zip_archive(&[("defs/b.txt", "fallback b")]))
+            .unwrap();
+        let names: Vec<_> = archive.layers().iter().map(|l| l.name()).collect();
+        assert_eq!(names, vec!["patch", "base", "fallback"]);
+
+        assert_eq!(archive.read_string(Path::new("defs/a.txt")).unwrap(), "patch a");
+        assert_eq!(archive.provider(Path::new("defs/a.txt")), Some("patch"));
+        assert_eq!(archive.provider(Path::new("sounds/d.wav")), Some("base"));
+        // A whiteout doesn't hide the file in its own layer
+        assert_eq!(archive.read_string(Path::new("defs/e.txt")).unwrap(), "patch e");
+        assert!(!archive.contains(Path::new("defs/b.txt")));
+        assert!(!archive.contains(Path::new("defs/old/c.txt")));
+        assert!(matches!(
+            archive.open(Path::new("defs/b.txt")),
+            Err(FileArchiveError::FileNotFound(_))
+        ));
+
+        let files = archive.files_in(Path::new("defs")).unwrap();
+        assert_eq!(
+            files,
+            vec![PathBuf::from("defs/a.txt"), PathBuf::from("defs/e.txt")]
+        );
+    }
+}
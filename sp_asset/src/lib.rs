@@ -2,5 +2,12 @@
 
 pub mod archive;
 pub mod cache;
+pub mod layered;
+pub mod loader;
+pub mod server;
+pub mod watch;
 
 pub use cache::*;
+pub use loader::*;
+pub use server::*;
+pub use watch::*;
@@ -0,0 +1,45 @@
+use std::{fmt::Display, path::Path};
+use thiserror::Error;
+
+use crate::{archive::FileArchiveError, AssetId};
+
+#[derive(Debug, Error)]
+pub enum AssetError {
+    #[error(transparent)]
+    Archive(#[from] FileArchiveError),
+    #[error("could not decode {path}: {message}")]
+    Decode { path: String, message: String },
+    #[error("no loader registered for {0}")]
+    NoLoader(&'static str),
+    #[error("asset {0:?} not found")]
+    NotFound(AssetId),
+    #[error("asset workers stopped")]
+    WorkersStopped,
+    #[error("previous load failed: {0}")]
+    Failed(String),
+}
+
+impl AssetError {
+    pub fn decode(path: &Path, err: impl Display) -> Self {
+        Self::Decode {
+            path: path.to_string_lossy().to_string(),
+            message: err.to_string(),
+        }
+    }
+}
+
+pub type AssetResult<T> = core::result::Result<T, AssetError>;
+
+/// Decodes an asset from the bytes of a file and its path within the archive
+pub trait Loader<T>: Send + Sync + 'static {
+    fn load(&self, bytes: &[u8], path: &Path) -> AssetResult<T>;
+}
+
+impl<T, F> Loader<T> for F
+where
+    F: Fn(&[u8], &Path) -> AssetResult<T> + Send + Sync + 'static,
+{
+    fn load(&self, bytes: &[u8], path: &Path) -> AssetResult<T> {
+        self(bytes, path)
+    }
+}
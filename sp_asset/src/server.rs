@@ -0,0 +1,620 @@
+use std::{
+    any::{Any, TypeId},
+    collections::{hash_map, HashMap, VecDeque},
+    fmt,
+    hash::{Hash, Hasher},
+    io::Read,
+    marker::PhantomData,
+    panic::{catch_unwind, AssertUnwindSafe},
+    path::{Path, PathBuf},
+    sync::{mpsc, Arc, Mutex, PoisonError},
+    thread::JoinHandle,
+};
+
+use crate::{
+    archive::{FileArchive, FileArchiveError},
+    loader::{AssetError, AssetResult, Loader},
+    watch::{AssetChange, AssetChangeKind},
+    AssetId,
+};
+
+/// Typed reference to an asset requested from an AssetServer
+pub struct Handle<T> {
+    id: AssetId,
+    marker: PhantomData<fn() -> T>,
+}
+
+impl<T> Handle<T> {
+    pub const fn new(id: AssetId) -> Self {
+        Self {
+            id,
+            marker: PhantomData,
+        }
+    }
+
+    pub fn id(&self) -> AssetId {
+        self.id
+    }
+}
+
+impl<T> Clone for Handle<T> {
+    fn clone(&self) -> Self {
+        *self
+    }
+}
+
+impl<T> Copy for Handle<T> {}
+
+impl<T> PartialEq for Handle<T> {
+    fn eq(&self, other: &Self) -> bool {
+        self.id == other.id
+    }
+}
+
+impl<T> Eq for Handle<T> {}
+
+impl<T> Hash for Handle<T> {
+    fn hash<H: Hasher>(&self, state: &mut H) {
+        self.id.hash(state);
+    }
+}
+
+impl<T> fmt::Debug for Handle<T> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_tuple("Handle").field(&self.id.0).finish()
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum LoadState {
+    Queued,
+    Loading,
+    Ready,
+    Failed,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum AssetEvent {
+    Ready(AssetId),
+    Failed(AssetId),
+}
+
+/// Number of assets in each state
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq)]
+pub struct LoadProgress {
+    pub queued: usize,
+    pub loading: usize,
+    pub ready: usize,
+    pub failed: usize,
+}
+
+impl LoadProgress {
+    pub fn total(&self) -> usize {
+        self.queued + self.loading + self.ready + self.failed
+    }
+
+    pub fn is_done(&self) -> bool {
+        self.queued == 0 && self.loading == 0
+    }
+
+    /// Fraction of assets that finished loading or failed, in [0, 1]
+    pub fn fraction(&self) -> f32 {
+        let total = self.total();
+        if total == 0 {
+            1.0
+        } else {
+            (self.ready + self.failed) as f32 / total as f32
+        }
+    }
+}
+
+type AnyAsset = Arc<dyn Any + Send + Sync>;
+type ErasedLoader = Arc<dyn Fn(&[u8], &Path) -> AssetResult<AnyAsset> + Send + Sync>;
+
+struct Job {
+    id: AssetId,
+    generation: u32,
+    path: PathBuf,
+    loader: ErasedLoader,
+}
+
+enum WorkerMsg {
+    Started(AssetId, u32),
+    Finished(AssetId, u32, AssetResult<AnyAsset>),
+}
+
+struct Entry {
+    path: PathBuf,
+    // Bumped on reload so results of older jobs are ignored
+    generation: u32,
+    type_id: TypeId,
+    type_name: &'static str,
+    state: LoadState,
+    asset: Option<AnyAsset>,
+    error: Option<String>,
+}
+
+fn read_and_load(archive: &Mutex<FileArchive>, job: &Job) -> AssetResult<AnyAsset> {
+    // Only hold the archive while reading so other workers can decode. A
+    // panic elsewhere while holding it doesn't stop further loads.
+    let bytes = {
+        let mut archive = archive.lock().unwrap_or_else(PoisonError::into_inner);
+        let mut buf = Vec::new();
+        archive
+            .open(&job.path)?
+            .read_to_end(&mut buf)
+            .map_err(FileArchiveError::Io)?;
+        buf
+    };
+    (job.loader)(&bytes, &job.path)
+}
+
+fn run_job(archive: &Mutex<FileArchive>, job: &Job) -> AssetResult<AnyAsset> {
+    // A panicking job fails its asset instead of killing the worker
+    catch_unwind(AssertUnwindSafe(|| read_and_load(archive, job))).unwrap_or_else(|panic| {
+        let message = panic
+            .downcast_ref::<&str>()
+            .map(|s| s.to_string())
+            .or_else(|| panic.downcast_ref::<String>().cloned())
+            .unwrap_or_default();
+        Err(AssetError::decode(&job.path, format!("panicked while loading: {}", message)))
+    })
+}
+
+fn run_worker(
+    archive: Arc<Mutex<FileArchive>>,
+    jobs: Arc<Mutex<mpsc::Receiver<Job>>>,
+    results: mpsc::Sender<WorkerMsg>,
+) {
+    loop {
+        let job = jobs.lock().unwrap_or_else(PoisonError::into_inner).recv();
+        let Ok(job) = job else {
+            break;
+        };
+        if results.send(WorkerMsg::Started(job.id, job.generation)).is_err() {
+            break;
+        }
+        let result = run_job(&archive, &job);
+        if results.send(WorkerMsg::Finished(job.id, job.generation, result)).is_err() {
+            break;
+        }
+    }
+}
+
+fn default_workers() -> usize {
+    if cfg!(target_arch = "wasm32") {
+        0
+    } else {
+        std::thread::available_parallelism().map_or(1, |n| n.get())
+    }
+}
+
+/// Loads assets from an archive in the background. Requests return handles
+/// immediately, and decoding happens on worker threads, or cooperatively
+/// during update() when there are no workers (e.g. on wasm).
+pub struct AssetServer {
+    archive: Arc<Mutex<FileArchive>>,
+    loaders: HashMap<TypeId, ErasedLoader>,
+    entries: HashMap<AssetId, Entry>,
+    events: Vec<AssetEvent>,
+    jobs: Option<mpsc::Sender<Job>>,
+    results: mpsc::Receiver<WorkerMsg>,
+    workers: Vec<JoinHandle<()>>,
+    pending: VecDeque<Job>,
+    jobs_per_update: usize,
+}
+
+impl AssetServer {
+    pub fn new(archive: Arc<Mutex<FileArchive>>) -> Self {
+        Self::with_workers(archive, default_workers())
+    }
+
+    /// With zero workers, assets are decoded during update()
+    pub fn with_workers(archive: Arc<Mutex<FileArchive>>, worker_count: usize) -> Self {
+        let (result_sender, results) = mpsc::channel();
+        let (jobs, workers) = if worker_count == 0 {
+            (None, Vec::new())
+        } else {
+            let (job_sender, job_receiver) = mpsc::channel();
+            let job_receiver = Arc::new(Mutex::new(job_receiver));
+            let workers = (0..worker_count)
+                .map(|i| {
+                    let archive = archive.clone();
+                    let jobs = job_receiver.clone();
+                    let results = result_sender.clone();
+                    std::thread::Builder::new()
+                        .name(format!("asset-worker-{}", i))
+                        .spawn(move || run_worker(archive, jobs, results))
+                        .expect("Could not spawn asset worker")
+                })
+                .collect();
+            (Some(job_sender), workers)
+        };
+        Self {
+            archive,
+            loaders: HashMap::new(),
+            entries: HashMap::new(),
+            events: Vec::new(),
+            jobs,
+            results,
+            workers,
+            pending: VecDeque::new(),
+            jobs_per_update: 1,
+        }
+    }
+
+    pub fn archive(&self) -> &Arc<Mutex<FileArchive>> {
+        &self.archive
+    }
+
+    /// Number of assets decoded per update() when there are no workers
+    pub fn set_jobs_per_update(&mut self, count: usize) {
+        self.jobs_per_update = count.max(1);
+    }
+
+    /// Registers the loader used for assets of type T, replacing any previous
+    pub fn add_loader<T: Send + Sync + 'static>(&mut self, loader: impl Loader<T>) {
+        let erased: ErasedLoader = Arc::new(move |bytes: &[u8], path: &Path| {
+            loader.load(bytes, path).map(|asset| Arc::new(asset) as AnyAsset)
+        });
+        self.loaders.insert(TypeId::of::<T>(), erased);
+    }
+
+    /// Requests an asset, returning the existing handle if the path was
+    /// already requested
+    pub fn load<T: Send + Sync + 'static>(&mut self, path: &Path) -> Handle<T> {
+        let id = AssetId::from_path(path);
+        if let hash_map::Entry::Vacant(slot) = self.entries.entry(id) {
+            slot.insert(Entry {
+                path: path.to_path_buf(),
+                generation: 0,
+                type_id: TypeId::of::<T>(),
+                type_name: std::any::type_name::<T>(),
+                state: LoadState::Queued,
+                asset: None,
+                error: None,
+            });
+            match self.loaders.get(&TypeId::of::<T>()).cloned() {
+                Some(loader) => self.dispatch(Job {
+                    id,
+                    generation: 0,
+                    path: path.to_path_buf(),
+                    loader,
+                }),
+                None => self.finish(id, 0, Err(AssetError::NoLoader(std::any::type_name::<T>()))),
+            }
+        }
+        Handle::new(id)
+    }
+
+    fn dispatch(&mut self, job: Job) {
+        match &self.jobs {
+            Some(jobs) => {
+                if let Err(mpsc::SendError(job)) = jobs.send(job) {
+                    self.finish(job.id, job.generation, Err(AssetError::WorkersStopped));
+                }
+            }
+            None => self.pending.push_back(job),
+        }
+    }
+
+    fn finish(&mut self, id: AssetId, generation: u32, result: AssetResult<AnyAsset>) {
+        let Some(entry) = self.entries.get_mut(&id) else {
+            return;
+        };
+        if entry.generation != generation {
+            log::debug!("Ignoring outdated load of {:?}", entry.path);
+            return;
+        }
+        match result {
+            Ok(asset) => {
+                log::debug!("Loaded asset {:?} ({})", entry.path, id.0);
+                entry.state = LoadState::Ready;
+                entry.asset = Some(asset);
+                entry.error = None;
+                self.events.push(AssetEvent::Ready(id));
+            }
+            Err(err) => {
+                // A failed reload keeps the previous version available
+                log::warn!("Could not load {:?}: {}", entry.path, err);
+                entry.state = LoadState::Failed;
+                entry.error = Some(err.to_string());
+                self.events.push(AssetEvent::Failed(id));
+            }
+        }
+    }
+
+    /// Loads an already requested asset again, e.g. after its file changed.
+    /// The previous version stays available until the new one is ready, and
+    /// results of loads started before the reload are ignored.
+    pub fn reload(&mut self, id: AssetId) -> bool {
+        let Some(entry) = self.entries.get_mut(&id) else {
+            return false;
+        };
+        entry.state = LoadState::Queued;
+        entry.generation = entry.generation.wrapping_add(1);
+        let generation = entry.generation;
+        let path = entry.path.clone();
+        let type_name = entry.type_name;
+        match self.loaders.get(&entry.type_id).cloned() {
+            Some(loader) => self.dispatch(Job {
+                id,
+                generation,
+                path,
+                loader,
+            }),
+            None => self.finish(id, generation, Err(AssetError::NoLoader(type_name))),
+        }
+        true
+    }
+
+    /// Reloads requested assets whose files were added or modified, returning
+    /// the number of reloads
+    pub fn reload_changed(&mut self, changes: &[AssetChange]) -> usize {
+        changes
+            .iter()
+            .filter(|change| change.kind != AssetChangeKind::Removed)
+            .filter(|change| self.reload(change.id))
+            .count()
+    }
+
+    /// Applies results from workers, or decodes queued assets when there
+    /// are no workers. Call once per frame.
+    pub fn update(&mut self) {
+        while let Ok(msg) = self.results.try_recv() {
+            match msg {
+                WorkerMsg::Started(id, generation) => {
+                    if let Some(entry) = self.entries.get_mut(&id) {
+                        if entry.generation == generation {
+                            entry.state = LoadState::Loading;
+                        }
+                    }
+                }
+                WorkerMsg::Finished(id, generation, result) => self.finish(id, generation, result),
+            }
+        }
+        for _ in 0..self.jobs_per_update {
+            let Some(job) = self.pending.pop_front() else {
+                break;
+            };
+            let result = run_job(&self.archive, &job);
+            self.finish(job.id, job.generation, result);
+        }
+    }
+
+    /// Events since the last call, in completion order
+    pub fn drain_events(&mut self) -> std::vec::Drain<'_, AssetEvent> {
+        self.events.drain(..)
+    }
+
+    pub fn state(&self, id: AssetId) -> Option<LoadState> {
+        self.entries.get(&id).map(|entry| entry.state)
+    }
+
+    /// Error message of a failed asset
+    pub fn error(&self, id: AssetId) -> Option<&str> {
+        self.entries.get(&id).and_then(|entry| entry.error.as_deref())
+    }
+
+    pub fn path(&self, id: AssetId) -> Option<&Path> {
+        self.entries.get(&id).map(|entry| entry.path.as_path())
+    }
+
+    /// Loaded asset, or None while loading, on failure, or when the path was
+    /// requested as a different type
+    pub fn get<T: Send + Sync + 'static>(&self, handle: Handle<T>) -> Option<Arc<T>> {
+        let asset = self.entries.get(&handle.id)?.asset.clone()?;
+        asset.downcast::<T>().ok()
+    }
+
+    pub fn progress(&self) -> LoadProgress {
+        let mut progress = LoadProgress::default();
+        for entry in self.entries.values() {
+            match entry.state {
+                LoadState::Queued => progress.queued += 1,
+                LoadState::Loading => progress.loading += 1,
+                LoadState::Ready => progress.ready += 1,
+                LoadState::Failed => progress.failed += 1,
+            }
+        }
+        progress
+    }
+}
+
+impl Drop for AssetServer {
+    fn drop(&mut self) {
+        // Closing the job channel stops the workers
+        self.jobs = None;
+        for worker in self.workers.drain(..) {
+            let _ = worker.join();
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::archive::zip_archive;
+
+    fn parse_number(bytes: &[u8], path: &Path) -> AssetResult<i32> {
+        let text = std::str::from_utf8(bytes).map_err(|err| AssetError::decode(path, err))?;
+        text.trim().parse().map_err(|err| AssetError::decode(path, err))
+    }
+
+    fn load_all(workers: usize) {
+        let archive = zip_archive(&[("a.txt", "1"), ("b.txt", "2"), ("bad.txt", "x")]);
+        let mut server = AssetServer::with_workers(Arc::new(Mutex::new(archive)), workers);
+        server.add_loader(parse_number);
+        let a = server.load::<i32>(Path::new("a.txt"));
+        let b = server.load::<i32>(Path::new("b.txt"));
+        let bad = server.load::<i32>(Path::new("bad.txt"));
+        let missing = server.load::<i32>(Path::new("missing.txt"));
+        let unknown = server.load::<String>(Path::new("a2.txt"));
+        assert_eq!(server.load::<i32>(Path::new("a.txt")), a);
+        assert_eq!(server.state(unknown.id()), Some(LoadState::Failed));
+
+        let mut events = Vec::new();
+        while !server.progress().is_done() {
+            server.update();
+            events.extend(server.drain_events());
+            std::thread::yield_now();
+        }
+        assert_eq!(events.len(), 5);
+        assert!(events.contains(&AssetEvent::Ready(b.id())));
+        assert!(events.contains(&AssetEvent::Failed(bad.id())));
+        assert_eq!(server.get(a).as_deref(), Some(&1));
+        assert_eq!(server.get(b).as_deref(), Some(&2));
+        assert!(server.get(bad).is_none());
+        assert!(server.error(bad.id()).unwrap().contains("bad.txt"));
+        assert_eq!(server.state(missing.id()), Some(LoadState::Failed));
+        let progress = server.progress();
+        assert_eq!((progress.ready, progress.failed), (2, 3));
+        assert_eq!(progress.fraction(), 1.0);
+    }
+
+    #[test]
+    fn load_on_workers() {
+        load_all(2);
+    }
+
+    #[test]
+    fn load_cooperatively() {
+        load_all(0);
+    }
+
+    #[test]
+    fn loader_panic_fails_asset() {
+        let archive = zip_archive(&[("a.txt", "1"), ("b.txt", "2"), ("c.txt", "3")]);
+        let mut server = AssetServer::with_workers(Arc::new(Mutex::new(archive)), 1);
+        server.add_loader(|bytes: &[u8], path: &Path| -> AssetResult<i32> {
+            if path == Path::new("a.txt") {
+                panic!("bad asset");
+            }
+            parse_number(bytes, path)
+        });
+        let a = server.load::<i32>(Path::new("a.txt"));
+        let b = server.load::<i32>(Path::new("b.txt"));
+        while !server.progress().is_done() {
+            server.update();
+            std::thread::yield_now();
+        }
+        assert_eq!(server.state(a.id()), Some(LoadState::Failed));
+        assert!(server.error(a.id()).unwrap().contains("bad asset"));
+        assert_eq!(server.get(b).as_deref(), Some(&2));
+        // The worker survived and keeps loading
+        let c = server.load::<i32>(Path::new("c.txt"));
+        while !server.progress().is_done() {
+            server.update();
+            std::thread::yield_now();
+        }
+        assert_eq!(server.get(c).as_deref(), Some(&3));
+    }
+
+    fn poisoned_archive() -> Arc<Mutex<FileArchive>> {
+        let archive = Arc::new(Mutex::new(zip_archive(&[("a.txt", "1"), ("b.txt", "2")])));
+        let shared = archive.clone();
+        let result = std::thread::spawn(move || {
+            let _guard = shared.lock().unwrap();
+            panic!("poison the archive");
+        })
+        .join();
+        assert!(result.is_err() && archive.is_poisoned());
+        archive
+    }
+
+    #[test]
+    fn load_from_poisoned_archive() {
+        for workers in [0, 2] {
+            let mut server = AssetServer::with_workers(poisoned_archive(), workers);
+            server.add_loader(parse_number);
+            let a = server.load::<i32>(Path::new("a.txt"));
+            let b = server.load::<i32>(Path::new("b.txt"));
+            while !server.progress().is_done() {
+                server.update();
+                std::thread::yield_now();
+            }
+            assert_eq!(server.get(a).as_deref(), Some(&1));
+            assert_eq!(server.get(b).as_deref(), Some(&2));
+        }
+    }
+
+    #[test]
+    fn reload_in_place() {
+        let archive = zip_archive(&[("a.txt", "1")]);
+        let mut server = AssetServer::with_workers(Arc::new(Mutex::new(archive)), 0);
+        server.add_loader(parse_number);
+        let a = server.load::<i32>(Path::new("a.txt"));
+        server.update();
+        assert_eq!(server.get(a).as_deref(), Some(&1));
+        server.drain_events().for_each(drop);
+
+        server.add_loader(|bytes: &[u8], path: &Path| parse_number(bytes, path).map(|x| x * 10));
+        let change = AssetChange {
+            id: AssetId::from_str("a.txt"),
+            path: PathBuf::from("a.txt"),
+            kind: AssetChangeKind::Modified,
+        };
+        assert_eq!(server.reload_changed(&[change]), 1);
+        assert_eq!(server.state(a.id()), Some(LoadState::Queued));
+        assert_eq!(server.get(a).as_deref(), Some(&1));
+        server.update();
+        assert_eq!(server.get(a).as_deref(), Some(&10));
+        assert_eq!(server.drain_events().collect::<Vec<_>>(), vec![AssetEvent::Ready(a.id())]);
+
+        server.add_loader(|_: &[u8], path: &Path| -> AssetResult<i32> {
+            Err(AssetError::decode(path, "broken"))
+        });
+        assert!(server.reload(a.id()));
+        server.update();
+        assert_eq!(server.state(a.id()), Some(LoadState::Failed));
+        assert_eq!(server.get(a).as_deref(), Some(&10));
+        assert!(!server.reload(AssetId::from_str("b.txt")));
+    }
+
+    #[test]
+    fn reload_ignores_outdated_loads() {
+        let files = |a: &str| zip_archive(&[("a.txt", a), ("b.txt", "wait"), ("c.txt", "wait")]);
+        let mut server = AssetServer::with_workers(Arc::new(Mutex::new(files("old"))), 2);
+        let (started_sender, started) = mpsc::channel();
+        let (resume, resumed) = mpsc::channel::<()>();
+        let (started_sender, resumed) = (Mutex::new(started_sender), Mutex::new(resumed));
+        let barrier = Arc::new(std::sync::Barrier::new(3));
+        let loader_barrier = barrier.clone();
+        server.add_loader(move |bytes: &[u8], _: &Path| -> AssetResult<String> {
+            match bytes {
+                b"old" => {
+                    started_sender.lock().unwrap().send(()).unwrap();
+                    resumed.lock().unwrap().recv().unwrap();
+                }
+                b"wait" => {
+                    loader_barrier.wait();
+                }
+                _ => (),
+            }
+            Ok(String::from_utf8_lossy(bytes).to_string())
+        });
+
+        // Reload while the first load is still running
+        let a = server.load::<String>(Path::new("a.txt"));
+        started.recv().unwrap();
+        *server.archive().lock().unwrap() = files("new");
+        assert!(server.reload(a.id()));
+        while server.state(a.id()) != Some(LoadState::Ready) {
+            server.update();
+            std::thread::yield_now();
+        }
+        assert_eq!(server.get(a).as_deref().map(String::as_str), Some("new"));
+
+        // Once both workers are busy with other assets, the outdated result
+        // has been sent
+        resume.send(()).unwrap();
+        server.load::<String>(Path::new("b.txt"));
+        server.load::<String>(Path::new("c.txt"));
+        barrier.wait();
+        server.update();
+        assert_eq!(server.get(a).as_deref().map(String::as_str), Some("new"));
+        assert_eq!(server.state(a.id()), Some(LoadState::Ready));
+        let ready = server.drain_events().filter(|e| *e == AssetEvent::Ready(a.id())).count();
+        assert_eq!(ready, 1);
+    }
+}
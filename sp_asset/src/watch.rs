@@ -0,0 +1,185 @@
+use std::{
+    collections::HashMap,
+    path::{Path, PathBuf},
+    time::{Duration, Instant, SystemTime},
+};
+
+use crate::{
+    archive::{visit_dirs, FileArchive},
+    AssetId,
+};
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum AssetChangeKind {
+    Added,
+    Modified,
+    Removed,
+}
+
+/// Change to a file under a watched directory, with its path relative to
+/// the directory so the id matches the one used when loading
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct AssetChange {
+    pub id: AssetId,
+    pub path: PathBuf,
+    pub kind: AssetChangeKind,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+struct FileStamp {
+    modified: Option<SystemTime>,
+    len: u64,
+}
+
+fn scan_dir(root: &Path) -> std::io::Result<HashMap<PathBuf, FileStamp>> {
+    let mut paths = Vec::new();
+    visit_dirs(root, &mut paths)?;
+    let mut files = HashMap::with_capacity(paths.len());
+    for path in paths {
+        // Files can disappear between listing and reading metadata
+        let Ok(meta) = std::fs::metadata(&path) else {
+            continue;
+        };
+        let stamp = FileStamp {
+            modified: meta.modified().ok(),
+            len: meta.len(),
+        };
+        let rel = path.strip_prefix(root).unwrap_or(&path).to_path_buf();
+        files.insert(rel, stamp);
+    }
+    Ok(files)
+}
+
+/// Detects changed files under a directory archive by polling modification
+/// times, so it works on any platform without native watchers
+pub struct DirWatcher {
+    root: PathBuf,
+    files: HashMap<PathBuf, FileStamp>,
+    interval: Duration,
+    last_scan: Option<Instant>,
+}
+
+impl DirWatcher {
+    pub fn new(root: &Path) -> Self {
+        let files = scan_dir(root).unwrap_or_else(|err| {
+            log::warn!("Could not scan {:?}: {}", root, err);
+            HashMap::new()
+        });
+        Self {
+            root: root.to_path_buf(),
+            files,
+            interval: Duration::from_millis(500),
+            last_scan: None,
+        }
+    }
+
+    /// Watcher for a Dir archive, or None for zip archives
+    pub fn from_archive(archive: &FileArchive) -> Option<Self> {
+        match archive {
+            FileArchive::Dir(root) => Some(Self::new(root)),
+            _ => None,
+        }
+    }
+
+    pub fn root(&self) -> &Path {
+        &self.root
+    }
+
+    /// Minimum time between scans done by poll()
+    pub fn set_interval(&mut self, interval: Duration) {
+        self.interval = interval;
+    }
+
+    /// Scans if the interval has elapsed since the last scan. Call once per
+    /// frame.
+    pub fn poll(&mut self) -> Vec<AssetChange> {
+        let now = Instant::now();
+        match self.last_scan {
+            Some(last) if now.duration_since(last) < self.interval => Vec::new(),
+            _ => {
+                self.last_scan = Some(now);
+                self.scan()
+            }
+        }
+    }
+
+    /// Compares the directory against the previous scan, sorted by path
+    pub fn scan(&mut self) -> Vec<AssetChange> {
+        let files = match scan_dir(&self.root) {
+            Ok(files) => files,
+            Err(err) => {
+                // Keep the previous snapshot and try again next time
+                log::warn!("Could not scan {:?}: {}", self.root, err);
+                return Vec::new();
+            }
+        };
+        let mut changes = Vec::new();
+        for (path, stamp) in files.iter() {
+            let kind = match self.files.get(path) {
+                None => AssetChangeKind::Added,
+                Some(prev) if prev != stamp => AssetChangeKind::Modified,
+                Some(_) => continue,
+            };
+            changes.push((path.clone(), kind));
+        }
+        for path in self.files.keys() {
+            if !files.contains_key(path) {
+                changes.push((path.clone(), AssetChangeKind::Removed));
+            }
+        }
+        self.files = files;
+        changes.sort_by(|a, b| a.0.cmp(&b.0));
+        changes
+            .into_iter()
+            .map(|(path, kind)| {
+                log::debug!("Asset {:?} {:?}", path, kind);
+                AssetChange {
+                    id: AssetId::from_path(&path),
+                    path,
+                    kind,
+                }
+            })
+            .collect()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::fs;
+
+    #[test]
+    fn detect_changes() {
+        let root = std::env::temp_dir().join(format!("sp_asset_watch_{}", std::process::id()));
+        let _ = fs::remove_dir_all(&root);
+        fs::create_dir_all(root.join("sounds")).unwrap();
+        fs::write(root.join("sounds/a.wav"), "a").unwrap();
+        fs::write(root.join("b.txt"), "b").unwrap();
+
+        let mut watcher = DirWatcher::from_archive(&FileArchive::Dir(root.clone())).unwrap();
+        assert!(watcher.scan().is_empty());
+
+        fs::write(root.join("sounds/a.wav"), "changed").unwrap();
+        fs::write(root.join("c.txt"), "c").unwrap();
+        fs::remove_file(root.join("b.txt")).unwrap();
+        let changes = watcher.scan();
+        let kinds: Vec<_> = changes.iter().map(|c| (c.path.clone(), c.kind)).collect();
+        assert_eq!(
+            kinds,
+            vec![
+                (PathBuf::from("b.txt"), AssetChangeKind::Removed),
+                (PathBuf::from("c.txt"), AssetChangeKind::Added),
+                (PathBuf::from("sounds/a.wav"), AssetChangeKind::Modified),
+            ]
+        );
+        assert_eq!(changes[2].id, AssetId::from_str("sounds/a.wav"));
+        assert!(watcher.scan().is_empty());
+
+        watcher.set_interval(Duration::from_secs(3600));
+        watcher.poll();
+        fs::write(root.join("d.txt"), "d").unwrap();
+        assert!(watcher.poll().is_empty());
+        assert_eq!(watcher.scan().len(), 1);
+        fs::remove_dir_all(&root).unwrap();
+    }
+}
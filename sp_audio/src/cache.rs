@@ -1,7 +1,29 @@
 use fyrox_sound::buffer::{DataSource, SoundBufferResource};
 use indexmap::IndexMap;
-use sp_asset::{archive::FileArchive, AssetId, AssetRef};
-use std::{io::Read, path::PathBuf};
+use sp_asset::{archive::FileArchive, AssetChange, AssetChangeKind, AssetId, AssetRef};
+use std::{
+    io::Read,
+    path::{Path, PathBuf},
+};
+
+fn load_sound(archive: &mut FileArchive, path: &Path) -> Option<(AssetId, SoundBufferResource)> {
+    let mut buf = Vec::new();
+    if let Err(err) = archive.open(path).and_then(|mut file| Ok(file.read_to_end(&mut buf)?)) {
+        log::warn!("Could not open {}: {}", path.as_os_str().to_string_lossy(), err);
+        return None;
+    }
+    match SoundBufferResource::new_generic(DataSource::from_memory(buf)) {
+        Ok(buffer) => {
+            let asset_ref = AssetRef::from_path(path);
+            log::debug!("Loaded sound {:?} ({})", &asset_ref.path, asset_ref.id.0);
+            Some((asset_ref.id, buffer))
+        }
+        Err(_err) => {
+            log::warn!("Could not read {}", path.as_os_str().to_string_lossy());
+            None
+        }
+    }
+}
 
 pub struct SoundCache {
     sounds: IndexMap<AssetId, SoundBufferResource>,
@@ -11,26 +33,34 @@ impl SoundCache {
     pub fn from_paths(archive: &mut FileArchive, paths: &[PathBuf]) -> SoundCache {
         let sounds = paths
             .iter()
-            .filter_map(|path| {
-                let mut file = archive.open(path).unwrap();
-                let mut buf = Vec::new();
-                file.read_to_end(&mut buf).unwrap();
-                match SoundBufferResource::new_generic(DataSource::from_memory(buf)) {
-                    Ok(buffer) => {
-                        let asset_ref = AssetRef::from_path(&path);
-                        log::debug!("Loaded sound {:?} ({})", &asset_ref.path, asset_ref.id.0);
-                        Some((asset_ref.id, buffer))
-                    }
-                    Err(_err) => {
-                        log::warn!("Could not read {}", path.as_os_str().to_string_lossy());
-                        None
-                    }
-                }
-            })
+            .filter_map(|path| load_sound(archive, path))
             .collect();
         Self { sounds }
     }
 
+    /// Reloads changed sounds in place, keeping the previous buffer if the
+    /// new file can't be read or was removed
+    pub fn apply_changes(&mut self, archive: &mut FileArchive, changes: &[AssetChange]) {
+        for change in changes.iter() {
+            match change.kind {
+                AssetChangeKind::Added | AssetChangeKind::Modified => {
+                    if let Some((id, buffer)) = load_sound(archive, &change.path) {
+                        self.sounds.insert(id, buffer);
+                    }
+                }
+                AssetChangeKind::Removed => {
+                    // Keep the last loaded buffer so sounds still referenced
+                    // by id keep playing until the file comes back
+                    log::debug!("Sound {:?} removed, keeping loaded buffer", change.path);
+                }
+            }
+        }
+    }
+
+    pub fn sound(&self, sound_id: AssetId) -> Option<&SoundBufferResource> {
+        self.sounds.get(&sound_id)
+    }
+
     pub fn get_sound(&self, sound_id: AssetId) -> &SoundBufferResource {
         self.sounds
             .get(&sound_id)
@@ -3,7 +3,7 @@ use std::{
     sync::{Arc, Mutex},
 };
 
-use sp_asset::archive::FileArchive;
+use sp_asset::{archive::FileArchive, AssetChange};
 use sp_sound::{AudioConfig, AudioListener, SoundEffect, AudioFrame};
 use crate::{AudioEngine, SoundCache};
 
@@ -28,6 +28,20 @@ impl AudioSystem {
         }
     }
 
+    /// Reloads sounds changed under the sounds directory, e.g. as reported
+    /// by a DirWatcher during development
+    pub fn reload_sounds(&mut self, assets: &Mutex<FileArchive>, changes: &[AssetChange]) {
+        let changes = changes
+            .iter()
+            .filter(|change| change.path.starts_with("sounds"))
+            .cloned()
+            .collect::<Vec<_>>();
+        if !changes.is_empty() {
+            let mut assets = assets.lock().unwrap();
+            self.sounds.apply_changes(&mut assets, &changes);
+        }
+    }
+
     fn update_from_config(&mut self) {
         if let Some(engine) = &mut self.engine {
             engine.set_master_gain(self.config.sound_volume as f32 / 10.0);
@@ -68,7 +82,10 @@ impl AudioSystem {
         self.enable_on_demand();
         if let Some(engine) = &mut self.engine {
             //log::info!("Playing sound {} at {:?}", play.sound_id.0, play.playback.position);
-            let sound = self.sounds.get_sound(play.sound_id).clone();
+            let Some(sound) = self.sounds.sound(play.sound_id).cloned() else {
+                log::warn!("Could not find sound {}", play.sound_id.0);
+                return;
+            };
             engine.play(
                 sound,
                 play.playback.position,
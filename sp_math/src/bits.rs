@@ -1 +1,416 @@
+use std::ops::RangeInclusive;
 
+use glam::{IVec2, IVec3, UVec2, UVec3};
+
+use crate::range::{IRange2, IRange3};
+
+// -------------------------------
+// Morton / Z-order
+
+/// Offset applied to signed 3D coordinates so they fit in 21 bits per axis
+const BIAS3: i32 = 1 << 20;
+const MASK3: u64 = 0x1f_ffff;
+
+fn spread2(x: u32) -> u64 {
+    let mut x = x as u64;
+    x = (x | (x << 16)) & 0x0000_ffff_0000_ffff;
+    x = (x | (x << 8)) & 0x00ff_00ff_00ff_00ff;
+    x = (x | (x << 4)) & 0x0f0f_0f0f_0f0f_0f0f;
+    x = (x | (x << 2)) & 0x3333_3333_3333_3333;
+    (x | (x << 1)) & 0x5555_5555_5555_5555
+}
+
+fn compact2(x: u64) -> u32 {
+    let mut x = x & 0x5555_5555_5555_5555;
+    x = (x | (x >> 1)) & 0x3333_3333_3333_3333;
+    x = (x | (x >> 2)) & 0x0f0f_0f0f_0f0f_0f0f;
+    x = (x | (x >> 4)) & 0x00ff_00ff_00ff_00ff;
+    x = (x | (x >> 8)) & 0x0000_ffff_0000_ffff;
+    ((x | (x >> 16)) & 0xffff_ffff) as u32
+}
+
+fn spread3(x: u32) -> u64 {
+    let mut x = x as u64 & MASK3;
+    x = (x | (x << 32)) & 0x001f_0000_0000_ffff;
+    x = (x | (x << 16)) & 0x001f_0000_ff00_00ff;
+    x = (x | (x << 8)) & 0x100f_00f0_0f00_f00f;
+    x = (x | (x << 4)) & 0x10c3_0c30_c30c_30c3;
+    (x | (x << 2)) & 0x1249_2492_4924_9249
+}
+
+fn compact3(x: u64) -> u32 {
+    let mut x = x & 0x1249_2492_4924_9249;
+    x = (x | (x >> 2)) & 0x10c3_0c30_c30c_30c3;
+    x = (x | (x >> 4)) & 0x100f_00f0_0f00_f00f;
+    x = (x | (x >> 8)) & 0x001f_0000_ff00_00ff;
+    x = (x | (x >> 16)) & 0x001f_0000_0000_ffff;
+    ((x | (x >> 32)) & MASK3) as u32
+}
+
+/// Interleaves the bits of x and y, with x in the lowest bit
+pub fn morton_encode2(x: u32, y: u32) -> u64 {
+    spread2(x) | (spread2(y) << 1)
+}
+
+pub fn morton_decode2(key: u64) -> (u32, u32) {
+    (compact2(key), compact2(key >> 1))
+}
+
+/// Morton key of a signed coordinate, with the sign bit flipped so that key
+/// order matches coordinate order on each axis
+pub fn morton_encode_ivec2(p: IVec2) -> u64 {
+    morton_encode2(flip_sign(p.x), flip_sign(p.y))
+}
+
+pub fn morton_decode_ivec2(key: u64) -> IVec2 {
+    let (x, y) = morton_decode2(key);
+    IVec2::new(unflip_sign(x), unflip_sign(y))
+}
+
+/// Interleaves the low 21 bits of each axis into a 63-bit key, with x in the
+/// lowest bit
+pub fn morton_encode_uvec3(p: UVec3) -> u64 {
+    spread3(p.x) | (spread3(p.y) << 1) | (spread3(p.z) << 2)
+}
+
+pub fn morton_decode_uvec3(key: u64) -> UVec3 {
+    UVec3::new(compact3(key), compact3(key >> 1), compact3(key >> 2))
+}
+
+/// Morton key of a signed coordinate in [-2^20, 2^20) on each axis, values
+/// outside that range wrap
+pub fn morton_encode_ivec3(p: IVec3) -> u64 {
+    morton_encode_uvec3(p.wrapping_add(IVec3::splat(BIAS3)).as_uvec3())
+}
+
+pub fn morton_decode_ivec3(key: u64) -> IVec3 {
+    morton_decode_uvec3(key).as_ivec3() - BIAS3
+}
+
+fn flip_sign(x: i32) -> u32 {
+    (x as u32) ^ 0x8000_0000
+}
+
+fn unflip_sign(x: u32) -> i32 {
+    (x ^ 0x8000_0000) as i32
+}
+
+/// Decomposes a range into the minimal sorted list of contiguous Morton key
+/// intervals covering exactly its cells, for querying Z-order keyed storage
+pub fn morton_ranges2(range: IRange2) -> Vec<RangeInclusive<u64>> {
+    if range.is_empty() {
+        return Vec::new();
+    }
+    let lo = [flip_sign(range.min.x) as u64, flip_sign(range.min.y) as u64];
+    let hi = [
+        flip_sign(range.max.x - 1) as u64 + 1,
+        flip_sign(range.max.y - 1) as u64 + 1,
+    ];
+    decompose(
+        lo,
+        hi,
+        |p| morton_encode2(p[0] as u32, p[1] as u32),
+        |key| {
+            let (x, y) = morton_decode2(key);
+            [x as u64, y as u64]
+        },
+    )
+}
+
+/// Decomposes the part of a range within [-2^20, 2^20) into sorted
+/// contiguous Morton key intervals, see [`morton_ranges2`]
+pub fn morton_ranges3(range: IRange3) -> Vec<RangeInclusive<u64>> {
+    let range = range & IRange3::new(IVec3::splat(-BIAS3), IVec3::splat(BIAS3));
+    if range.is_empty() {
+        return Vec::new();
+    }
+    let lo = (range.min + BIAS3).as_uvec3();
+    let hi = (range.max + BIAS3).as_uvec3();
+    decompose(
+        [lo.x as u64, lo.y as u64, lo.z as u64],
+        [hi.x as u64, hi.y as u64, hi.z as u64],
+        |p| morton_encode_uvec3(UVec3::new(p[0] as u32, p[1] as u32, p[2] as u32)),
+        |key| {
+            let p = morton_decode_uvec3(key);
+            [p.x as u64, p.y as u64, p.z as u64]
+        },
+    )
+}
+
+/// Cells of a range in increasing Morton key order
+pub fn morton_iter2(range: IRange2) -> impl Iterator<Item = IVec2> {
+    morton_ranges2(range)
+        .into_iter()
+        .flat_map(|keys| keys.map(morton_decode_ivec2))
+}
+
+/// Cells of the part of a range within [-2^20, 2^20) in increasing Morton key
+/// order
+pub fn morton_iter3(range: IRange3) -> impl Iterator<Item = IVec3> {
+    morton_ranges3(range)
+        .into_iter()
+        .flat_map(|keys| keys.map(morton_decode_ivec3))
+}
+
+/// Mask of the low bits of a key covered by an aligned block at level
+fn block_mask<const D: usize>(level: u32) -> u64 {
+    let bits = level * D as u32;
+    if bits == 0 {
+        0
+    } else {
+        u64::MAX >> (64 - bits)
+    }
+}
+
+fn decompose<const D: usize>(
+    lo: [u64; D],
+    hi: [u64; D],
+    encode: impl Fn([u64; D]) -> u64,
+    decode: impl Fn(u64) -> [u64; D],
+) -> Vec<RangeInclusive<u64>> {
+    let mut last = lo;
+    for (l, h) in last.iter_mut().zip(hi.iter()) {
+        *l = h - 1;
+    }
+    let key_min = encode(lo);
+    let key_max = encode(last);
+    // Smallest aligned block containing both corners
+    let differing = 64 - (key_min ^ key_max).leading_zeros();
+    let level = differing.div_ceil(D as u32);
+    let root = key_min & !block_mask::<D>(level);
+    let mut out = Vec::new();
+    split(root, level, &lo, &hi, &decode, &mut out);
+    out
+}
+
+fn split<const D: usize>(
+    key: u64,
+    level: u32,
+    lo: &[u64; D],
+    hi: &[u64; D],
+    decode: &impl Fn(u64) -> [u64; D],
+    out: &mut Vec<RangeInclusive<u64>>,
+) {
+    let corner = decode(key);
+    let size = 1u64 << level;
+    let mut inside = true;
+    for axis in 0..D {
+        let (min, max) = (corner[axis], corner[axis] + size);
+        if max <= lo[axis] || min >= hi[axis] {
+            return;
+        }
+        inside &= min >= lo[axis] && max <= hi[axis];
+    }
+    if inside {
+        let end = key + block_mask::<D>(level);
+        match out.last_mut() {
+            Some(prev) if prev.end().checked_add(1) == Some(key) => {
+                *prev = *prev.start()..=end;
+            }
+            _ => out.push(key..=end),
+        }
+        return;
+    }
+    let child_bits = (level - 1) * D as u32;
+    for child in 0..(1u64 << D) {
+        split(key + (child << child_bits), level - 1, lo, hi, decode, out);
+    }
+}
+
+// -------------------------------
+// Hilbert
+
+/// Converts coordinates to Skilling's transposed Hilbert index in place
+fn axes_to_transpose<const D: usize>(x: &mut [u64; D], order: u32) {
+    let m = 1u64 << (order - 1);
+    let mut q = m;
+    while q > 1 {
+        let p = q - 1;
+        for i in 0..D {
+            if x[i] & q != 0 {
+                x[0] ^= p;
+            } else {
+                let t = (x[0] ^ x[i]) & p;
+                x[0] ^= t;
+                x[i] ^= t;
+            }
+        }
+        q >>= 1;
+    }
+    for i in 1..D {
+        x[i] ^= x[i - 1];
+    }
+    let mut t = 0;
+    let mut q = m;
+    while q > 1 {
+        if x[D - 1] & q != 0 {
+            t ^= q - 1;
+        }
+        q >>= 1;
+    }
+    for v in x.iter_mut() {
+        *v ^= t;
+    }
+}
+
+fn transpose_to_axes<const D: usize>(x: &mut [u64; D], order: u32) {
+    let n = 2u64 << (order - 1);
+    let t = x[D - 1] >> 1;
+    for i in (1..D).rev() {
+        x[i] ^= x[i - 1];
+    }
+    x[0] ^= t;
+    let mut q = 2;
+    while q != n {
+        let p = q - 1;
+        for i in (0..D).rev() {
+            if x[i] & q != 0 {
+                x[0] ^= p;
+            } else {
+                let t = (x[0] ^ x[i]) & p;
+                x[0] ^= t;
+                x[i] ^= t;
+            }
+        }
+        q <<= 1;
+    }
+}
+
+fn hilbert_encode<const D: usize>(mut x: [u64; D], order: u32) -> u64 {
+    axes_to_transpose(&mut x, order);
+    let mut d = 0;
+    for bit in (0..order).rev() {
+        for v in x.iter() {
+            d = (d << 1) | ((v >> bit) & 1);
+        }
+    }
+    d
+}
+
+fn hilbert_decode<const D: usize>(d: u64, order: u32) -> [u64; D] {
+    let mut x = [0; D];
+    let mut shift = order * D as u32;
+    for bit in (0..order).rev() {
+        for v in x.iter_mut() {
+            shift -= 1;
+            *v |= ((d >> shift) & 1) << bit;
+        }
+    }
+    transpose_to_axes(&mut x, order);
+    x
+}
+
+/// Index along a 2D Hilbert curve covering 2^order cells per axis, for order
+/// in 1..=32. Consecutive indices are always adjacent cells
+pub fn hilbert_encode2(p: UVec2, order: u32) -> u64 {
+    assert!((1..=32).contains(&order));
+    hilbert_encode([p.x as u64, p.y as u64], order)
+}
+
+pub fn hilbert_decode2(d: u64, order: u32) -> UVec2 {
+    assert!((1..=32).contains(&order));
+    let [x, y] = hilbert_decode(d, order);
+    UVec2::new(x as u32, y as u32)
+}
+
+/// Index along a 3D Hilbert curve covering 2^order cells per axis, for order
+/// in 1..=21
+pub fn hilbert_encode3(p: UVec3, order: u32) -> u64 {
+    assert!((1..=21).contains(&order));
+    hilbert_encode([p.x as u64, p.y as u64, p.z as u64], order)
+}
+
+pub fn hilbert_decode3(d: u64, order: u32) -> UVec3 {
+    assert!((1..=21).contains(&order));
+    let [x, y, z] = hilbert_decode(d, order);
+    UVec3::new(x as u32, y as u32, z as u32)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::pcg::PcgRng;
+
+    #[test]
+    fn morton_round_trip() {
+        assert_eq!(morton_encode2(0b11, 0b01), 0b0111);
+        assert_eq!(morton_encode_uvec3(UVec3::new(1, 0, 1)), 0b101);
+        let mut rng = PcgRng::new(7, 0);
+        for _ in 0..1000 {
+            let (x, y) = (rng.next_u32(), rng.next_u32());
+            assert_eq!(morton_decode2(morton_encode2(x, y)), (x, y));
+            let p = IVec2::new(x as i32, y as i32);
+            assert_eq!(morton_decode_ivec2(morton_encode_ivec2(p)), p);
+            let u = UVec3::new(x, y, x ^ y) & UVec3::splat(MASK3 as u32);
+            assert_eq!(morton_decode_uvec3(morton_encode_uvec3(u)), u);
+            let s = u.as_ivec3() - BIAS3;
+            assert_eq!(morton_decode_ivec3(morton_encode_ivec3(s)), s);
+        }
+        assert!(morton_encode_ivec2(IVec2::new(-1, -1)) < morton_encode_ivec2(IVec2::ZERO));
+        // Wraps modulo 2^21 like the unsigned encoding
+        let wrapped = morton_decode_ivec3(morton_encode_ivec3(IVec3::splat(i32::MAX)));
+        assert_eq!(wrapped, IVec3::splat(-1));
+    }
+
+    #[test]
+    fn morton_ranges_cover_exactly() {
+        let range = IRange2::from_xy(-3, -2, 5, 4);
+        let ranges = morton_ranges2(range);
+        assert!(ranges.windows(2).all(|w| w[0].end() + 1 < *w[1].start()));
+        let mut keys: Vec<u64> = range.iter().map(morton_encode_ivec2).collect();
+        keys.sort_unstable();
+        let covered: Vec<u64> = ranges.into_iter().flatten().collect();
+        assert_eq!(covered, keys);
+
+        let cells: Vec<IVec2> = morton_iter2(range).collect();
+        let decoded: Vec<IVec2> = keys.into_iter().map(morton_decode_ivec2).collect();
+        assert_eq!(cells, decoded);
+
+        // An aligned block is a single interval
+        assert_eq!(morton_ranges2(IRange2::from_xy(0, 0, 8, 8)).len(), 1);
+        assert!(morton_ranges2(IRange2::ZERO).is_empty());
+    }
+
+    #[test]
+    fn morton_ranges3_cover_exactly() {
+        let range = IRange3::new(IVec3::new(-2, 1, -3), IVec3::new(3, 4, 2));
+        let mut keys: Vec<u64> = range.iter().map(morton_encode_ivec3).collect();
+        keys.sort_unstable();
+        let covered: Vec<u64> = morton_ranges3(range).into_iter().flatten().collect();
+        assert_eq!(covered, keys);
+        assert_eq!(morton_iter3(range).count(), 75);
+
+        // Only the part within the encodable range is covered
+        let edge = IRange3::new(IVec3::new(-5, 0, 0), IVec3::new(i32::MAX, 2, 2));
+        let inside = IRange3::new(IVec3::new(-5, 0, 0), IVec3::new(BIAS3, 2, 2));
+        assert_eq!(morton_ranges3(edge), morton_ranges3(inside));
+        let outside = IRange3::new(IVec3::splat(BIAS3), IVec3::splat(BIAS3 + 4));
+        assert!(morton_ranges3(outside).is_empty());
+    }
+
+    #[test]
+    fn hilbert_is_continuous() {
+        let order = 4;
+        let mut seen = vec![false; 256];
+        let mut prev = hilbert_decode2(0, order);
+        assert_eq!(prev, UVec2::ZERO);
+        for d in 0..256 {
+            let p = hilbert_decode2(d, order);
+            assert_eq!(hilbert_encode2(p, order), d);
+            assert!((p.as_ivec2() - prev.as_ivec2()).abs().element_sum() <= 1);
+            seen[(p.y * 16 + p.x) as usize] = true;
+            prev = p;
+        }
+        assert!(seen.iter().all(|&s| s));
+
+        let mut prev = hilbert_decode3(0, 3);
+        for d in 1..512 {
+            let p = hilbert_decode3(d, 3);
+            assert_eq!(hilbert_encode3(p, 3), d);
+            assert_eq!((p.as_ivec3() - prev.as_ivec3()).abs().element_sum(), 1);
+            prev = p;
+        }
+        let p = UVec2::new(u32::MAX, 12345);
+        assert_eq!(hilbert_decode2(hilbert_encode2(p, 32), 32), p);
+    }
+}
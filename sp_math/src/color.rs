@@ -15,7 +15,7 @@ fn srgba_to_linear(x: f32) -> f32 {
 fn linear_to_srgba(x: f32) -> f32 {
     if x > 0.0031308 {
         let a = 0.055;
-        (1.0 + a) * x.powf(-2.4) - a
+        (1.0 + a) * x.powf(1.0 / 2.4) - a
     } else {
         12.92 * x
     }
@@ -79,10 +79,69 @@ impl IRgba {
     pub const BLUE: Self = Self::from_u32(0x0000ffff);
     pub const MAGENTA: Self = Self::from_u32(0xff00ffff);
 
+    /// R is in high bits, A is in low bits
+    pub const fn to_u32(&self) -> u32 {
+        (self.r as u32) << 24 | (self.g as u32) << 16 | (self.b as u32) << 8 | self.a as u32
+    }
+
     pub fn to_array(&self) -> [u8; 4] {
         [self.r, self.g, self.b, self.a]
     }
 
+    /// Parses #rgb, #rgba, #rrggbb and #rrggbbaa
+    pub fn from_hex(s: &str) -> Option<Self> {
+        let hex = s.strip_prefix('#')?;
+        if !hex.bytes().all(|c| c.is_ascii_hexdigit()) {
+            return None;
+        }
+        let digit = |i: usize| u8::from_str_radix(&hex[i..i + 1], 16).ok();
+        let pair = |i: usize| u8::from_str_radix(&hex[i..i + 2], 16).ok();
+        match hex.len() {
+            3 | 4 => {
+                let alpha = if hex.len() == 4 { digit(3)? * 17 } else { 255 };
+                Some(Self::new(digit(0)? * 17, digit(1)? * 17, digit(2)? * 17, alpha))
+            }
+            6 | 8 => {
+                let alpha = if hex.len() == 8 { pair(6)? } else { 255 };
+                Some(Self::new(pair(0)?, pair(2)?, pair(4)?, alpha))
+            }
+            _ => None,
+        }
+    }
+
+    /// Formats as #rrggbb, or #rrggbbaa when not opaque
+    pub fn to_hex(&self) -> String {
+        if self.a == 255 {
+            format!("#{:02x}{:02x}{:02x}", self.r, self.g, self.b)
+        } else {
+            format!("#{:02x}{:02x}{:02x}{:02x}", self.r, self.g, self.b, self.a)
+        }
+    }
+
+    /// Looks up a CSS color name, ignoring case
+    pub fn from_name(name: &str) -> Option<Self> {
+        CSS_NAMES
+            .iter()
+            .find(|(n, _)| n.eq_ignore_ascii_case(name))
+            .map(|(_, c)| Self::from_u32(*c))
+    }
+
+    /// CSS name of this exact color, if it has one
+    pub fn name(&self) -> Option<&'static str> {
+        let x = self.to_u32();
+        CSS_NAMES.iter().find(|(_, c)| *c == x).map(|(n, _)| *n)
+    }
+
+    /// Parses a hex color or CSS color name
+    pub fn parse(s: &str) -> Option<Self> {
+        let s = s.trim();
+        if s.starts_with('#') {
+            Self::from_hex(s)
+        } else {
+            Self::from_name(s)
+        }
+    }
+
     pub fn to_rgba(&self) -> Rgba {
         Rgba::new(
             (self.r as f32) / 255.0,
@@ -190,6 +249,57 @@ impl Rgba {
         }
     }
 
+    /// Rounds and clamps each channel, unlike to_irgba which truncates
+    pub fn to_irgba_rounded(&self) -> IRgba {
+        let c = |x: f32| (x.clamp(0.0, 1.0) * 255.0).round() as u8;
+        IRgba::new(c(self.r), c(self.g), c(self.b), c(self.a))
+    }
+
+    pub fn to_hsla(&self) -> Hsla {
+        self.to_hsva().to_hsla()
+    }
+
+    /// Treats the color as sRGB encoded
+    #[allow(clippy::excessive_precision)]
+    pub fn to_oklab(&self) -> Oklab {
+        let c = self.srgba_to_linear();
+        let l = (0.4122214708 * c.r + 0.5363325363 * c.g + 0.0514459929 * c.b).cbrt();
+        let m = (0.2119034982 * c.r + 0.6806995451 * c.g + 0.1073969566 * c.b).cbrt();
+        let s = (0.0883024619 * c.r + 0.2817188376 * c.g + 0.6299787005 * c.b).cbrt();
+        Oklab::new(
+            0.2104542553 * l + 0.7936177850 * m - 0.0040720468 * s,
+            1.9779984951 * l - 2.4285922050 * m + 0.4505937099 * s,
+            0.0259040371 * l + 0.7827717662 * m - 0.8086757660 * s,
+            self.a,
+        )
+    }
+
+    pub fn to_oklch(&self) -> Oklch {
+        self.to_oklab().to_oklch()
+    }
+
+    /// Parses a hex color or CSS color name
+    pub fn parse(s: &str) -> Option<Self> {
+        IRgba::parse(s).map(|c| c.to_rgba())
+    }
+
+    pub fn to_hex(&self) -> String {
+        self.to_irgba_rounded().to_hex()
+    }
+
+    /// WCAG relative luminance of the sRGB encoded color
+    pub fn relative_luminance(&self) -> f32 {
+        let c = self.srgba_to_linear();
+        0.2126 * c.r + 0.7152 * c.g + 0.0722 * c.b
+    }
+
+    /// WCAG contrast ratio in [1, 21], ignoring alpha
+    pub fn contrast_ratio(&self, rhs: Self) -> f32 {
+        let a = self.relative_luminance();
+        let b = rhs.relative_luminance();
+        (a.max(b) + 0.05) / (a.min(b) + 0.05)
+    }
+
     pub fn with_alpha(&self, alpha: f32) -> Self {
         Self { a: alpha, ..*self }
     }
@@ -214,6 +324,25 @@ impl Rgba {
         Self::from_vec4(self.to_vec4().lerp(rhs.to_vec4(), t))
     }
 
+    /// Perceptually uniform interpolation through OKLab
+    pub fn lerp_oklab(&self, rhs: Self, t: f32) -> Self {
+        self.to_oklab().lerp(rhs.to_oklab(), t).to_rgba()
+    }
+
+    pub fn lerp_in(&self, rhs: Self, t: f32, space: ColorSpace) -> Self {
+        match space {
+            ColorSpace::Srgb => self.lerp(rhs, t),
+            ColorSpace::LinearRgb => self
+                .srgba_to_linear()
+                .lerp(rhs.srgba_to_linear(), t)
+                .linear_to_srgba(),
+            ColorSpace::Hsv => self.to_hsva().lerp(rhs.to_hsva(), t).to_rgba(),
+            ColorSpace::Hsl => self.to_hsla().lerp(rhs.to_hsla(), t).to_rgba(),
+            ColorSpace::Oklab => self.lerp_oklab(rhs, t),
+            ColorSpace::Oklch => self.to_oklch().lerp(rhs.to_oklch(), t).to_rgba(),
+        }
+    }
+
     pub fn srgba_to_linear(&self) -> Self {
         Self {
             r: srgba_to_linear(self.r),
@@ -309,6 +438,13 @@ impl Hsva {
         }
     }
 
+    pub fn to_hsla(&self) -> Hsla {
+        let l = self.v * (1.0 - self.s / 2.0);
+        let m = l.min(1.0 - l);
+        let s = if m > 1e-7 { (self.v - l) / m } else { 0.0 };
+        Hsla::new(self.h, s, l, self.a)
+    }
+
     pub fn add_hue(&self, h: f32) -> Self {
         Self { h: self.h + h, ..*self }
     }
@@ -358,6 +494,428 @@ impl std::ops::Mul<f32> for Hsva {
     }
 }
 
+/// Hue is in degrees: [0, 360]
+#[derive(Default, Debug, Clone, Copy, PartialEq)]
+pub struct Hsla {
+    pub h: f32,
+    pub s: f32,
+    pub l: f32,
+    pub a: f32,
+}
+
+impl Hsla {
+    pub const fn new(h: f32, s: f32, l: f32, a: f32) -> Self {
+        Self { h, s, l, a }
+    }
+
+    pub fn hsl(h: f32, s: f32, l: f32) -> Self {
+        Self::new(h, s, l, 1.0)
+    }
+
+    pub fn to_hsva(&self) -> Hsva {
+        let v = self.l + self.s * self.l.min(1.0 - self.l);
+        let s = if v > 1e-7 { 2.0 * (1.0 - self.l / v) } else { 0.0 };
+        Hsva::new(self.h, s, v, self.a)
+    }
+
+    pub fn to_rgba(&self) -> Rgba {
+        self.to_hsva().to_rgba()
+    }
+
+    pub fn lerp(&self, rhs: Self, t: f32) -> Self {
+        Self {
+            h: lerp_hue(self.h, rhs.h, t),
+            s: crate::interp::lerp(self.s, rhs.s, t),
+            l: crate::interp::lerp(self.l, rhs.l, t),
+            a: crate::interp::lerp(self.a, rhs.a, t),
+        }
+    }
+}
+
+/// OKLab color, where l is perceived lightness in [0, 1]:
+/// https://bottosson.github.io/posts/oklab/
+#[derive(Default, Debug, Clone, Copy, PartialEq)]
+pub struct Oklab {
+    pub l: f32,
+    pub a: f32,
+    pub b: f32,
+    pub alpha: f32,
+}
+
+impl Oklab {
+    pub const fn new(l: f32, a: f32, b: f32, alpha: f32) -> Self {
+        Self { l, a, b, alpha }
+    }
+
+    /// Returns an sRGB encoded color, which may be out of gamut
+    #[allow(clippy::excessive_precision)]
+    pub fn to_rgba(&self) -> Rgba {
+        let l = self.l + 0.3963377774 * self.a + 0.2158037573 * self.b;
+        let m = self.l - 0.1055613458 * self.a - 0.0638541728 * self.b;
+        let s = self.l - 0.0894841775 * self.a - 1.2914855480 * self.b;
+        let (l, m, s) = (l * l * l, m * m * m, s * s * s);
+        Rgba::new(
+            4.0767416621 * l - 3.3077115913 * m + 0.2309699292 * s,
+            -1.2684380046 * l + 2.6097574011 * m - 0.3413193965 * s,
+            -0.0041960863 * l - 0.7034186147 * m + 1.7076147010 * s,
+            self.alpha,
+        )
+        .linear_to_srgba()
+    }
+
+    pub fn to_oklch(&self) -> Oklch {
+        let h = self.b.atan2(self.a).to_degrees();
+        Oklch::new(
+            self.l,
+            self.a.hypot(self.b),
+            if h < 0.0 { h + 360.0 } else { h },
+            self.alpha,
+        )
+    }
+
+    pub fn distance(&self, rhs: Self) -> f32 {
+        let (dl, da, db) = (self.l - rhs.l, self.a - rhs.a, self.b - rhs.b);
+        (dl * dl + da * da + db * db).sqrt()
+    }
+
+    pub fn lerp(&self, rhs: Self, t: f32) -> Self {
+        Self {
+            l: crate::interp::lerp(self.l, rhs.l, t),
+            a: crate::interp::lerp(self.a, rhs.a, t),
+            b: crate::interp::lerp(self.b, rhs.b, t),
+            alpha: crate::interp::lerp(self.alpha, rhs.alpha, t),
+        }
+    }
+}
+
+/// Polar form of OKLab with hue in degrees: [0, 360]
+#[derive(Default, Debug, Clone, Copy, PartialEq)]
+pub struct Oklch {
+    pub l: f32,
+    pub c: f32,
+    pub h: f32,
+    pub alpha: f32,
+}
+
+impl Oklch {
+    pub const fn new(l: f32, c: f32, h: f32, alpha: f32) -> Self {
+        Self { l, c, h, alpha }
+    }
+
+    pub fn to_oklab(&self) -> Oklab {
+        let (sin, cos) = self.h.to_radians().sin_cos();
+        Oklab::new(self.l, self.c * cos, self.c * sin, self.alpha)
+    }
+
+    pub fn to_rgba(&self) -> Rgba {
+        self.to_oklab().to_rgba()
+    }
+
+    /// Grays have no meaningful hue, so they take the hue of the other color
+    pub fn lerp(&self, rhs: Self, t: f32) -> Self {
+        let gray = 1e-4;
+        let h0 = if self.c < gray { rhs.h } else { self.h };
+        let h1 = if rhs.c < gray { h0 } else { rhs.h };
+        let h0 = if self.c < gray { h1 } else { h0 };
+        Self {
+            l: crate::interp::lerp(self.l, rhs.l, t),
+            c: crate::interp::lerp(self.c, rhs.c, t),
+            h: lerp_hue(h0, h1, t),
+            alpha: crate::interp::lerp(self.alpha, rhs.alpha, t),
+        }
+    }
+}
+
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum ColorSpace {
+    #[default]
+    Srgb,
+    LinearRgb,
+    Hsv,
+    Hsl,
+    Oklab,
+    Oklch,
+}
+
+/// Colors at positions in [0, 1], interpolated in the chosen color space
+#[derive(Debug, Default, Clone, PartialEq)]
+pub struct Gradient {
+    pub space: ColorSpace,
+    stops: Vec<(f32, Rgba)>,
+}
+
+impl Gradient {
+    pub fn new(space: ColorSpace) -> Self {
+        Self {
+            space,
+            stops: Vec::new(),
+        }
+    }
+
+    /// Spaces colors evenly from 0 to 1
+    pub fn from_colors(space: ColorSpace, colors: &[Rgba]) -> Self {
+        let mut gradient = Self::new(space);
+        let last = colors.len().saturating_sub(1).max(1) as f32;
+        for (i, color) in colors.iter().enumerate() {
+            gradient.add_stop(i as f32 / last, *color);
+        }
+        gradient
+    }
+
+    /// Stops at the same position as an existing one are placed after it,
+    /// which allows hard edges.
+    pub fn add_stop(&mut self, pos: f32, color: Rgba) {
+        let i = self.stops.partition_point(|(p, _)| *p <= pos);
+        self.stops.insert(i, (pos, color));
+    }
+
+    pub fn with_stop(mut self, pos: f32, color: Rgba) -> Self {
+        self.add_stop(pos, color);
+        self
+    }
+
+    pub fn stops(&self) -> &[(f32, Rgba)] {
+        &self.stops
+    }
+
+    /// Clamps to the end colors outside the stops. Empty gradients are
+    /// transparent.
+    pub fn sample(&self, t: f32) -> Rgba {
+        let (first, last) = match (self.stops.first(), self.stops.last()) {
+            (Some(first), Some(last)) => (first, last),
+            _ => return Rgba::ZERO,
+        };
+        if t < first.0 {
+            return first.1;
+        }
+        if t >= last.0 {
+            return last.1;
+        }
+        let i = self.stops.partition_point(|(p, _)| *p <= t);
+        let (p0, c0) = self.stops[i - 1];
+        let (p1, c1) = self.stops[i];
+        c0.lerp_in(c1, (t - p0) / (p1 - p0), self.space)
+    }
+}
+
+/// Index of the perceptually closest palette color, comparing in OKLab
+pub fn nearest_in_palette(palette: &[Rgba], color: Rgba) -> Option<usize> {
+    let lab = color.to_oklab();
+    palette
+        .iter()
+        .map(|c| c.to_oklab().distance(lab))
+        .enumerate()
+        .min_by(|a, b| a.1.total_cmp(&b.1))
+        .map(|(i, _)| i)
+}
+
+/// Reduces colors to at most max_colors by repeatedly splitting the box with
+/// the widest RGB channel at its median. Each box yields its average color.
+pub fn median_cut(colors: &[IRgba], max_colors: usize) -> Vec<IRgba> {
+    let channel = |c: &IRgba, i: usize| c.to_array()[i];
+    let widest = |colors: &[IRgba]| {
+        (0..3)
+            .map(|i| {
+                let min = colors.iter().map(|c| channel(c, i)).min().unwrap_or(0);
+                let max = colors.iter().map(|c| channel(c, i)).max().unwrap_or(0);
+                (max - min, i)
+            })
+            .max_by_key(|(range, i)| (*range, std::cmp::Reverse(*i)))
+            .unwrap_or((0, 0))
+    };
+    let mut boxes: Vec<Vec<IRgba>> = Vec::new();
+    if !colors.is_empty() && max_colors > 0 {
+        boxes.push(colors.to_vec());
+    }
+    while boxes.len() < max_colors {
+        // First box with the largest range, so ties split deterministically
+        let (bi, (range, ci)) = match boxes
+            .iter()
+            .map(|b| widest(b))
+            .enumerate()
+            .max_by_key(|(i, (range, _))| (*range, std::cmp::Reverse(*i)))
+        {
+            Some(best) => best,
+            None => break,
+        };
+        if range == 0 {
+            break;
+        }
+        let mut b = std::mem::take(&mut boxes[bi]);
+        b.sort_by_key(|c| (channel(c, ci), c.to_u32()));
+        let upper = b.split_off(b.len() / 2);
+        boxes[bi] = b;
+        boxes.push(upper);
+    }
+    boxes
+        .iter()
+        .map(|b| {
+            let mut sum = [0u64; 4];
+            for c in b.iter() {
+                for (s, x) in sum.iter_mut().zip(c.to_array()) {
+                    *s += x as u64;
+                }
+            }
+            let n = b.len() as u64;
+            let avg = |s: u64| ((s + n / 2) / n) as u8;
+            IRgba::new(avg(sum[0]), avg(sum[1]), avg(sum[2]), avg(sum[3]))
+        })
+        .collect()
+}
+
+/// CSS color names in RRGGBBAA form
+const CSS_NAMES: &[(&str, u32)] = &[
+    ("aliceblue", 0xf0f8ffff),
+    ("antiquewhite", 0xfaebd7ff),
+    ("aqua", 0x00ffffff),
+    ("aquamarine", 0x7fffd4ff),
+    ("azure", 0xf0ffffff),
+    ("beige", 0xf5f5dcff),
+    ("bisque", 0xffe4c4ff),
+    ("black", 0x000000ff),
+    ("blanchedalmond", 0xffebcdff),
+    ("blue", 0x0000ffff),
+    ("blueviolet", 0x8a2be2ff),
+    ("brown", 0xa52a2aff),
+    ("burlywood", 0xdeb887ff),
+    ("cadetblue", 0x5f9ea0ff),
+    ("chartreuse", 0x7fff00ff),
+    ("chocolate", 0xd2691eff),
+    ("coral", 0xff7f50ff),
+    ("cornflowerblue", 0x6495edff),
+    ("cornsilk", 0xfff8dcff),
+    ("crimson", 0xdc143cff),
+    ("cyan", 0x00ffffff),
+    ("darkblue", 0x00008bff),
+    ("darkcyan", 0x008b8bff),
+    ("darkgoldenrod", 0xb8860bff),
+    ("darkgray", 0xa9a9a9ff),
+    ("darkgreen", 0x006400ff),
+    ("darkgrey", 0xa9a9a9ff),
+    ("darkkhaki", 0xbdb76bff),
+    ("darkmagenta", 0x8b008bff),
+    ("darkolivegreen", 0x556b2fff),
+    ("darkorange", 0xff8c00ff),
+    ("darkorchid", 0x9932ccff),
+    ("darkred", 0x8b0000ff),
+    ("darksalmon", 0xe9967aff),
+    ("darkseagreen", 0x8fbc8fff),
+    ("darkslateblue", 0x483d8bff),
+    ("darkslategray", 0x2f4f4fff),
+    ("darkslategrey", 0x2f4f4fff),
+    ("darkturquoise", 0x00ced1ff),
+    ("darkviolet", 0x9400d3ff),
+    ("deeppink", 0xff1493ff),
+    ("deepskyblue", 0x00bfffff),
+    ("dimgray", 0x696969ff),
+    ("dimgrey", 0x696969ff),
+    ("dodgerblue", 0x1e90ffff),
+    ("firebrick", 0xb22222ff),
+    ("floralwhite", 0xfffaf0ff),
+    ("forestgreen", 0x228b22ff),
+    ("fuchsia", 0xff00ffff),
+    ("gainsboro", 0xdcdcdcff),
+    ("ghostwhite", 0xf8f8ffff),
+    ("gold", 0xffd700ff),
+    ("goldenrod", 0xdaa520ff),
+    ("gray", 0x808080ff),
+    ("green", 0x008000ff),
+    ("greenyellow", 0xadff2fff),
+    ("grey", 0x808080ff),
+    ("honeydew", 0xf0fff0ff),
+    ("hotpink", 0xff69b4ff),
+    ("indianred", 0xcd5c5cff),
+    ("indigo", 0x4b0082ff),
+    ("ivory", 0xfffff0ff),
+    ("khaki", 0xf0e68cff),
+    ("lavender", 0xe6e6faff),
+    ("lavenderblush", 0xfff0f5ff),
+    ("lawngreen", 0x7cfc00ff),
+    ("lemonchiffon", 0xfffacdff),
+    ("lightblue", 0xadd8e6ff),
+    ("lightcoral", 0xf08080ff),
+    ("lightcyan", 0xe0ffffff),
+    ("lightgoldenrodyellow", 0xfafad2ff),
+    ("lightgray", 0xd3d3d3ff),
+    ("lightgreen", 0x90ee90ff),
+    ("lightgrey", 0xd3d3d3ff),
+    ("lightpink", 0xffb6c1ff),
+    ("lightsalmon", 0xffa07aff),
+    ("lightseagreen", 0x20b2aaff),
+    ("lightskyblue", 0x87cefaff),
+    ("lightslategray", 0x778899ff),
+    ("lightslategrey", 0x778899ff),
+    ("lightsteelblue", 0xb0c4deff),
+    ("lightyellow", 0xffffe0ff),
+    ("lime", 0x00ff00ff),
+    ("limegreen", 0x32cd32ff),
+    ("linen", 0xfaf0e6ff),
+    ("magenta", 0xff00ffff),
+    ("maroon", 0x800000ff),
+    ("mediumaquamarine", 0x66cdaaff),
+    ("mediumblue", 0x0000cdff),
+    ("mediumorchid", 0xba55d3ff),
+    ("mediumpurple", 0x9370dbff),
+    ("mediumseagreen", 0x3cb371ff),
+    ("mediumslateblue", 0x7b68eeff),
+    ("mediumspringgreen", 0x00fa9aff),
+    ("mediumturquoise", 0x48d1ccff),
+    ("mediumvioletred", 0xc71585ff),
+    ("midnightblue", 0x191970ff),
+    ("mintcream", 0xf5fffaff),
+    ("mistyrose", 0xffe4e1ff),
+    ("moccasin", 0xffe4b5ff),
+    ("navajowhite", 0xffdeadff),
+    ("navy", 0x000080ff),
+    ("oldlace", 0xfdf5e6ff),
+    ("olive", 0x808000ff),
+    ("olivedrab", 0x6b8e23ff),
+    ("orange", 0xffa500ff),
+    ("orangered", 0xff4500ff),
+    ("orchid", 0xda70d6ff),
+    ("palegoldenrod", 0xeee8aaff),
+    ("palegreen", 0x98fb98ff),
+    ("paleturquoise", 0xafeeeeff),
+    ("palevioletred", 0xdb7093ff),
+    ("papayawhip", 0xffefd5ff),
+    ("peachpuff", 0xffdab9ff),
+    ("peru", 0xcd853fff),
+    ("pink", 0xffc0cbff),
+    ("plum", 0xdda0ddff),
+    ("powderblue", 0xb0e0e6ff),
+    ("purple", 0x800080ff),
+    ("rebeccapurple", 0x663399ff),
+    ("red", 0xff0000ff),
+    ("rosybrown", 0xbc8f8fff),
+    ("royalblue", 0x4169e1ff),
+    ("saddlebrown", 0x8b4513ff),
+    ("salmon", 0xfa8072ff),
+    ("sandybrown", 0xf4a460ff),
+    ("seagreen", 0x2e8b57ff),
+    ("seashell", 0xfff5eeff),
+    ("sienna", 0xa0522dff),
+    ("silver", 0xc0c0c0ff),
+    ("skyblue", 0x87ceebff),
+    ("slateblue", 0x6a5acdff),
+    ("slategray", 0x708090ff),
+    ("slategrey", 0x708090ff),
+    ("snow", 0xfffafaff),
+    ("springgreen", 0x00ff7fff),
+    ("steelblue", 0x4682b4ff),
+    ("tan", 0xd2b48cff),
+    ("teal", 0x008080ff),
+    ("thistle", 0xd8bfd8ff),
+    ("tomato", 0xff6347ff),
+    ("transparent", 0x00000000),
+    ("turquoise", 0x40e0d0ff),
+    ("violet", 0xee82eeff),
+    ("wheat", 0xf5deb3ff),
+    ("white", 0xffffffff),
+    ("whitesmoke", 0xf5f5f5ff),
+    ("yellow", 0xffff00ff),
+    ("yellowgreen", 0x9acd32ff),
+];
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -374,6 +932,14 @@ mod tests {
         assert_eq!(Rgba::RED.to_hsva().lerp(Rgba::MAGENTA.to_hsva(), 0.5).h, 330.0);
     }
 
+    #[test]
+    fn linear_to_srgba_known_values() {
+        let c = Rgba::lum(0.5).linear_to_srgba();
+        assert!((c.r - 0.7354).abs() < 1e-4, "{c:?}");
+        assert!((Rgba::lum(0.5).srgba_to_linear().r - 0.2140).abs() < 1e-4);
+        assert!((Rgba::WHITE.linear_to_srgba().r - 1.0).abs() < 1e-6);
+    }
+
     #[test]
     fn convert_hsva() {
         test_roundtrip(Rgba::BLACK);
@@ -383,4 +949,119 @@ mod tests {
         assert_eq!(Rgba::GREEN.to_hsva(), Hsva::new(120.0, 1.0, 1.0, 1.0));
         assert_eq!(Rgba::BLUE.to_hsva(), Hsva::new(240.0, 1.0, 1.0, 1.0));
     }
+
+    fn assert_close(a: Rgba, b: Rgba) {
+        assert!((a.to_vec4() - b.to_vec4()).abs().max_element() < 1e-3, "{a:?} != {b:?}");
+    }
+
+    #[test]
+    fn srgb_linear_roundtrip() {
+        for i in 0..=10 {
+            let c = Rgba::lum(i as f32 / 10.0);
+            assert_close(c.srgba_to_linear().linear_to_srgba(), c);
+        }
+    }
+
+    #[test]
+    fn convert_hsla() {
+        assert_eq!(Rgba::RED.to_hsla(), Hsla::new(0.0, 1.0, 0.5, 1.0));
+        assert_eq!(Rgba::WHITE.to_hsla(), Hsla::new(0.0, 0.0, 1.0, 1.0));
+        let c = Rgba::new(0.2, 0.4, 0.9, 0.5);
+        assert_close(c.to_hsla().to_rgba(), c);
+    }
+
+    #[test]
+    fn convert_oklab() {
+        // Reference values from https://bottosson.github.io/posts/oklab/
+        let white = Rgba::WHITE.to_oklab();
+        assert!((white.l - 1.0).abs() < 1e-3 && white.a.abs() < 1e-3 && white.b.abs() < 1e-3);
+        let red = Rgba::RED.to_oklch();
+        assert!((red.l - 0.628).abs() < 1e-3);
+        assert!((red.c - 0.2577).abs() < 1e-3);
+        assert!((red.h - 29.23).abs() < 0.1);
+        for c in [Rgba::RED, Rgba::CYAN, Rgba::new(0.3, 0.6, 0.1, 0.25)] {
+            assert_close(c.to_oklab().to_rgba(), c);
+            assert_close(c.to_oklch().to_oklab().to_rgba(), c);
+        }
+    }
+
+    #[test]
+    fn perceptual_lerp() {
+        let mid = Rgba::BLACK.lerp_oklab(Rgba::WHITE, 0.5);
+        assert!((mid.to_oklab().l - 0.5).abs() < 1e-3);
+        assert!(mid.r > 0.38 && mid.r < 0.40);
+        // Gray keeps the hue of the saturated end
+        let c = Rgba::WHITE.lerp_in(Rgba::BLUE, 0.5, ColorSpace::Oklch).to_oklch();
+        assert!((c.h - Rgba::BLUE.to_oklch().h).abs() < 0.5);
+    }
+
+    #[test]
+    fn gradient_stops() {
+        let g = Gradient::new(ColorSpace::Srgb)
+            .with_stop(1.0, Rgba::BLUE)
+            .with_stop(0.0, Rgba::RED)
+            .with_stop(0.5, Rgba::GREEN)
+            .with_stop(0.5, Rgba::WHITE);
+        assert_eq!(g.sample(-1.0), Rgba::RED);
+        assert_eq!(g.sample(0.25), Rgba::new(0.5, 0.5, 0.0, 1.0));
+        assert_eq!(g.sample(0.5), Rgba::WHITE);
+        assert_eq!(g.sample(0.75), Rgba::new(0.5, 0.5, 1.0, 1.0));
+        assert_eq!(g.sample(2.0), Rgba::BLUE);
+        assert_eq!(Gradient::default().sample(0.5), Rgba::ZERO);
+        let hsv = Gradient::from_colors(ColorSpace::Hsv, &[Rgba::RED, Rgba::GREEN]);
+        assert_close(hsv.sample(0.5), Rgba::YELLOW);
+    }
+
+    #[test]
+    fn parse_and_format() {
+        assert_eq!(IRgba::parse("#ff8000"), Some(IRgba::rgb(255, 128, 0)));
+        assert_eq!(IRgba::parse(" #FF800080 "), Some(IRgba::new(255, 128, 0, 128)));
+        assert_eq!(IRgba::parse("#f80"), Some(IRgba::rgb(255, 136, 0)));
+        assert_eq!(IRgba::parse("#f808"), Some(IRgba::new(255, 136, 0, 136)));
+        assert_eq!(IRgba::parse("RebeccaPurple"), Some(IRgba::rgb(0x66, 0x33, 0x99)));
+        assert_eq!(IRgba::parse("#ff80"), Some(IRgba::new(255, 255, 136, 0)));
+        assert_eq!(IRgba::parse("#ff800"), None);
+        assert_eq!(IRgba::parse("#gg8000"), None);
+        assert_eq!(IRgba::parse("#+f8000"), None);
+        assert_eq!(IRgba::parse("notacolor"), None);
+        assert_eq!(IRgba::rgb(255, 128, 0).to_hex(), "#ff8000");
+        assert_eq!(IRgba::new(1, 2, 3, 4).to_hex(), "#01020304");
+        assert_eq!(Rgba::new(1.0, 0.5, 0.0, 1.0).to_hex(), "#ff8000");
+        assert_eq!(IRgba::rgb(0, 128, 128).name(), Some("teal"));
+        assert_eq!(IRgba::rgb(1, 128, 128).name(), None);
+        for (name, _) in CSS_NAMES.iter() {
+            let c = IRgba::from_name(name).unwrap();
+            assert_eq!(IRgba::from_hex(&c.to_hex()), Some(c));
+        }
+    }
+
+    #[test]
+    fn wcag_contrast() {
+        assert!((Rgba::BLACK.contrast_ratio(Rgba::WHITE) - 21.0).abs() < 1e-3);
+        assert_eq!(Rgba::RED.contrast_ratio(Rgba::RED), 1.0);
+        // #777777 on white is just under the 4.5 AA threshold
+        let gray = Rgba::parse("#777777").unwrap();
+        assert!((gray.contrast_ratio(Rgba::WHITE) - 4.48).abs() < 0.01);
+    }
+
+    #[test]
+    fn palette_quantization() {
+        let palette = [Rgba::BLACK, Rgba::WHITE, Rgba::RED, Rgba::BLUE];
+        assert_eq!(nearest_in_palette(&palette, Rgba::lum(0.2)), Some(0));
+        assert_eq!(nearest_in_palette(&palette, Rgba::rgb(0.9, 0.2, 0.1)), Some(2));
+        assert_eq!(nearest_in_palette(&[], Rgba::RED), None);
+
+        let mut pixels = Vec::new();
+        for i in 0..16 {
+            pixels.push(IRgba::rgb(250 - i, 0, 0));
+            pixels.push(IRgba::rgb(0, 0, 200 + i));
+        }
+        let colors = median_cut(&pixels, 2);
+        assert_eq!(colors.len(), 2);
+        assert!(colors.contains(&IRgba::rgb(0, 0, 208)));
+        assert!(colors.contains(&IRgba::rgb(243, 0, 0)));
+        assert_eq!(median_cut(&pixels, 64).len(), 32);
+        assert_eq!(median_cut(&[IRgba::RED; 5], 4), vec![IRgba::RED]);
+        assert!(median_cut(&[], 4).is_empty());
+    }
 }
@@ -0,0 +1,313 @@
+// Iso-contours of sampled scalar fields. Samples at or above the iso value
+// are inside. Contours follow the polygon module conventions: outlines are
+// counter-clockwise and holes clockwise. Triangles are counter-clockwise
+// when seen from outside, so their normals point away from the inside.
+
+use std::collections::{HashMap, HashSet};
+
+use glam::{IVec2, IVec3, Vec2, Vec3};
+
+use crate::range::{IRange2, IRange3};
+
+/// Pairs each inside to outside crossing with the outside to inside crossing
+/// that ends its segment, walking the corners counter-clockwise. With four
+/// crossings the saddle is resolved by whether the center is inside.
+fn face_segments(inside: [bool; 4], center_inside: bool, mut emit: impl FnMut(usize, usize)) {
+    let mut crossings = [(0, false); 4];
+    let mut count = 0;
+    for i in 0..4 {
+        if inside[i] != inside[(i + 1) % 4] {
+            crossings[count] = (i, inside[i]);
+            count += 1;
+        }
+    }
+    for k in 0..count {
+        let (edge, leaving) = crossings[k];
+        if !leaving {
+            continue;
+        }
+        let partner = if count == 4 && !center_inside {
+            (k + count - 1) % count
+        } else {
+            (k + 1) % count
+        };
+        emit(edge, crossings[partner].0);
+    }
+}
+
+/// Closed contour lines of a field sampled at every point of bounds. Points
+/// outside bounds count as outside, so contours touching the border are
+/// closed along the outermost samples.
+pub fn marching_squares(bounds: IRange2, iso: f32, sample: impl Fn(IVec2) -> f32) -> Vec<Vec<Vec2>> {
+    if bounds.is_empty() {
+        return Vec::new();
+    }
+    let values: Vec<f32> = bounds.iter().map(sample).collect();
+    let value = |p: IVec2| {
+        bounds.contains(p).then(|| {
+            let d = p - bounds.min;
+            values[(d.y * bounds.size().x + d.x) as usize]
+        })
+    };
+    let inside = |p: IVec2| value(p).is_some_and(|v| v >= iso);
+
+    // Edges are keyed by their lower point and axis
+    type Edge = (IVec2, u8);
+    let edge_key = |a: IVec2, b: IVec2| -> Edge { (a.min(b), if a.y == b.y { 0 } else { 1 }) };
+    let edge_point = |(p, axis): Edge| {
+        let q = p + if axis == 0 { IVec2::X } else { IVec2::Y };
+        match (value(p), value(q)) {
+            (Some(a), Some(b)) => p.as_vec2().lerp(q.as_vec2(), ((iso - a) / (b - a)).clamp(0.0, 1.0)),
+            (Some(_), None) => p.as_vec2(),
+            _ => q.as_vec2(),
+        }
+    };
+
+    let mut segments: Vec<(Edge, Edge)> = Vec::new();
+    for cell in IRange2::new(bounds.min - IVec2::ONE, bounds.max).iter() {
+        let corners = [cell, cell + IVec2::X, cell + IVec2::ONE, cell + IVec2::Y];
+        let ins = corners.map(inside);
+        if ins.iter().all(|i| *i == ins[0]) {
+            continue;
+        }
+        let center_inside = corners
+            .iter()
+            .map(|p| value(*p))
+            .sum::<Option<f32>>()
+            .is_some_and(|sum| sum * 0.25 >= iso);
+        face_segments(ins, center_inside, |from, to| {
+            segments.push((
+                edge_key(corners[from], corners[(from + 1) % 4]),
+                edge_key(corners[to], corners[(to + 1) % 4]),
+            ));
+        });
+    }
+
+    let next: HashMap<Edge, Edge> = segments.iter().copied().collect();
+    let mut used: HashSet<Edge> = HashSet::new();
+    let mut contours = Vec::new();
+    for (start, _) in segments.iter() {
+        if used.contains(start) {
+            continue;
+        }
+        let mut contour: Vec<Vec2> = Vec::new();
+        let mut edge = *start;
+        loop {
+            used.insert(edge);
+            let p = edge_point(edge);
+            if contour.last() != Some(&p) {
+                contour.push(p);
+            }
+            edge = next[&edge];
+            if edge == *start {
+                break;
+            }
+        }
+        while contour.len() > 1 && contour.first() == contour.last() {
+            contour.pop();
+        }
+        if contour.len() >= 3 {
+            contours.push(contour);
+        }
+    }
+    contours
+}
+
+/// Corners of each cube face, counter-clockwise seen from outside. Corner i
+/// sits at (i & 1, i >> 1 & 1, i >> 2 & 1).
+const CUBE_FACES: [[usize; 4]; 6] = [
+    [0, 4, 6, 2],
+    [1, 3, 7, 5],
+    [0, 1, 5, 4],
+    [2, 6, 7, 3],
+    [0, 2, 3, 1],
+    [4, 5, 7, 6],
+];
+
+fn cube_corner(i: usize) -> IVec3 {
+    IVec3::new(i as i32 & 1, i as i32 >> 1 & 1, i as i32 >> 2 & 1)
+}
+
+/// Triangles of the iso-surface of a field sampled at every point of
+/// bounds. Surfaces are left open at the border so that neighboring chunks
+/// sharing a layer of samples join up.
+pub fn marching_cubes(bounds: IRange3, iso: f32, sample: impl Fn(IVec3) -> f32) -> Vec<[Vec3; 3]> {
+    let size = bounds.size();
+    if size.min_element() < 2 {
+        return Vec::new();
+    }
+    let values: Vec<f32> = bounds.iter().map(sample).collect();
+    let value = |p: IVec3| {
+        let d = p - bounds.min;
+        values[((d.z * size.y + d.y) * size.x + d.x) as usize]
+    };
+
+    let mut triangles = Vec::new();
+    for cell in IRange3::new(bounds.min, bounds.max - IVec3::ONE).iter() {
+        let v: [f32; 8] = std::array::from_fn(|i| value(cell + cube_corner(i)));
+        let ins = v.map(|v| v >= iso);
+        if ins.iter().all(|i| *i == ins[0]) {
+            continue;
+        }
+
+        // Surface crossings linked around the cube faces, keyed by the
+        // corners of each edge with the lower corner first
+        const NONE: u8 = u8::MAX;
+        let mut next = [NONE; 64];
+        let key = |a: usize, b: usize| (a.min(b) * 8 + a.max(b)) as u8;
+        for face in CUBE_FACES {
+            let center = face.iter().map(|i| v[*i]).sum::<f32>() * 0.25;
+            face_segments(face.map(|i| ins[i]), center >= iso, |from, to| {
+                let a = key(face[from], face[(from + 1) % 4]);
+                next[a as usize] = key(face[to], face[(to + 1) % 4]);
+            });
+        }
+        let point = |edge: u8| {
+            let (a, b) = ((edge / 8) as usize, (edge % 8) as usize);
+            let pa = (cell + cube_corner(a)).as_vec3();
+            let pb = (cell + cube_corner(b)).as_vec3();
+            pa.lerp(pb, ((iso - v[a]) / (v[b] - v[a])).clamp(0.0, 1.0))
+        };
+
+        for start in 0..64 {
+            if next[start] == NONE {
+                continue;
+            }
+            let mut ring = Vec::new();
+            let mut edge = start as u8;
+            while next[edge as usize] != NONE {
+                ring.push(point(edge));
+                let following = next[edge as usize];
+                next[edge as usize] = NONE;
+                edge = following;
+            }
+            // The rings circle the inside corners counter-clockwise, so
+            // the fan is reversed to face away from them.
+            for i in 1..ring.len().saturating_sub(1) {
+                triangles.push([ring[0], ring[i + 1], ring[i]]);
+            }
+        }
+    }
+    triangles
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::polygon::{signed_area, triangulate};
+    use std::f32::consts::PI;
+
+    fn disc(center: Vec2, radius: f32) -> impl Fn(IVec2) -> f32 {
+        move |p: IVec2| radius - p.as_vec2().distance(center)
+    }
+
+    fn triangle_area(t: &[Vec2; 3]) -> f32 {
+        (t[1] - t[0]).perp_dot(t[2] - t[0]) * 0.5
+    }
+
+    #[test]
+    fn circle_contour() {
+        let center = Vec2::new(10.3, 9.6);
+        let contours = marching_squares(IRange2::from_xy(0, 0, 21, 21), 0.0, disc(center, 7.0));
+        assert_eq!(contours.len(), 1);
+        let c = &contours[0];
+        assert!((signed_area(c) - PI * 49.0).abs() < 1.5);
+        assert!(c.iter().all(|p| (p.distance(center) - 7.0).abs() < 0.1));
+    }
+
+    #[test]
+    fn border_closes_contours() {
+        let contours = marching_squares(IRange2::from_xy(0, 0, 5, 5), 0.5, |_| 1.0);
+        assert_eq!(contours.len(), 1);
+        assert_eq!(signed_area(&contours[0]), 16.0);
+        assert!(marching_squares(IRange2::from_xy(0, 0, 5, 5), 0.5, |_| 0.0).is_empty());
+        assert!(marching_squares(IRange2::from_xy(0, 0, 0, 5), 0.5, |_| 1.0).is_empty());
+    }
+
+    #[test]
+    fn ring_triangulates() {
+        let center = Vec2::new(12.0, 12.0);
+        let ring = |p: IVec2| {
+            let d = p.as_vec2().distance(center);
+            (9.0 - d).min(d - 4.0)
+        };
+        let contours = marching_squares(IRange2::from_xy(0, 0, 25, 25), 0.0, ring);
+        assert_eq!(contours.len(), 2);
+        let (outer, hole): (Vec<_>, Vec<_>) = contours.iter().partition(|c| signed_area(c) > 0.0);
+        assert_eq!((outer.len(), hole.len()), (1, 1));
+        let tris = triangulate(outer[0], &[hole[0].as_slice()]);
+        let area: f32 = tris.iter().map(triangle_area).sum();
+        assert!((area - PI * (81.0 - 16.0)).abs() < 4.0);
+    }
+
+    #[test]
+    fn saddles() {
+        // Diagonal inside corners join only when the cell center is inside
+        let field = |center: f32| {
+            move |p: IVec2| match (p.x, p.y) {
+                (1, 1) | (2, 2) => 1.0,
+                (1, 2) | (2, 1) => center,
+                _ => 0.0,
+            }
+        };
+        let bounds = IRange2::from_xy(0, 0, 4, 4);
+        assert_eq!(marching_squares(bounds, 0.6, field(0.0)).len(), 2);
+        assert_eq!(marching_squares(bounds, 0.6, field(0.4)).len(), 1);
+    }
+
+    #[test]
+    fn sphere_surface() {
+        let center = Vec3::new(6.2, 5.9, 6.1);
+        let radius = 4.5;
+        let sphere = |p: IVec3| radius - p.as_vec3().distance(center);
+        let tris = marching_cubes(IRange3::from_xyz(0, 0, 0, 13, 13, 13), 0.0, sphere);
+        assert!(!tris.is_empty());
+
+        // Closed and consistently wound: every directed edge has a twin
+        let mut edges: HashMap<([u32; 3], [u32; 3]), i32> = HashMap::new();
+        let bits = |p: Vec3| p.to_array().map(f32::to_bits);
+        for t in tris.iter() {
+            for i in 0..3 {
+                let (a, b) = (bits(t[i]), bits(t[(i + 1) % 3]));
+                *edges.entry((a.min(b), a.max(b))).or_default() += if a < b { 1 } else { -1 };
+            }
+        }
+        assert!(edges.values().all(|n| *n == 0));
+
+        let volume: f32 = tris
+            .iter()
+            .map(|t| (t[0] - center).dot((t[1] - center).cross(t[2] - center)) / 6.0)
+            .sum();
+        assert!((volume - 4.0 / 3.0 * PI * radius.powi(3)).abs() < 20.0);
+        for t in tris.iter() {
+            assert!(t.iter().all(|p| (p.distance(center) - radius).abs() < 0.2));
+        }
+    }
+
+    #[test]
+    fn cube_saddles_stay_closed() {
+        // Random fields exercise ambiguous faces; walls are closed by the
+        // zero padding so every edge must still pair up.
+        let mut rng = crate::pcg::PcgRng::new(3, 9);
+        for _ in 0..20 {
+            let noise: Vec<f32> = (0..7 * 7 * 7).map(|_| rng.next_f32()).collect();
+            let field = |p: IVec3| {
+                if p.min_element() == 0 || p.max_element() == 6 {
+                    0.0
+                } else {
+                    noise[(p.z * 49 + p.y * 7 + p.x) as usize]
+                }
+            };
+            let tris = marching_cubes(IRange3::from_xyz(0, 0, 0, 7, 7, 7), 0.5, field);
+            let mut edges: HashMap<([u32; 3], [u32; 3]), i32> = HashMap::new();
+            let bits = |p: Vec3| p.to_array().map(f32::to_bits);
+            for t in tris.iter() {
+                for i in 0..3 {
+                    let (a, b) = (bits(t[i]), bits(t[(i + 1) % 3]));
+                    *edges.entry((a.min(b), a.max(b))).or_default() += if a < b { 1 } else { -1 };
+                }
+            }
+            assert!(edges.values().all(|n| *n == 0));
+        }
+    }
+}
@@ -0,0 +1,414 @@
+use glam::{DVec2, Vec2};
+
+use crate::polygon::centroid;
+use crate::range::Range2;
+
+/// Delaunay triangulation of a point set, built incrementally with
+/// Bowyer-Watson. Duplicate points are left out of the triangulation.
+#[derive(Debug, Default, Clone, PartialEq)]
+pub struct Delaunay {
+    pub points: Vec<Vec2>,
+    /// Counter-clockwise point indices
+    pub triangles: Vec<[u32; 3]>,
+    /// Triangle across the edge from vertex i to vertex i + 1, None on the
+    /// convex hull
+    pub adjacent: Vec<[Option<u32>; 3]>,
+}
+
+const NONE: usize = usize::MAX;
+
+struct Tri {
+    v: [usize; 3],
+    adj: [usize; 3],
+    alive: bool,
+}
+
+fn orient(a: DVec2, b: DVec2, c: DVec2) -> f64 {
+    (b - a).perp_dot(c - a)
+}
+
+/// Positive when p is inside the circumcircle of the counter-clockwise
+/// triangle abc
+fn in_circle(a: DVec2, b: DVec2, c: DVec2, p: DVec2) -> f64 {
+    let (a, b, c) = (a - p, b - p, c - p);
+    a.length_squared() * b.perp_dot(c) - b.length_squared() * a.perp_dot(c)
+        + c.length_squared() * a.perp_dot(b)
+}
+
+fn circumcenter(a: DVec2, b: DVec2, c: DVec2) -> DVec2 {
+    let (b, c) = (b - a, c - a);
+    let d = 2.0 * b.perp_dot(c);
+    let x = c.y * b.length_squared() - b.y * c.length_squared();
+    let y = b.x * c.length_squared() - c.x * b.length_squared();
+    a + DVec2::new(x, y) / d
+}
+
+impl Delaunay {
+    pub fn new(points: &[Vec2]) -> Self {
+        let n = points.len();
+        let mut result = Self {
+            points: points.to_vec(),
+            ..Default::default()
+        };
+        if n < 3 {
+            return result;
+        }
+
+        // Enclosing triangle far enough out that its vertices barely bend
+        // the circumcircles of hull triangles
+        let mut pts: Vec<DVec2> = points.iter().map(|p| p.as_dvec2()).collect();
+        let (min, max) = pts
+            .iter()
+            .fold((pts[0], pts[0]), |(lo, hi), p| (lo.min(*p), hi.max(*p)));
+        let center = (min + max) * 0.5;
+        let r = (max - min).max_element().max(1.0) * 1e4;
+        let s = 3f64.sqrt();
+        pts.push(center + DVec2::new(0.0, 2.0) * r);
+        pts.push(center + DVec2::new(-s, -1.0) * r);
+        pts.push(center + DVec2::new(s, -1.0) * r);
+        let mut tris = vec![Tri {
+            v: [n, n + 1, n + 2],
+            adj: [NONE; 3],
+            alive: true,
+        }];
+
+        let mut last = 0;
+        let mut cavity = Vec::new();
+        let mut boundary: Vec<(usize, usize, usize, usize)> = Vec::new();
+        for (i, p) in pts[..n].iter().copied().enumerate() {
+            let t = locate(&tris, &pts, last, p);
+            if tris[t].v.iter().any(|v| pts[*v] == p) {
+                continue;
+            }
+
+            // Remove every triangle whose circumcircle holds p, the hole is
+            // connected and contains the triangle under p
+            cavity.clear();
+            cavity.push(t);
+            tris[t].alive = false;
+            let mut k = 0;
+            while k < cavity.len() {
+                for u in tris[cavity[k]].adj {
+                    if u != NONE && tris[u].alive {
+                        let [a, b, c] = tris[u].v.map(|v| pts[v]);
+                        if in_circle(a, b, c, p) > 0.0 {
+                            tris[u].alive = false;
+                            cavity.push(u);
+                        }
+                    }
+                }
+                k += 1;
+            }
+
+            boundary.clear();
+            for bad in cavity.iter().copied() {
+                for e in 0..3 {
+                    let outer = tris[bad].adj[e];
+                    if outer == NONE || tris[outer].alive {
+                        boundary.push((tris[bad].v[e], tris[bad].v[(e + 1) % 3], outer, bad));
+                    }
+                }
+            }
+
+            // Fan the hole from p, linking the new triangles to the
+            // outside and to each other
+            let first = tris.len();
+            for (a, b, outer, bad) in boundary.iter().copied() {
+                let new = tris.len();
+                tris.push(Tri {
+                    v: [a, b, i],
+                    adj: [outer, NONE, NONE],
+                    alive: true,
+                });
+                if outer != NONE {
+                    let e = tris[outer].adj.iter().position(|u| *u == bad).unwrap();
+                    tris[outer].adj[e] = new;
+                }
+            }
+            for t in first..tris.len() {
+                let [a, b, _] = tris[t].v;
+                tris[t].adj[1] = (first..tris.len()).find(|u| tris[*u].v[0] == b).unwrap();
+                tris[t].adj[2] = (first..tris.len()).find(|u| tris[*u].v[1] == a).unwrap();
+            }
+            last = tris.len() - 1;
+        }
+
+        let mut remap = vec![NONE; tris.len()];
+        for (t, tri) in tris.iter().enumerate() {
+            if tri.alive && tri.v.iter().all(|v| *v < n) {
+                remap[t] = result.triangles.len();
+                result.triangles.push(tri.v.map(|v| v as u32));
+            }
+        }
+        for (t, tri) in tris.iter().enumerate() {
+            if remap[t] != NONE {
+                result.adjacent.push(
+                    tri.adj
+                        .map(|u| (u != NONE && remap[u] != NONE).then(|| remap[u] as u32)),
+                );
+            }
+        }
+        result
+    }
+
+    pub fn circumcenter(&self, triangle: usize) -> Vec2 {
+        let [a, b, c] = self.triangles[triangle].map(|i| self.points[i as usize].as_dvec2());
+        circumcenter(a, b, c).as_vec2()
+    }
+
+    /// Each edge once, as a pair of point indices
+    pub fn edges(&self) -> impl Iterator<Item = [u32; 2]> + '_ {
+        self.triangles.iter().enumerate().flat_map(move |(t, tri)| {
+            (0..3).filter_map(move |e| {
+                let unique = self.adjacent[t][e].is_none_or(|u| u as usize > t);
+                unique.then_some([tri[e], tri[(e + 1) % 3]])
+            })
+        })
+    }
+
+    /// Counter-clockwise point indices of the convex hull
+    pub fn hull(&self) -> Vec<u32> {
+        let mut next = vec![u32::MAX; self.points.len()];
+        let mut start = None;
+        for (t, tri) in self.triangles.iter().enumerate() {
+            for e in 0..3 {
+                if self.adjacent[t][e].is_none() {
+                    next[tri[e] as usize] = tri[(e + 1) % 3];
+                    start = Some(tri[e]);
+                }
+            }
+        }
+        let mut hull = Vec::new();
+        if let Some(start) = start {
+            let mut i = start;
+            loop {
+                hull.push(i);
+                i = next[i as usize];
+                if i == start {
+                    break;
+                }
+            }
+        }
+        hull
+    }
+
+    /// Sorted indices of the points sharing an edge with each point
+    pub fn neighbors(&self) -> Vec<Vec<u32>> {
+        let mut neighbors = vec![Vec::new(); self.points.len()];
+        for [a, b] in self.edges() {
+            neighbors[a as usize].push(b);
+            neighbors[b as usize].push(a);
+        }
+        for list in neighbors.iter_mut() {
+            list.sort_unstable();
+        }
+        neighbors
+    }
+
+    /// Voronoi cell of each point clipped to bounds, counter-clockwise.
+    /// Points left out of the triangulation get empty cells, unless there
+    /// is no triangulation at all, then every point is compared with every
+    /// other.
+    pub fn voronoi_cells(&self, bounds: Range2) -> Vec<Vec<Vec2>> {
+        let neighbors = if self.triangles.is_empty() {
+            let all: Vec<u32> = (0..self.points.len() as u32).collect();
+            (0..self.points.len())
+                .map(|i| {
+                    all.iter()
+                        .copied()
+                        .filter(|j| self.points[*j as usize] != self.points[i])
+                        .collect()
+                })
+                .collect()
+        } else {
+            self.neighbors()
+        };
+        let rect = vec![bounds.x0y0(), bounds.x1y0(), bounds.x1y1(), bounds.x0y1()];
+        let mut cells = Vec::with_capacity(self.points.len());
+        for (i, p) in self.points.iter().copied().enumerate() {
+            if !self.triangles.is_empty() && neighbors[i].is_empty() {
+                cells.push(Vec::new());
+                continue;
+            }
+            let mut cell = rect.clone();
+            for j in neighbors[i].iter() {
+                let q = self.points[*j as usize];
+                cell = clip_half_plane(&cell, (p + q) * 0.5, q - p);
+            }
+            cells.push(cell);
+        }
+        cells
+    }
+}
+
+/// Walks toward p from a starting triangle, falling back to a scan if the
+/// walk cycles on nearly degenerate input
+fn locate(tris: &[Tri], pts: &[DVec2], start: usize, p: DVec2) -> usize {
+    let mut t = start;
+    let mut steps = 0;
+    'walk: while steps < tris.len() {
+        let [a, b, c] = tris[t].v.map(|v| pts[v]);
+        for (e, (a, b)) in [(a, b), (b, c), (c, a)].into_iter().enumerate() {
+            if orient(a, b, p) < 0.0 && tris[t].adj[e] != NONE {
+                t = tris[t].adj[e];
+                steps += 1;
+                continue 'walk;
+            }
+        }
+        return t;
+    }
+    tris.iter()
+        .position(|tri| {
+            let [a, b, c] = tri.v.map(|v| pts[v]);
+            tri.alive && orient(a, b, p) >= 0.0 && orient(b, c, p) >= 0.0 && orient(c, a, p) >= 0.0
+        })
+        .unwrap_or(start)
+}
+
+/// Keeps the part of a convex polygon where (x - origin) . normal <= 0
+fn clip_half_plane(polygon: &[Vec2], origin: Vec2, normal: Vec2) -> Vec<Vec2> {
+    let mut result = Vec::with_capacity(polygon.len() + 1);
+    let mut j = polygon.len().wrapping_sub(1);
+    for i in 0..polygon.len() {
+        let (a, b) = (polygon[j], polygon[i]);
+        let (da, db) = ((a - origin).dot(normal), (b - origin).dot(normal));
+        if (da <= 0.0) != (db <= 0.0) {
+            result.push(a.lerp(b, da / (da - db)));
+        }
+        if db <= 0.0 {
+            result.push(b);
+        }
+        j = i;
+    }
+    result
+}
+
+/// Moves each point to the centroid of its Voronoi cell within bounds, which
+/// spreads points out evenly over a few iterations
+pub fn lloyd_relax(points: &[Vec2], bounds: Range2, iterations: usize) -> Vec<Vec2> {
+    let mut points = points.to_vec();
+    for _ in 0..iterations {
+        let cells = Delaunay::new(&points).voronoi_cells(bounds);
+        for (p, cell) in points.iter_mut().zip(cells.iter()) {
+            if let Some(c) = centroid(cell) {
+                *p = c;
+            }
+        }
+    }
+    points
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::pcg::PcgRng;
+    use crate::polygon::{convex_hull, signed_area};
+
+    fn random_points(seed: u64, count: usize, bounds: Range2) -> Vec<Vec2> {
+        let mut rng = PcgRng::new(seed, 1);
+        (0..count).map(|_| bounds.lerp(rng.next_vec2())).collect()
+    }
+
+    fn triangle(d: &Delaunay, t: usize) -> [Vec2; 3] {
+        d.triangles[t].map(|i| d.points[i as usize])
+    }
+
+    #[test]
+    fn square_with_center() {
+        let points = [Vec2::ZERO, Vec2::X, Vec2::ONE, Vec2::Y, Vec2::splat(0.5)];
+        let d = Delaunay::new(&points);
+        assert_eq!(d.triangles.len(), 4);
+        assert!(d.triangles.iter().all(|t| t.contains(&4)));
+        assert_eq!(d.hull().len(), 4);
+        assert_eq!(d.edges().count(), 8);
+        assert_eq!(d.neighbors()[4], vec![0, 1, 2, 3]);
+        assert!(Delaunay::new(&points[..2]).triangles.is_empty());
+        assert!(Delaunay::new(&[Vec2::ZERO, Vec2::X, Vec2::X * 2.0])
+            .triangles
+            .is_empty());
+    }
+
+    #[test]
+    fn random_triangulation() {
+        let bounds = Range2::from_x0y0x1y1(-5.0, -3.0, 20.0, 9.0);
+        let mut points = random_points(4, 300, bounds);
+        points.push(points[17]);
+        let d = Delaunay::new(&points);
+
+        // Empty circumcircles
+        for t in 0..d.triangles.len() {
+            let tri = triangle(&d, t);
+            assert!(signed_area(&tri) > 0.0);
+            let c = d.circumcenter(t);
+            let r = c.distance(tri[0]);
+            assert!(points.iter().all(|p| p.distance(c) >= r * (1.0 - 1e-4)));
+        }
+
+        // Symmetric adjacency across shared edges
+        for (t, tri) in d.triangles.iter().enumerate() {
+            for e in 0..3 {
+                if let Some(u) = d.adjacent[t][e] {
+                    let (a, b) = (tri[e], tri[(e + 1) % 3]);
+                    let other = d.triangles[u as usize];
+                    let f = (0..3)
+                        .find(|f| other[*f] == b && other[(f + 1) % 3] == a)
+                        .unwrap();
+                    assert_eq!(d.adjacent[u as usize][f], Some(t as u32));
+                }
+            }
+        }
+
+        // Covers the hull, with 2n - 2 - h triangles for n unique points
+        let hull = convex_hull(&points);
+        assert_eq!(d.hull().len(), hull.len());
+        assert_eq!(d.triangles.len(), 2 * 300 - 2 - hull.len());
+        let area: f32 = (0..d.triangles.len())
+            .map(|t| signed_area(&triangle(&d, t)))
+            .sum();
+        assert!((area - signed_area(&hull)).abs() < 1e-2);
+    }
+
+    #[test]
+    fn voronoi_partitions_bounds() {
+        let bounds = Range2::from_x0y0x1y1(0.0, 0.0, 10.0, 10.0);
+        let mut points = random_points(9, 60, bounds);
+        points.push(points[3]);
+        let d = Delaunay::new(&points);
+        let cells = d.voronoi_cells(bounds);
+        assert_eq!(cells.len(), points.len());
+        assert!(cells[60].is_empty());
+        let area: f32 = cells.iter().map(|c| signed_area(c)).sum();
+        assert!((area - 100.0).abs() < 1e-2);
+
+        let mut rng = PcgRng::new(1, 2);
+        for _ in 0..200 {
+            let q = bounds.lerp(rng.next_vec2());
+            let nearest = (0..60)
+                .min_by(|a, b| points[*a].distance(q).total_cmp(&points[*b].distance(q)))
+                .unwrap();
+            assert!(crate::polygon::contains_point(&cells[nearest], q));
+        }
+
+        let pair = Delaunay::new(&[Vec2::new(2.0, 5.0), Vec2::new(8.0, 5.0)]).voronoi_cells(bounds);
+        assert_eq!(signed_area(&pair[0]), 50.0);
+    }
+
+    #[test]
+    fn lloyd_spreads_points() {
+        let bounds = Range2::from_x0y0x1y1(0.0, 0.0, 10.0, 10.0);
+        let points = random_points(21, 40, bounds);
+        let min_gap = |points: &[Vec2]| {
+            let mut gap = f32::MAX;
+            for i in 0..points.len() {
+                for j in i + 1..points.len() {
+                    gap = gap.min(points[i].distance(points[j]));
+                }
+            }
+            gap
+        };
+        let relaxed = lloyd_relax(&points, bounds, 8);
+        assert_eq!(relaxed, lloyd_relax(&points, bounds, 8));
+        assert!(relaxed.iter().all(|p| bounds.contains(*p)));
+        assert!(min_gap(&relaxed) > min_gap(&points) * 2.0);
+    }
+}
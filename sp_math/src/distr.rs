@@ -0,0 +1,462 @@
+use std::f64::consts::TAU;
+
+use crate::pcg::PcgRng;
+
+/// ln(n!) summed directly for small n and using Stirling's series above it
+fn ln_factorial(n: u32) -> f64 {
+    if n < 16 {
+        (2..=n).map(|i| (i as f64).ln()).sum()
+    } else {
+        let x = n as f64 + 1.0;
+        (x - 0.5) * x.ln() - x + 0.5 * TAU.ln() + 1.0 / (12.0 * x) - 1.0 / (360.0 * x * x * x)
+    }
+}
+
+impl PcgRng {
+    /// Returns value in half open range (0, 1], which is safe to take the log of
+    fn next_f64_nonzero(&mut self) -> f64 {
+        (self.next_u32() as f64 + 1.0) * (1.0 / 4294967296.0)
+    }
+
+    pub fn choose<'a, T>(&mut self, items: &'a [T]) -> Option<&'a T> {
+        if items.is_empty() {
+            None
+        } else {
+            Some(&items[self.next_u32_to(items.len() as u32) as usize])
+        }
+    }
+
+    /// Index picked with probability proportional to its weight. Negative and
+    /// non-finite weights count as zero. Returns None if no weight is positive.
+    pub fn next_weighted(&mut self, weights: &[f32]) -> Option<usize> {
+        let weight = |w: f32| if w.is_finite() && w > 0.0 { w as f64 } else { 0.0 };
+        let total: f64 = weights.iter().map(|w| weight(*w)).sum();
+        if total <= 0.0 {
+            return None;
+        }
+        let r = self.next_f64() * total;
+        let mut acc = 0.0;
+        let mut last = None;
+        for (i, w) in weights.iter().enumerate() {
+            let w = weight(*w);
+            if w > 0.0 {
+                acc += w;
+                last = Some(i);
+                if r < acc {
+                    return last;
+                }
+            }
+        }
+        last
+    }
+
+    /// Picks from (item, weight) pairs, see next_weighted
+    pub fn choose_weighted<'a, T>(&mut self, items: &'a [(T, f32)]) -> Option<&'a T> {
+        let weights: Vec<f32> = items.iter().map(|(_, w)| *w).collect();
+        self.next_weighted(&weights).map(|i| &items[i].0)
+    }
+
+    /// Gaussian distribution using the Box-Muller transform
+    pub fn next_normal(&mut self, mean: f32, std_dev: f32) -> f32 {
+        let u = self.next_f64_nonzero();
+        let v = self.next_f64();
+        let z = (-2.0 * u.ln()).sqrt() * (TAU * v).cos();
+        mean + std_dev * z as f32
+    }
+
+    /// Time between events that happen rate times per unit on average
+    pub fn next_exponential(&mut self, rate: f32) -> f32 {
+        if rate <= 0.0 {
+            return f32::INFINITY;
+        }
+        (-self.next_f64_nonzero().ln() / rate as f64) as f32
+    }
+
+    /// Number of events in a unit interval when mean events happen on average.
+    /// Uses Knuth's method for small means and Hormann's PTRS rejection
+    /// sampler above that, so cost stays bounded for large means.
+    pub fn next_poisson(&mut self, mean: f32) -> u32 {
+        let mean = mean as f64;
+        if mean <= 0.0 || !mean.is_finite() {
+            return 0;
+        }
+        if mean < 30.0 {
+            let limit = (-mean).exp();
+            let mut k = 0;
+            let mut p = self.next_f64_nonzero();
+            while p > limit {
+                k += 1;
+                p *= self.next_f64_nonzero();
+            }
+            return k;
+        }
+        // https://research.wu.ac.at/files/18952997/document.pdf
+        let slam = mean.sqrt();
+        let loglam = mean.ln();
+        let b = 0.931 + 2.53 * slam;
+        let a = -0.059 + 0.02483 * b;
+        let inv_alpha = 1.1239 + 1.1328 / (b - 3.4);
+        let vr = 0.9277 - 3.6224 / (b - 2.0);
+        loop {
+            let u = self.next_f64() - 0.5;
+            let v = self.next_f64_nonzero();
+            let us = 0.5 - u.abs();
+            let k = ((2.0 * a / us + b) * u + mean + 0.43).floor();
+            if us >= 0.07 && v <= vr {
+                return k as u32;
+            }
+            if k < 0.0 || (us < 0.013 && v > us) {
+                continue;
+            }
+            let lhs = v.ln() + inv_alpha.ln() - (a / (us * us) + b).ln();
+            if lhs <= -mean + k * loglam - ln_factorial(k as u32) {
+                return k as u32;
+            }
+        }
+    }
+
+    /// Triangular distribution over [min, max] peaking at mode
+    pub fn next_triangular(&mut self, min: f32, mode: f32, max: f32) -> f32 {
+        let u = self.next_f32();
+        let range = max - min;
+        if range <= 0.0 {
+            return min;
+        }
+        let split = (mode - min) / range;
+        if u < split {
+            min + (u * range * (mode - min)).sqrt()
+        } else {
+            max - ((1.0 - u) * range * (max - mode)).sqrt()
+        }
+    }
+
+    /// Uniformly chooses up to k items from an iterator of unknown length in
+    /// a single pass. Items keep their iteration order when fewer than k exist.
+    pub fn reservoir_sample<T>(&mut self, items: impl IntoIterator<Item = T>, k: usize) -> Vec<T> {
+        let mut reservoir = Vec::with_capacity(k);
+        if k == 0 {
+            return reservoir;
+        }
+        for (i, item) in items.into_iter().enumerate() {
+            if i < k {
+                reservoir.push(item);
+            } else {
+                let j = self.next_u64() % (i as u64 + 1);
+                if (j as usize) < k {
+                    reservoir[j as usize] = item;
+                }
+            }
+        }
+        reservoir
+    }
+
+    /// Uniformly chooses one item from an iterator of unknown length
+    pub fn choose_iter<T>(&mut self, items: impl IntoIterator<Item = T>) -> Option<T> {
+        self.reservoir_sample(items, 1).pop()
+    }
+}
+
+/// Walker's alias method for O(1) weighted sampling from a fixed table:
+/// https://www.keithschwarz.com/darts-dice-coins/
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct AliasTable {
+    /// Chance of keeping the column, scaled to 2^32
+    thresholds: Vec<u64>,
+    aliases: Vec<u32>,
+}
+
+impl AliasTable {
+    /// Negative and non-finite weights count as zero. Returns None if no
+    /// weight is positive.
+    pub fn new(weights: &[f32]) -> Option<Self> {
+        let n = weights.len();
+        let weight = |w: f32| if w.is_finite() && w > 0.0 { w as f64 } else { 0.0 };
+        let total: f64 = weights.iter().map(|w| weight(*w)).sum();
+        if total <= 0.0 || n > u32::MAX as usize {
+            return None;
+        }
+        let mut scaled: Vec<f64> = weights.iter().map(|w| weight(*w) * n as f64 / total).collect();
+        let mut thresholds = vec![1u64 << 32; n];
+        let mut aliases: Vec<u32> = (0..n as u32).collect();
+        let mut small: Vec<usize> = (0..n).filter(|i| scaled[*i] < 1.0).collect();
+        let mut large: Vec<usize> = (0..n).filter(|i| scaled[*i] >= 1.0).collect();
+        while let (Some(s), Some(&l)) = (small.pop(), large.last()) {
+            thresholds[s] = (scaled[s] * 4294967296.0) as u64;
+            aliases[s] = l as u32;
+            scaled[l] -= 1.0 - scaled[s];
+            if scaled[l] < 1.0 {
+                large.pop();
+                small.push(l);
+            }
+        }
+        // Leftovers from rounding error keep their full column
+        Some(Self { thresholds, aliases })
+    }
+
+    pub fn len(&self) -> usize {
+        self.thresholds.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.thresholds.is_empty()
+    }
+
+    pub fn sample(&self, rng: &mut PcgRng) -> usize {
+        let i = rng.next_u32_to(self.len() as u32) as usize;
+        if (rng.next_u32() as u64) < self.thresholds[i] {
+            i
+        } else {
+            self.aliases[i] as usize
+        }
+    }
+}
+
+/// Deals items in random order, reshuffling once all have been dealt. Every
+/// item appears once per round, and a round never starts with the item that
+/// ended the previous one unless all items are equal.
+#[derive(Debug, Clone)]
+pub struct ShuffleBag<T> {
+    items: Vec<T>,
+    order: Vec<usize>,
+    cursor: usize,
+    // Index of the item dealt last, None before the first round
+    last: Option<usize>,
+}
+
+impl<T: PartialEq> ShuffleBag<T> {
+    pub fn new(items: Vec<T>) -> Self {
+        let order = (0..items.len()).collect();
+        let cursor = items.len();
+        Self {
+            items,
+            order,
+            cursor,
+            last: None,
+        }
+    }
+
+    /// Adds count copies of each item, so items with more copies come up more
+    pub fn from_counts(items: impl IntoIterator<Item = (T, usize)>) -> Self
+    where
+        T: Clone,
+    {
+        let mut all = Vec::new();
+        for (item, count) in items {
+            all.extend(std::iter::repeat_n(item, count));
+        }
+        Self::new(all)
+    }
+
+    pub fn len(&self) -> usize {
+        self.items.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.items.is_empty()
+    }
+
+    /// Items left before the next reshuffle
+    pub fn remaining(&self) -> usize {
+        self.items.len() - self.cursor
+    }
+
+    pub fn next(&mut self, rng: &mut PcgRng) -> Option<&T> {
+        if self.items.is_empty() {
+            return None;
+        }
+        if self.cursor >= self.order.len() {
+            rng.shuffle(&mut self.order);
+            if let Some(last) = self.last {
+                let first = self.order[0];
+                if self.items[first] == self.items[last] {
+                    let other = (1..self.order.len())
+                        .find(|i| self.items[self.order[*i]] != self.items[last]);
+                    if let Some(other) = other {
+                        self.order.swap(0, other);
+                    }
+                }
+            }
+            self.cursor = 0;
+        }
+        let index = self.order[self.cursor];
+        self.cursor += 1;
+        self.last = Some(index);
+        Some(&self.items[index])
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const N: usize = 100_000;
+
+    fn mean_variance(samples: impl Iterator<Item = f64>) -> (f64, f64) {
+        let samples: Vec<f64> = samples.collect();
+        let n = samples.len() as f64;
+        let mean = samples.iter().sum::<f64>() / n;
+        let var = samples.iter().map(|x| (x - mean) * (x - mean)).sum::<f64>() / n;
+        (mean, var)
+    }
+
+    fn assert_frequencies(counts: &[usize], weights: &[f32]) {
+        let total: f32 = weights.iter().sum();
+        let n: usize = counts.iter().sum();
+        for (count, w) in counts.iter().zip(weights) {
+            let expected = *w as f64 / total as f64;
+            let actual = *count as f64 / n as f64;
+            assert!((actual - expected).abs() < 0.01, "{actual} vs {expected}");
+        }
+    }
+
+    #[test]
+    fn weighted_pick() {
+        let mut rng = PcgRng::new(1, 0);
+        let weights = [1.0, 0.0, 3.0, -2.0, 6.0];
+        let mut counts = [0; 5];
+        for _ in 0..N {
+            counts[rng.next_weighted(&weights).unwrap()] += 1;
+        }
+        assert_eq!(counts[1], 0);
+        assert_eq!(counts[3], 0);
+        assert_frequencies(&counts, &[1.0, 0.0, 3.0, 0.0, 6.0]);
+        assert_eq!(rng.next_weighted(&[0.0, -1.0]), None);
+        assert_eq!(rng.next_weighted(&[]), None);
+        let loot = [("common", 9.0), ("rare", 1.0)];
+        assert!(rng.choose_weighted(&loot).is_some());
+    }
+
+    #[test]
+    fn alias_table() {
+        let weights = [5.0, 1.0, 0.0, 2.0, 2.0];
+        let table = AliasTable::new(&weights).unwrap();
+        let mut rng = PcgRng::new(2, 0);
+        let mut counts = [0; 5];
+        for _ in 0..N {
+            counts[table.sample(&mut rng)] += 1;
+        }
+        assert_eq!(counts[2], 0);
+        assert_frequencies(&counts, &weights);
+        assert_eq!(AliasTable::new(&[]), None);
+        assert_eq!(AliasTable::new(&[0.0]), None);
+    }
+
+    #[test]
+    fn normal_shape() {
+        let mut rng = PcgRng::new(3, 0);
+        let (mean, var) = mean_variance((0..N).map(|_| rng.next_normal(2.0, 3.0) as f64));
+        assert!((mean - 2.0).abs() < 0.05);
+        assert!((var - 9.0).abs() < 0.2);
+        let within_one = (0..N)
+            .filter(|_| rng.next_normal(0.0, 1.0).abs() < 1.0)
+            .count() as f64
+            / N as f64;
+        assert!((within_one - 0.6827).abs() < 0.01);
+    }
+
+    #[test]
+    fn exponential_shape() {
+        let mut rng = PcgRng::new(4, 0);
+        let (mean, var) = mean_variance((0..N).map(|_| rng.next_exponential(4.0) as f64));
+        assert!((mean - 0.25).abs() < 0.005);
+        assert!((var - 0.0625).abs() < 0.005);
+    }
+
+    #[test]
+    fn poisson_shape() {
+        let mut rng = PcgRng::new(5, 0);
+        for lambda in [0.5, 4.0, 29.0, 31.0, 500.0] {
+            let (mean, var) = mean_variance((0..N).map(|_| rng.next_poisson(lambda) as f64));
+            let tolerance = 0.02 * lambda as f64 + 0.02;
+            assert!((mean - lambda as f64).abs() < tolerance, "{lambda} {mean}");
+            assert!((var - lambda as f64).abs() < tolerance * 3.0, "{lambda} {var}");
+        }
+        assert_eq!(rng.next_poisson(0.0), 0);
+    }
+
+    #[test]
+    fn triangular_shape() {
+        let mut rng = PcgRng::new(6, 0);
+        let samples: Vec<f32> = (0..N).map(|_| rng.next_triangular(0.0, 2.0, 8.0)).collect();
+        assert!(samples.iter().all(|x| (0.0..=8.0).contains(x)));
+        let (mean, _) = mean_variance(samples.iter().map(|x| *x as f64));
+        assert!((mean - 10.0 / 3.0).abs() < 0.05);
+        // P(x < mode) = (mode - min) / (max - min)
+        let below = samples.iter().filter(|x| **x < 2.0).count() as f64 / N as f64;
+        assert!((below - 0.25).abs() < 0.01);
+    }
+
+    #[test]
+    fn shuffle_bag() {
+        let mut rng = PcgRng::new(7, 0);
+        let mut bag = ShuffleBag::from_counts([('a', 1), ('b', 2), ('c', 3)]);
+        for _ in 0..200 {
+            let mut round = Vec::new();
+            for _ in 0..6 {
+                round.push(*bag.next(&mut rng).unwrap());
+            }
+            round.sort();
+            assert_eq!(round, vec!['a', 'b', 'b', 'c', 'c', 'c']);
+            assert_eq!(bag.remaining(), 0);
+        }
+        let mut empty: ShuffleBag<u32> = ShuffleBag::new(Vec::new());
+        assert_eq!(empty.next(&mut rng), None);
+    }
+
+    #[test]
+    fn shuffle_bag_avoids_repeat_across_rounds() {
+        let mut rng = PcgRng::new(8, 0);
+        let mut bag = ShuffleBag::new(vec![1, 2, 3]);
+        let mut prev = 0;
+        for _ in 0..3000 {
+            let x = *bag.next(&mut rng).unwrap();
+            assert_ne!(x, prev);
+            prev = x;
+        }
+    }
+
+    #[test]
+    fn shuffle_bag_first_draw_is_uniform() {
+        let mut counts = [0; 3];
+        for seed in 0..N as u64 {
+            let mut rng = PcgRng::new(seed, 0);
+            let mut bag = ShuffleBag::new(vec![0, 1, 2]);
+            counts[*bag.next(&mut rng).unwrap()] += 1;
+        }
+        assert_frequencies(&counts, &[1.0; 3]);
+    }
+
+    #[test]
+    fn reservoir() {
+        let mut rng = PcgRng::new(9, 0);
+        let mut counts = [0; 10];
+        for _ in 0..N / 10 {
+            for x in rng.reservoir_sample(0..10, 3) {
+                counts[x] += 1;
+            }
+        }
+        assert_frequencies(&counts, &[1.0; 10]);
+        assert_eq!(rng.reservoir_sample(0..2, 5), vec![0, 1]);
+        assert!(rng.reservoir_sample(0..2, 0).is_empty());
+        assert_eq!(rng.choose_iter(std::iter::empty::<u32>()), None);
+    }
+
+    #[test]
+    fn reproducible_from_seed() {
+        let run = |seed| {
+            let mut rng = PcgRng::new(seed, 0);
+            let table = AliasTable::new(&[1.0, 2.0, 3.0]).unwrap();
+            (0..100)
+                .map(|_| {
+                    (
+                        rng.next_normal(0.0, 1.0).to_bits(),
+                        rng.next_poisson(50.0),
+                        table.sample(&mut rng),
+                    )
+                })
+                .collect::<Vec<_>>()
+        };
+        assert_eq!(run(10), run(10));
+        assert_ne!(run(10), run(11));
+    }
+}
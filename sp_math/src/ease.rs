@@ -2,6 +2,8 @@
 
 use std::f32::consts::{FRAC_PI_2, PI};
 
+use crate::fixed::Fx32;
+
 #[inline]
 fn powf(x: f32, exp: f32) -> f32 {
     (x as f64).powf(exp as f64) as f32
@@ -241,7 +243,30 @@ pub fn bounce_inout(t: f32) -> f32 {
     }
 }
 
-#[derive(Clone, Copy)]
+/// CSS-style timing curve from (0, 0) to (1, 1) with control points
+/// (x1, y1) and (x2, y2). Control x values should be in [0, 1] so the curve
+/// is monotonic in x, which lets bisection find the curve parameter.
+pub fn cubic_bezier(x1: f32, y1: f32, x2: f32, y2: f32, t: f32) -> f32 {
+    let bezier = |a: f32, b: f32, s: f32| {
+        let u = 1.0 - s;
+        3.0 * u * u * s * a + 3.0 * u * s * s * b + s * s * s
+    };
+    let t = t.clamp(0.0, 1.0);
+    let mut lo = 0.0;
+    let mut hi = 1.0;
+    for _ in 0..24 {
+        let s = (lo + hi) * 0.5;
+        if bezier(x1, x2, s) < t {
+            lo = s;
+        } else {
+            hi = s;
+        }
+    }
+    bezier(y1, y2, (lo + hi) * 0.5)
+}
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub enum Ease {
     Linear,
     QuadIn,
@@ -274,6 +299,8 @@ pub enum Ease {
     BounceIn,
     BounceOut,
     BounceInOut,
+    /// Control points (x1, y1, x2, y2) as in CSS cubic-bezier()
+    CubicBezier(f32, f32, f32, f32),
 }
 
 impl Default for Ease {
@@ -317,6 +344,373 @@ impl Ease {
             Ease::BounceIn      => bounce_in(x),
             Ease::BounceOut     => bounce_out(x),
             Ease::BounceInOut   => bounce_inout(x),
+            Ease::CubicBezier(x1, y1, x2, y2) => cubic_bezier(x1, y1, x2, y2, x),
+        }
+    }
+
+    /// Deterministic fixed-point variant of map
+    #[rustfmt::skip]
+    pub fn map_fx(&self, x: Fx32) -> Fx32 {
+        match *self {
+            Ease::Linear        => fx::linear(x),
+            Ease::QuadIn        => fx::quad_in(x),
+            Ease::QuadOut       => fx::quad_out(x),
+            Ease::QuadInOut     => fx::quad_inout(x),
+            Ease::CubicIn       => fx::cubic_in(x),
+            Ease::CubicOut      => fx::cubic_out(x),
+            Ease::CubicInOut    => fx::cubic_inout(x),
+            Ease::QuartIn       => fx::quart_in(x),
+            Ease::QuartOut      => fx::quart_out(x),
+            Ease::QuartInOut    => fx::quart_inout(x),
+            Ease::QuintIn       => fx::quint_in(x),
+            Ease::QuintOut      => fx::quint_out(x),
+            Ease::QuintInOut    => fx::quint_inout(x),
+            Ease::SineIn        => fx::sine_in(x),
+            Ease::SineOut       => fx::sine_out(x),
+            Ease::SineInOut     => fx::sine_inout(x),
+            Ease::CircIn        => fx::circ_in(x),
+            Ease::CircOut       => fx::circ_out(x),
+            Ease::CircInOut     => fx::circ_inout(x),
+            Ease::ExpoIn        => fx::expo_in(x),
+            Ease::ExpoOut       => fx::expo_out(x),
+            Ease::ExpoInOut     => fx::expo_inout(x),
+            Ease::ElasticIn     => fx::elastic_in(x),
+            Ease::ElasticOut    => fx::elastic_out(x),
+            Ease::ElasticInOut  => fx::elastic_inout(x),
+            Ease::BackIn        => fx::back_in(x),
+            Ease::BackOut       => fx::back_out(x),
+            Ease::BackInOut     => fx::back_inout(x),
+            Ease::BounceIn      => fx::bounce_in(x),
+            Ease::BounceOut     => fx::bounce_out(x),
+            Ease::BounceInOut   => fx::bounce_inout(x),
+            Ease::CubicBezier(x1, y1, x2, y2) => fx::cubic_bezier(
+                Fx32::from_f32(x1),
+                Fx32::from_f32(y1),
+                Fx32::from_f32(x2),
+                Fx32::from_f32(y2),
+                x,
+            ),
+        }
+    }
+}
+
+/// Fixed-point versions of the functions above for lockstep simulation
+pub mod fx {
+    use crate::fixed::Fx32;
+
+    const fn c(x: f64) -> Fx32 {
+        Fx32::from_f64(x)
+    }
+
+    const ONE: Fx32 = Fx32::ONE;
+    const TWO: Fx32 = Fx32::TWO;
+    const HALF: Fx32 = Fx32::HALF;
+    const PI: Fx32 = Fx32::PI;
+    const FRAC_PI_2: Fx32 = Fx32::FRAC_PI_2;
+
+    #[inline]
+    pub fn linear(t: Fx32) -> Fx32 {
+        t
+    }
+
+    #[inline]
+    pub fn quad_in(t: Fx32) -> Fx32 {
+        t * t
+    }
+
+    #[inline]
+    pub fn quad_out(t: Fx32) -> Fx32 {
+        -t * (t - TWO)
+    }
+
+    #[inline]
+    pub fn quad_inout(t: Fx32) -> Fx32 {
+        if t < HALF {
+            TWO * t * t
+        } else {
+            c(-2.0) * t * t + c(4.0) * t - ONE
         }
     }
+
+    #[inline]
+    pub fn cubic_in(t: Fx32) -> Fx32 {
+        t * t * t
+    }
+
+    #[inline]
+    pub fn cubic_out(t: Fx32) -> Fx32 {
+        let f = t - ONE;
+        f * f * f + ONE
+    }
+
+    #[inline]
+    pub fn cubic_inout(t: Fx32) -> Fx32 {
+        if t < HALF {
+            c(4.0) * t * t * t
+        } else {
+            let f = TWO * t - TWO;
+            HALF * f * f * f + ONE
+        }
+    }
+
+    #[inline]
+    pub fn quart_in(t: Fx32) -> Fx32 {
+        t * t * t * t
+    }
+
+    #[inline]
+    pub fn quart_out(t: Fx32) -> Fx32 {
+        let f = t - ONE;
+        f * f * f * (ONE - t) + ONE
+    }
+
+    #[inline]
+    pub fn quart_inout(t: Fx32) -> Fx32 {
+        if t < HALF {
+            c(8.0) * t * t * t * t
+        } else {
+            let f = t - ONE;
+            c(-8.0) * f * f * f * f + ONE
+        }
+    }
+
+    #[inline]
+    pub fn quint_in(t: Fx32) -> Fx32 {
+        t * t * t * t * t
+    }
+
+    #[inline]
+    pub fn quint_out(t: Fx32) -> Fx32 {
+        let f = t - ONE;
+        f * f * f * f * f + ONE
+    }
+
+    #[inline]
+    pub fn quint_inout(t: Fx32) -> Fx32 {
+        if t < HALF {
+            c(16.0) * t * t * t * t * t
+        } else {
+            let f = TWO * t - TWO;
+            HALF * f * f * f * f * f + ONE
+        }
+    }
+
+    #[inline]
+    pub fn sine_in(t: Fx32) -> Fx32 {
+        ((t - ONE) * FRAC_PI_2).sin() + ONE
+    }
+
+    #[inline]
+    pub fn sine_out(t: Fx32) -> Fx32 {
+        (t * FRAC_PI_2).sin()
+    }
+
+    #[inline]
+    pub fn sine_inout(t: Fx32) -> Fx32 {
+        HALF * (ONE - (t * PI).cos())
+    }
+
+    #[inline]
+    pub fn circ_in(t: Fx32) -> Fx32 {
+        ONE - (ONE - t * t).sqrt()
+    }
+
+    #[inline]
+    pub fn circ_out(t: Fx32) -> Fx32 {
+        ((TWO - t) * t).sqrt()
+    }
+
+    #[inline]
+    pub fn circ_inout(t: Fx32) -> Fx32 {
+        if t < HALF {
+            HALF * (ONE - (ONE - c(4.0) * t * t).sqrt())
+        } else {
+            HALF * ((-(TWO * t - c(3.0)) * (TWO * t - ONE)).sqrt() + ONE)
+        }
+    }
+
+    #[inline]
+    pub fn expo_in(t: Fx32) -> Fx32 {
+        if t == Fx32::ZERO {
+            Fx32::ZERO
+        } else {
+            (c(10.0) * (t - ONE)).exp2()
+        }
+    }
+
+    #[inline]
+    pub fn expo_out(t: Fx32) -> Fx32 {
+        if t == ONE {
+            ONE
+        } else {
+            ONE - (c(-10.0) * t).exp2()
+        }
+    }
+
+    #[inline]
+    pub fn expo_inout(t: Fx32) -> Fx32 {
+        if t == Fx32::ZERO {
+            Fx32::ZERO
+        } else if t == ONE {
+            ONE
+        } else if t < HALF {
+            HALF * (c(20.0) * t - c(10.0)).exp2()
+        } else {
+            c(-0.5) * (c(-20.0) * t + c(10.0)).exp2() + ONE
+        }
+    }
+
+    #[inline]
+    pub fn elastic_in(t: Fx32) -> Fx32 {
+        (c(13.0) * FRAC_PI_2 * t).sin() * (c(10.0) * (t - ONE)).exp2()
+    }
+
+    #[inline]
+    pub fn elastic_out(t: Fx32) -> Fx32 {
+        (c(-13.0) * FRAC_PI_2 * (t + ONE)).sin() * (c(-10.0) * t).exp2() + ONE
+    }
+
+    #[inline]
+    pub fn elastic_inout(t: Fx32) -> Fx32 {
+        if t < HALF {
+            HALF * (c(13.0) * FRAC_PI_2 * TWO * t).sin() * (c(10.0) * (TWO * t - ONE)).exp2()
+        } else {
+            HALF * ((c(-13.0) * FRAC_PI_2 * TWO * t).sin() * (c(-10.0) * (TWO * t - ONE)).exp2()
+                + TWO)
+        }
+    }
+
+    #[inline]
+    pub fn back_in(t: Fx32) -> Fx32 {
+        t * t * t - t * (t * PI).sin()
+    }
+
+    #[inline]
+    pub fn back_out(t: Fx32) -> Fx32 {
+        let f = ONE - t;
+        ONE - f * f * f + f * (f * PI).sin()
+    }
+
+    #[inline]
+    pub fn back_inout(t: Fx32) -> Fx32 {
+        if t < HALF {
+            let f = TWO * t;
+            HALF * (f * f * f - f * (f * PI).sin())
+        } else {
+            let f = TWO - TWO * t;
+            HALF * (ONE - (f * f * f - f * (f * PI).sin())) + HALF
+        }
+    }
+
+    #[inline]
+    pub fn bounce_in(t: Fx32) -> Fx32 {
+        ONE - bounce_out(ONE - t)
+    }
+
+    #[inline]
+    pub fn bounce_out(t: Fx32) -> Fx32 {
+        if t < c(4.0 / 11.0) {
+            c(121.0 / 16.0) * t * t
+        } else if t < c(8.0 / 11.0) {
+            c(363.0 / 40.0) * t * t - c(99.0 / 10.0) * t + c(17.0 / 5.0)
+        } else if t < c(9.0 / 10.0) {
+            c(4356.0 / 361.0) * t * t - c(35442.0 / 1805.0) * t + c(16061.0 / 1805.0)
+        } else {
+            c(54.0 / 5.0) * t * t - c(513.0 / 25.0) * t + c(268.0 / 25.0)
+        }
+    }
+
+    #[inline]
+    pub fn bounce_inout(t: Fx32) -> Fx32 {
+        if t < HALF {
+            HALF * bounce_in(t * TWO)
+        } else {
+            HALF * bounce_out(t * TWO - ONE) + HALF
+        }
+    }
+
+    pub fn cubic_bezier(x1: Fx32, y1: Fx32, x2: Fx32, y2: Fx32, t: Fx32) -> Fx32 {
+        let bezier = |a: Fx32, b: Fx32, s: Fx32| {
+            let u = ONE - s;
+            c(3.0) * u * u * s * a + c(3.0) * u * s * s * b + s * s * s
+        };
+        let t = t.clamp(Fx32::ZERO, ONE);
+        let mut lo = Fx32::ZERO;
+        let mut hi = ONE;
+        for _ in 0..Fx32::FRAC_BITS {
+            let s = (lo + hi) * HALF;
+            if bezier(x1, x2, s) < t {
+                lo = s;
+            } else {
+                hi = s;
+            }
+        }
+        bezier(y1, y2, (lo + hi) * HALF)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn fixed_matches_float() {
+        for (i, ease) in ALL.iter().enumerate() {
+            for j in 0..=20 {
+                let t = j as f32 / 20.0;
+                let expected = ease.map(t);
+                let actual = ease.map_fx(Fx32::from_f32(t)).to_f32();
+                assert!((expected - actual).abs() < 2e-3, "{:?}", (i, t, expected, actual));
+            }
+        }
+    }
+
+    #[test]
+    fn cubic_bezier_matches_known_curves() {
+        let linear = Ease::CubicBezier(0.0, 0.0, 1.0, 1.0);
+        let ease_in_out = Ease::CubicBezier(0.42, 0.0, 0.58, 1.0);
+        for j in 0..=20 {
+            let t = j as f32 / 20.0;
+            assert!((linear.map(t) - t).abs() < 1e-4);
+            assert!((linear.map_fx(Fx32::from_f32(t)).to_f32() - t).abs() < 1e-3);
+            let fx = ease_in_out.map_fx(Fx32::from_f32(t)).to_f32();
+            assert!((ease_in_out.map(t) - fx).abs() < 1e-3);
+        }
+        assert!((ease_in_out.map(0.5) - 0.5).abs() < 1e-4);
+        assert!(ease_in_out.map(0.25) < 0.25);
+    }
+
+    const ALL: [Ease; 31] = [
+        Ease::Linear,
+        Ease::QuadIn,
+        Ease::QuadOut,
+        Ease::QuadInOut,
+        Ease::CubicIn,
+        Ease::CubicOut,
+        Ease::CubicInOut,
+        Ease::QuartIn,
+        Ease::QuartOut,
+        Ease::QuartInOut,
+        Ease::QuintIn,
+        Ease::QuintOut,
+        Ease::QuintInOut,
+        Ease::SineIn,
+        Ease::SineOut,
+        Ease::SineInOut,
+        Ease::CircIn,
+        Ease::CircOut,
+        Ease::CircInOut,
+        Ease::ExpoIn,
+        Ease::ExpoOut,
+        Ease::ExpoInOut,
+        Ease::ElasticIn,
+        Ease::ElasticOut,
+        Ease::ElasticInOut,
+        Ease::BackIn,
+        Ease::BackOut,
+        Ease::BackInOut,
+        Ease::BounceIn,
+        Ease::BounceOut,
+        Ease::BounceInOut,
+    ];
 }
@@ -0,0 +1,986 @@
+//! Deterministic fixed-point math for lockstep simulation. All operations
+//! after construction use integer arithmetic only, so results match across
+//! compilers and targets. Trig and exp2 use lookup tables with linear
+//! interpolation.
+
+use std::fmt;
+use std::ops;
+
+use glam::{IVec2, IVec3, Vec2, Vec3};
+
+use crate::range::Range2;
+
+// Quarter wave sin(i / 256 * pi / 2) in Q2.30
+const SIN_TABLE: [u32; 257] = [
+    0, 6588356, 13176464, 19764076, 26350943, 32936819, 39521455, 46104602, 52686014, 59265442,
+    65842639, 72417357, 78989349, 85558366, 92124163, 98686491, 105245103, 111799753, 118350194,
+    124896179, 131437462, 137973796, 144504935, 151030634, 157550647, 164064728, 170572633,
+    177074115, 183568930, 190056834, 196537583, 203010932, 209476638, 215934457, 222384147,
+    228825464, 235258165, 241682010, 248096755, 254502159, 260897982, 267283981, 273659918,
+    280025552, 286380643, 292724951, 299058239, 305380268, 311690799, 317989595, 324276419,
+    330551034, 336813204, 343062693, 349299266, 355522689, 361732726, 367929144, 374111709,
+    380280190, 386434353, 392573967, 398698801, 404808624, 410903207, 416982319, 423045732,
+    429093217, 435124548, 441139496, 447137835, 453119340, 459083786, 465030947, 470960600,
+    476872522, 482766489, 488642281, 494499676, 500338453, 506158392, 511959275, 517740883,
+    523502998, 529245404, 534967884, 540670223, 546352205, 552013618, 557654248, 563273883,
+    568872310, 574449320, 580004702, 585538248, 591049748, 596538995, 602005783, 607449906,
+    612871159, 618269338, 623644239, 628995660, 634323400, 639627258, 644907034, 650162530,
+    655393548, 660599890, 665781362, 670937767, 676068911, 681174602, 686254647, 691308855,
+    696337036, 701339000, 706314559, 711263525, 716185713, 721080937, 725949013, 730789757,
+    735602987, 740388522, 745146182, 749875788, 754577161, 759250125, 763894504, 768510122,
+    773096806, 777654384, 782182683, 786681534, 791150767, 795590213, 799999706, 804379079,
+    808728167, 813046808, 817334838, 821592095, 825818421, 830013654, 834177638, 838310216,
+    842411232, 846480531, 850517961, 854523370, 858496606, 862437520, 866345964, 870221790,
+    874064853, 877875009, 881652112, 885396022, 889106597, 892783698, 896427186, 900036924,
+    903612776, 907154608, 910662286, 914135678, 917574653, 920979082, 924348837, 927683790,
+    930983817, 934248793, 937478595, 940673101, 943832191, 946955747, 950043650, 953095785,
+    956112036, 959092290, 962036435, 964944360, 967815955, 970651112, 973449725, 976211688,
+    978936898, 981625251, 984276646, 986890984, 989468165, 992008094, 994510675, 996975812,
+    999403415, 1001793390, 1004145648, 1006460100, 1008736660, 1010975242, 1013175761, 1015338134,
+    1017462281, 1019548121, 1021595575, 1023604567, 1025575020, 1027506862, 1029400018, 1031254418,
+    1033069992, 1034846671, 1036584389, 1038283080, 1039942680, 1041563127, 1043144360, 1044686319,
+    1046188946, 1047652185, 1049075980, 1050460278, 1051805027, 1053110176, 1054375676, 1055601479,
+    1056787540, 1057933813, 1059040255, 1060106826, 1061133483, 1062120190, 1063066909, 1063973603,
+    1064840240, 1065666786, 1066453210, 1067199483, 1067905576, 1068571464, 1069197120, 1069782521,
+    1070327646, 1070832474, 1071296985, 1071721163, 1072104991, 1072448455, 1072751542, 1073014240,
+    1073236540, 1073418433, 1073559913, 1073660973, 1073721611, 1073741824,
+];
+
+// atan(i / 256) in Q2.30 radians
+const ATAN_TABLE: [u32; 257] = [
+    0, 4194283, 8388437, 12582336, 16775851, 20968854, 25161218, 29352814, 33543516, 37733196,
+    41921726, 46108981, 50294833, 54479155, 58661822, 62842708, 67021687, 71198634, 75373424,
+    79545932, 83716036, 87883610, 92048532, 96210679, 100369930, 104526161, 108679253, 112829084,
+    116975536, 121118487, 125257820, 129393416, 133525159, 137652930, 141776614, 145896097,
+    150011262, 154121996, 158228185, 162329719, 166426484, 170518371, 174605269, 178687069,
+    182763663, 186834944, 190900805, 194961140, 199015846, 203064818, 207107953, 211145151,
+    215176309, 219201328, 223220110, 227232556, 231238569, 235238055, 239230917, 243217063,
+    247196400, 251168835, 255134279, 259092643, 263043837, 266987774, 270924369, 274853536,
+    278775192, 282689253, 286595638, 290494267, 294385059, 298267937, 302142824, 306009643,
+    309868320, 313718782, 317560955, 321394768, 325220151, 329037035, 332845353, 336645037,
+    340436023, 344218245, 347991640, 351756148, 355511705, 359258254, 362995735, 366724092,
+    370443267, 374153206, 377853855, 381545162, 385227074, 388899541, 392562515, 396215946,
+    399859787, 403493994, 407118521, 410733324, 414338361, 417933591, 421518973, 425094468,
+    428660037, 432215645, 435761254, 439296830, 442822340, 446337750, 449843028, 453338145,
+    456823070, 460297774, 463762232, 467216414, 470660297, 474093856, 477517067, 480929907,
+    484332355, 487724391, 491105994, 494477146, 497837829, 501188027, 504527723, 507856902,
+    511175551, 514483656, 517781204, 521068185, 524344587, 527610402, 530865619, 534110231,
+    537344232, 540567613, 543780370, 546982499, 550173994, 553354853, 556525073, 559684652,
+    562833591, 565971887, 569099543, 572216558, 575322936, 578418678, 581503788, 584578271,
+    587642129, 590695370, 593737999, 596770023, 599791448, 602802283, 605802536, 608792216,
+    611771334, 614739898, 617697921, 620645413, 623582386, 626508854, 629424828, 632330323,
+    635225352, 638109930, 640984073, 643847795, 646701114, 649544044, 652376604, 655198810,
+    658010682, 660812236, 663603492, 666384468, 669155185, 671915663, 674665921, 677405981,
+    680135863, 682855589, 685565182, 688264663, 690954054, 693633380, 696302662, 698961924,
+    701611191, 704250487, 706879836, 709499262, 712108791, 714708448, 717298260, 719878250,
+    722448447, 725008876, 727559563, 730100536, 732631822, 735153448, 737665442, 740167831,
+    742660643, 745143906, 747617650, 750081902, 752536690, 754982045, 757417995, 759844569,
+    762261796, 764669707, 767068330, 769457696, 771837835, 774208776, 776570551, 778923188,
+    781266719, 783601175, 785926586, 788242982, 790550395, 792848855, 795138394, 797419043,
+    799690833, 801953796, 804207961, 806453363, 808690030, 810917996, 813137292, 815347949,
+    817549999, 819743474, 821928406, 824104826, 826272767, 828432260, 830583337, 832726030,
+    834860371, 836986393, 839104126, 841213603, 843314857,
+];
+
+// 2^(i / 256) in Q2.30
+const EXP2_TABLE: [u32; 257] = [
+    1073741824, 1076653033, 1079572136, 1082499153, 1085434106, 1088377016, 1091327906, 1094286796,
+    1097253708, 1100228665, 1103211687, 1106202798, 1109202018, 1112209370, 1115224875, 1118248556,
+    1121280436, 1124320536, 1127368878, 1130425485, 1133490379, 1136563583, 1139645120, 1142735011,
+    1145833280, 1148939949, 1152055042, 1155178580, 1158310587, 1161451085, 1164600099, 1167757650,
+    1170923762, 1174098458, 1177281762, 1180473697, 1183674286, 1186883552, 1190101520, 1193328213,
+    1196563654, 1199807867, 1203060876, 1206322705, 1209593378, 1212872918, 1216161350, 1219458698,
+    1222764986, 1226080238, 1229404479, 1232737732, 1236080024, 1239431376, 1242791816, 1246161366,
+    1249540052, 1252927899, 1256324931, 1259731174, 1263146652, 1266571390, 1270005413, 1273448747,
+    1276901417, 1280363448, 1283834865, 1287315695, 1290805962, 1294305692, 1297814910, 1301333643,
+    1304861917, 1308399756, 1311947188, 1315504238, 1319070932, 1322647296, 1326233356, 1329829140,
+    1333434672, 1337049980, 1340675091, 1344310030, 1347954824, 1351609500, 1355274085, 1358948606,
+    1362633090, 1366327563, 1370032052, 1373746586, 1377471191, 1381205894, 1384950723, 1388705706,
+    1392470869, 1396246240, 1400031848, 1403827719, 1407633882, 1411450365, 1415277195, 1419114401,
+    1422962010, 1426820052, 1430688553, 1434567544, 1438457051, 1442357104, 1446267730, 1450188960,
+    1454120821, 1458063343, 1462016553, 1465980482, 1469955159, 1473940611, 1477936870, 1481943963,
+    1485961921, 1489990772, 1494030547, 1498081275, 1502142985, 1506215708, 1510299473, 1514394310,
+    1518500250, 1522617322, 1526745556, 1530884983, 1535035634, 1539197537, 1543370725, 1547555228,
+    1551751076, 1555958300, 1560176931, 1564406999, 1568648537, 1572901575, 1577166143, 1581442275,
+    1585730000, 1590029350, 1594340357, 1598663052, 1602997467, 1607343634, 1611701585, 1616071351,
+    1620452965, 1624846459, 1629251865, 1633669214, 1638098541, 1642539877, 1646993254, 1651458706,
+    1655936265, 1660425963, 1664927835, 1669441912, 1673968228, 1678506817, 1683057710, 1687620943,
+    1692196547, 1696784557, 1701385007, 1705997930, 1710623359, 1715261330, 1719911875, 1724575029,
+    1729250827, 1733939301, 1738640488, 1743354420, 1748081133, 1752820662, 1757573041, 1762338305,
+    1767116489, 1771907628, 1776711757, 1781528911, 1786359126, 1791202437, 1796058879, 1800928489,
+    1805811301, 1810707353, 1815616678, 1820539314, 1825475297, 1830424663, 1835387448, 1840363688,
+    1845353420, 1850356681, 1855373507, 1860403934, 1865448001, 1870505744, 1875577199, 1880662405,
+    1885761398, 1890874216, 1896000896, 1901141476, 1906295993, 1911464486, 1916646992, 1921843549,
+    1927054196, 1932278970, 1937517909, 1942771053, 1948038440, 1953320108, 1958616096, 1963926443,
+    1969251188, 1974590370, 1979944027, 1985312200, 1990694927, 1996092249, 2001504204, 2006930832,
+    2012372174, 2017828268, 2023299156, 2028784876, 2034285470, 2039800978, 2045331439, 2050876895,
+    2056437387, 2062012954, 2067603638, 2073209480, 2078830522, 2084466803, 2090118366, 2095785251,
+    2101467502, 2107165158, 2112878262, 2118606857, 2124350982, 2130110682, 2135885998, 2141676973,
+    2147483648,
+];
+
+const PI_Q30: i64 = 3373259426;
+const FRAC_PI_2_Q30: i64 = 1686629713;
+/// 2^64 / tau, for converting radians to a binary angle where 2^32 is a full turn
+const INV_TAU_Q64: i128 = 2935890503282001226;
+
+/// Binary angle to sin in Q2.30
+fn sin_bam(bam: u32) -> i64 {
+    let i = (bam >> 22) as usize;
+    let f = ((bam >> 6) & 0xffff) as i64;
+    let quadrant = i >> 8;
+    let k = i & 255;
+    let (a, b) = if quadrant & 1 == 0 {
+        (SIN_TABLE[k] as i64, SIN_TABLE[k + 1] as i64)
+    } else {
+        (SIN_TABLE[256 - k] as i64, SIN_TABLE[255 - k] as i64)
+    };
+    let v = a + (((b - a) * f) >> 16);
+    if quadrant >= 2 {
+        -v
+    } else {
+        v
+    }
+}
+
+/// Returns angle in Q2.30 radians for any pair of equally scaled values
+fn atan2_q30(y: i128, x: i128) -> i64 {
+    if x == 0 && y == 0 {
+        return 0;
+    }
+    let ax = x.abs();
+    let ay = y.abs();
+    let (num, den, swap) = if ay <= ax { (ay, ax, false) } else { (ax, ay, true) };
+    let r = (num << 24) / den;
+    let i = (r >> 16) as usize;
+    let f = (r & 0xffff) as i64;
+    let a = if i >= 256 {
+        ATAN_TABLE[256] as i64
+    } else {
+        let a0 = ATAN_TABLE[i] as i64;
+        let a1 = ATAN_TABLE[i + 1] as i64;
+        a0 + (((a1 - a0) * f) >> 16)
+    };
+    let a = if swap { FRAC_PI_2_Q30 - a } else { a };
+    let a = if x < 0 { PI_Q30 - a } else { a };
+    if y < 0 {
+        -a
+    } else {
+        a
+    }
+}
+
+/// 2^f in Q2.30 for fractional part f given in Q8.16 steps of the table
+fn exp2_frac_q30(f: i64) -> i64 {
+    let i = (f >> 16) as usize;
+    let w = f & 0xffff;
+    let a = EXP2_TABLE[i] as i64;
+    let b = EXP2_TABLE[i + 1] as i64;
+    a + (((b - a) * w) >> 16)
+}
+
+fn isqrt(x: u128) -> u128 {
+    if x < 2 {
+        return x;
+    }
+    let mut result = 0u128;
+    let mut bit = 1u128 << ((127 - x.leading_zeros()) & !1);
+    let mut x = x;
+    while bit != 0 {
+        if x >= result + bit {
+            x -= result + bit;
+            result = (result >> 1) + bit;
+        } else {
+            result >>= 1;
+        }
+        bit >>= 2;
+    }
+    result
+}
+
+macro_rules! fixed_scalar {
+    ($name:ident, $raw:ty, $wide:ty, $frac:expr) => {
+        #[derive(Default, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash)]
+        pub struct $name($raw);
+
+        impl $name {
+            pub const FRAC_BITS: u32 = $frac;
+            pub const ZERO: Self = Self(0);
+            pub const ONE: Self = Self(1 << $frac);
+            pub const HALF: Self = Self(1 << ($frac - 1));
+            pub const TWO: Self = Self(2 << $frac);
+            pub const EPSILON: Self = Self(1);
+            pub const MIN: Self = Self(<$raw>::MIN);
+            pub const MAX: Self = Self(<$raw>::MAX);
+            pub const PI: Self = Self::from_q30(PI_Q30);
+            pub const FRAC_PI_2: Self = Self::from_q30(FRAC_PI_2_Q30);
+            pub const TAU: Self = Self::from_q30(PI_Q30 * 2);
+
+            pub const fn from_bits(bits: $raw) -> Self {
+                Self(bits)
+            }
+
+            pub const fn to_bits(self) -> $raw {
+                self.0
+            }
+
+            pub const fn from_int(x: $raw) -> Self {
+                Self(x << $frac)
+            }
+
+            /// Rounds to nearest. Usable in const context for literals.
+            pub const fn from_f64(x: f64) -> Self {
+                let scaled = x * (1u64 << $frac) as f64;
+                Self((if scaled >= 0.0 { scaled + 0.5 } else { scaled - 0.5 }) as $raw)
+            }
+
+            pub fn from_f32(x: f32) -> Self {
+                Self::from_f64(x as f64)
+            }
+
+            /// Exact ratio of two integers, truncated toward zero
+            pub fn from_ratio(num: $raw, den: $raw) -> Self {
+                Self((((num as $wide) << $frac) / den as $wide) as $raw)
+            }
+
+            const fn from_q30(x: i64) -> Self {
+                Self((((x as i128) << $frac) >> 30) as $raw)
+            }
+
+            pub fn to_f64(self) -> f64 {
+                self.0 as f64 / (1u64 << $frac) as f64
+            }
+
+            pub fn to_f32(self) -> f32 {
+                self.to_f64() as f32
+            }
+
+            /// Rounds toward negative infinity
+            pub fn to_int(self) -> $raw {
+                self.0 >> $frac
+            }
+
+            /// Rounds halves toward positive infinity
+            pub fn round_to_int(self) -> $raw {
+                // Adding the half bit after shifting can't overflow
+                (self.0 >> $frac) + ((self.0 >> ($frac - 1)) & 1)
+            }
+
+            pub fn floor(self) -> Self {
+                Self(self.0 & !((1 << $frac) - 1))
+            }
+
+            pub fn ceil(self) -> Self {
+                (self + Self(((1 as $raw) << $frac) - 1)).floor()
+            }
+
+            pub fn fract(self) -> Self {
+                Self(self.0 & ((1 << $frac) - 1))
+            }
+
+            pub fn abs(self) -> Self {
+                Self(self.0.abs())
+            }
+
+            pub fn signum(self) -> Self {
+                Self::from_int(self.0.signum())
+            }
+
+            pub fn is_negative(self) -> bool {
+                self.0 < 0
+            }
+
+            pub fn lerp(self, rhs: Self, t: Self) -> Self {
+                self + (rhs - self) * t
+            }
+
+            /// Returns zero for negative input
+            pub fn sqrt(self) -> Self {
+                if self.0 <= 0 {
+                    Self::ZERO
+                } else {
+                    Self(isqrt((self.0 as u128) << $frac) as $raw)
+                }
+            }
+
+            fn to_bam(self) -> u32 {
+                (((self.0 as i128) * INV_TAU_Q64) >> ($frac + 32)) as u32
+            }
+
+            /// Input in radians
+            pub fn sin(self) -> Self {
+                Self::from_q30(sin_bam(self.to_bam()))
+            }
+
+            /// Input in radians
+            pub fn cos(self) -> Self {
+                Self::from_q30(sin_bam(self.to_bam().wrapping_add(1 << 30)))
+            }
+
+            pub fn sin_cos(self) -> (Self, Self) {
+                let bam = self.to_bam();
+                (
+                    Self::from_q30(sin_bam(bam)),
+                    Self::from_q30(sin_bam(bam.wrapping_add(1 << 30))),
+                )
+            }
+
+            /// Saturates near the poles
+            pub fn tan(self) -> Self {
+                let (s, c) = self.sin_cos();
+                if c.0 == 0 {
+                    if s.0 < 0 {
+                        Self::MIN
+                    } else {
+                        Self::MAX
+                    }
+                } else {
+                    s / c
+                }
+            }
+
+            /// Returns angle in radians in range [-pi, pi]
+            pub fn atan2(self, x: Self) -> Self {
+                Self::from_q30(atan2_q30(self.0 as i128, x.0 as i128))
+            }
+
+            pub fn atan(self) -> Self {
+                self.atan2(Self::ONE)
+            }
+
+            /// Saturates on overflow
+            pub fn exp2(self) -> Self {
+                let n = (self.0 >> $frac) as i64;
+                let f = ((((self.0 & ((1 << $frac) - 1)) as i128) << 24) >> $frac) as i64;
+                let v = ((exp2_frac_q30(f) as i128) << $frac) >> 30;
+                let bits = <$raw>::BITS as i64;
+                if n >= bits - 1 {
+                    Self::MAX
+                } else if n <= -bits - 2 {
+                    Self::ZERO
+                } else {
+                    let v = if n >= 0 { v << n } else { v >> -n };
+                    Self(v.min(<$raw>::MAX as i128) as $raw)
+                }
+            }
+        }
+
+        impl fmt::Debug for $name {
+            fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+                write!(f, "{}", self.to_f64())
+            }
+        }
+
+        impl fmt::Display for $name {
+            fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+                write!(f, "{}", self.to_f64())
+            }
+        }
+
+        impl ops::Add for $name {
+            type Output = Self;
+            fn add(self, rhs: Self) -> Self {
+                Self(self.0.wrapping_add(rhs.0))
+            }
+        }
+
+        impl ops::Sub for $name {
+            type Output = Self;
+            fn sub(self, rhs: Self) -> Self {
+                Self(self.0.wrapping_sub(rhs.0))
+            }
+        }
+
+        impl ops::Mul for $name {
+            type Output = Self;
+            fn mul(self, rhs: Self) -> Self {
+                Self(((self.0 as $wide * rhs.0 as $wide) >> $frac) as $raw)
+            }
+        }
+
+        impl ops::Div for $name {
+            type Output = Self;
+            fn div(self, rhs: Self) -> Self {
+                Self((((self.0 as $wide) << $frac) / rhs.0 as $wide) as $raw)
+            }
+        }
+
+        impl ops::Rem for $name {
+            type Output = Self;
+            fn rem(self, rhs: Self) -> Self {
+                Self(self.0 % rhs.0)
+            }
+        }
+
+        impl ops::Neg for $name {
+            type Output = Self;
+            fn neg(self) -> Self {
+                Self(self.0.wrapping_neg())
+            }
+        }
+
+        impl ops::AddAssign for $name {
+            fn add_assign(&mut self, rhs: Self) {
+                *self = *self + rhs;
+            }
+        }
+
+        impl ops::SubAssign for $name {
+            fn sub_assign(&mut self, rhs: Self) {
+                *self = *self - rhs;
+            }
+        }
+
+        impl ops::MulAssign for $name {
+            fn mul_assign(&mut self, rhs: Self) {
+                *self = *self * rhs;
+            }
+        }
+
+        impl ops::DivAssign for $name {
+            fn div_assign(&mut self, rhs: Self) {
+                *self = *self / rhs;
+            }
+        }
+    };
+}
+
+fixed_scalar!(Fx32, i32, i64, 16);
+fixed_scalar!(Fx64, i64, i128, 32);
+
+impl Fx32 {
+    pub fn to_fx64(self) -> Fx64 {
+        Fx64::from_bits((self.0 as i64) << 16)
+    }
+}
+
+impl Fx64 {
+    /// Truncates fractional bits and wraps out of range values
+    pub fn to_fx32(self) -> Fx32 {
+        Fx32::from_bits((self.0 >> 16) as i32)
+    }
+}
+
+macro_rules! fixed_vec2 {
+    ($name:ident, $fx:ident, $raw:ty, $wide:ty) => {
+        #[derive(Debug, Default, Clone, Copy, PartialEq, Eq, Hash)]
+        pub struct $name {
+            pub x: $fx,
+            pub y: $fx,
+        }
+
+        impl $name {
+            pub const ZERO: Self = Self::splat($fx::ZERO);
+            pub const ONE: Self = Self::splat($fx::ONE);
+            pub const X: Self = Self::new($fx::ONE, $fx::ZERO);
+            pub const Y: Self = Self::new($fx::ZERO, $fx::ONE);
+
+            pub const fn new(x: $fx, y: $fx) -> Self {
+                Self { x, y }
+            }
+
+            pub const fn splat(v: $fx) -> Self {
+                Self::new(v, v)
+            }
+
+            pub fn from_vec2(v: Vec2) -> Self {
+                Self::new($fx::from_f32(v.x), $fx::from_f32(v.y))
+            }
+
+            pub fn to_vec2(self) -> Vec2 {
+                Vec2::new(self.x.to_f32(), self.y.to_f32())
+            }
+
+            pub fn from_ivec2(v: IVec2) -> Self {
+                Self::new($fx::from_int(v.x as $raw), $fx::from_int(v.y as $raw))
+            }
+
+            /// Rounds toward negative infinity
+            pub fn to_ivec2(self) -> IVec2 {
+                IVec2::new(self.x.to_int() as i32, self.y.to_int() as i32)
+            }
+
+            pub fn dot(self, rhs: Self) -> $fx {
+                self.x * rhs.x + self.y * rhs.y
+            }
+
+            pub fn perp(self) -> Self {
+                Self::new(-self.y, self.x)
+            }
+
+            pub fn perp_dot(self, rhs: Self) -> $fx {
+                self.x * rhs.y - self.y * rhs.x
+            }
+
+            pub fn length_squared(self) -> $fx {
+                self.dot(self)
+            }
+
+            /// Computed at double width so it doesn't overflow for large vectors
+            pub fn length(self) -> $fx {
+                let x = self.x.to_bits() as $wide;
+                let y = self.y.to_bits() as $wide;
+                $fx::from_bits(isqrt((x * x) as u128 + (y * y) as u128) as $raw)
+            }
+
+            pub fn distance(self, rhs: Self) -> $fx {
+                (self - rhs).length()
+            }
+
+            pub fn normalize_or_zero(self) -> Self {
+                let len = self.length();
+                if len == $fx::ZERO {
+                    Self::ZERO
+                } else {
+                    Self::new(self.x / len, self.y / len)
+                }
+            }
+
+            pub fn min(self, rhs: Self) -> Self {
+                Self::new(self.x.min(rhs.x), self.y.min(rhs.y))
+            }
+
+            pub fn max(self, rhs: Self) -> Self {
+                Self::new(self.x.max(rhs.x), self.y.max(rhs.y))
+            }
+
+            pub fn clamp(self, min: Self, max: Self) -> Self {
+                self.max(min).min(max)
+            }
+
+            pub fn abs(self) -> Self {
+                Self::new(self.x.abs(), self.y.abs())
+            }
+
+            pub fn lerp(self, rhs: Self, t: $fx) -> Self {
+                self + (rhs - self) * t
+            }
+        }
+
+        impl ops::Add for $name {
+            type Output = Self;
+            fn add(self, rhs: Self) -> Self {
+                Self::new(self.x + rhs.x, self.y + rhs.y)
+            }
+        }
+
+        impl ops::Sub for $name {
+            type Output = Self;
+            fn sub(self, rhs: Self) -> Self {
+                Self::new(self.x - rhs.x, self.y - rhs.y)
+            }
+        }
+
+        impl ops::Mul for $name {
+            type Output = Self;
+            fn mul(self, rhs: Self) -> Self {
+                Self::new(self.x * rhs.x, self.y * rhs.y)
+            }
+        }
+
+        impl ops::Mul<$fx> for $name {
+            type Output = Self;
+            fn mul(self, rhs: $fx) -> Self {
+                Self::new(self.x * rhs, self.y * rhs)
+            }
+        }
+
+        impl ops::Div<$fx> for $name {
+            type Output = Self;
+            fn div(self, rhs: $fx) -> Self {
+                Self::new(self.x / rhs, self.y / rhs)
+            }
+        }
+
+        impl ops::Neg for $name {
+            type Output = Self;
+            fn neg(self) -> Self {
+                Self::new(-self.x, -self.y)
+            }
+        }
+
+        impl ops::AddAssign for $name {
+            fn add_assign(&mut self, rhs: Self) {
+                *self = *self + rhs;
+            }
+        }
+
+        impl ops::SubAssign for $name {
+            fn sub_assign(&mut self, rhs: Self) {
+                *self = *self - rhs;
+            }
+        }
+    };
+}
+
+macro_rules! fixed_vec3 {
+    ($name:ident, $fx:ident, $raw:ty, $wide:ty) => {
+        #[derive(Debug, Default, Clone, Copy, PartialEq, Eq, Hash)]
+        pub struct $name {
+            pub x: $fx,
+            pub y: $fx,
+            pub z: $fx,
+        }
+
+        impl $name {
+            pub const ZERO: Self = Self::splat($fx::ZERO);
+            pub const ONE: Self = Self::splat($fx::ONE);
+            pub const X: Self = Self::new($fx::ONE, $fx::ZERO, $fx::ZERO);
+            pub const Y: Self = Self::new($fx::ZERO, $fx::ONE, $fx::ZERO);
+            pub const Z: Self = Self::new($fx::ZERO, $fx::ZERO, $fx::ONE);
+
+            pub const fn new(x: $fx, y: $fx, z: $fx) -> Self {
+                Self { x, y, z }
+            }
+
+            pub const fn splat(v: $fx) -> Self {
+                Self::new(v, v, v)
+            }
+
+            pub fn from_vec3(v: Vec3) -> Self {
+                Self::new($fx::from_f32(v.x), $fx::from_f32(v.y), $fx::from_f32(v.z))
+            }
+
+            pub fn to_vec3(self) -> Vec3 {
+                Vec3::new(self.x.to_f32(), self.y.to_f32(), self.z.to_f32())
+            }
+
+            pub fn from_ivec3(v: IVec3) -> Self {
+                Self::new(
+                    $fx::from_int(v.x as $raw),
+                    $fx::from_int(v.y as $raw),
+                    $fx::from_int(v.z as $raw),
+                )
+            }
+
+            /// Rounds toward negative infinity
+            pub fn to_ivec3(self) -> IVec3 {
+                IVec3::new(
+                    self.x.to_int() as i32,
+                    self.y.to_int() as i32,
+                    self.z.to_int() as i32,
+                )
+            }
+
+            pub fn dot(self, rhs: Self) -> $fx {
+                self.x * rhs.x + self.y * rhs.y + self.z * rhs.z
+            }
+
+            pub fn cross(self, rhs: Self) -> Self {
+                Self::new(
+                    self.y * rhs.z - self.z * rhs.y,
+                    self.z * rhs.x - self.x * rhs.z,
+                    self.x * rhs.y - self.y * rhs.x,
+                )
+            }
+
+            pub fn length_squared(self) -> $fx {
+                self.dot(self)
+            }
+
+            /// Computed at double width so it doesn't overflow for large vectors
+            pub fn length(self) -> $fx {
+                let x = self.x.to_bits() as $wide;
+                let y = self.y.to_bits() as $wide;
+                let z = self.z.to_bits() as $wide;
+                $fx::from_bits(isqrt((x * x) as u128 + (y * y) as u128 + (z * z) as u128) as $raw)
+            }
+
+            pub fn distance(self, rhs: Self) -> $fx {
+                (self - rhs).length()
+            }
+
+            pub fn normalize_or_zero(self) -> Self {
+                let len = self.length();
+                if len == $fx::ZERO {
+                    Self::ZERO
+                } else {
+                    Self::new(self.x / len, self.y / len, self.z / len)
+                }
+            }
+
+            pub fn min(self, rhs: Self) -> Self {
+                Self::new(self.x.min(rhs.x), self.y.min(rhs.y), self.z.min(rhs.z))
+            }
+
+            pub fn max(self, rhs: Self) -> Self {
+                Self::new(self.x.max(rhs.x), self.y.max(rhs.y), self.z.max(rhs.z))
+            }
+
+            pub fn clamp(self, min: Self, max: Self) -> Self {
+                self.max(min).min(max)
+            }
+
+            pub fn abs(self) -> Self {
+                Self::new(self.x.abs(), self.y.abs(), self.z.abs())
+            }
+
+            pub fn lerp(self, rhs: Self, t: $fx) -> Self {
+                self + (rhs - self) * t
+            }
+        }
+
+        impl ops::Add for $name {
+            type Output = Self;
+            fn add(self, rhs: Self) -> Self {
+                Self::new(self.x + rhs.x, self.y + rhs.y, self.z + rhs.z)
+            }
+        }
+
+        impl ops::Sub for $name {
+            type Output = Self;
+            fn sub(self, rhs: Self) -> Self {
+                Self::new(self.x - rhs.x, self.y - rhs.y, self.z - rhs.z)
+            }
+        }
+
+        impl ops::Mul for $name {
+            type Output = Self;
+            fn mul(self, rhs: Self) -> Self {
+                Self::new(self.x * rhs.x, self.y * rhs.y, self.z * rhs.z)
+            }
+        }
+
+        impl ops::Mul<$fx> for $name {
+            type Output = Self;
+            fn mul(self, rhs: $fx) -> Self {
+                Self::new(self.x * rhs, self.y * rhs, self.z * rhs)
+            }
+        }
+
+        impl ops::Div<$fx> for $name {
+            type Output = Self;
+            fn div(self, rhs: $fx) -> Self {
+                Self::new(self.x / rhs, self.y / rhs, self.z / rhs)
+            }
+        }
+
+        impl ops::Neg for $name {
+            type Output = Self;
+            fn neg(self) -> Self {
+                Self::new(-self.x, -self.y, -self.z)
+            }
+        }
+
+        impl ops::AddAssign for $name {
+            fn add_assign(&mut self, rhs: Self) {
+                *self = *self + rhs;
+            }
+        }
+
+        impl ops::SubAssign for $name {
+            fn sub_assign(&mut self, rhs: Self) {
+                *self = *self - rhs;
+            }
+        }
+    };
+}
+
+fixed_vec2!(Fx32Vec2, Fx32, i32, i64);
+fixed_vec2!(Fx64Vec2, Fx64, i64, i128);
+fixed_vec3!(Fx32Vec3, Fx32, i32, i64);
+fixed_vec3!(Fx64Vec3, Fx64, i64, i128);
+
+/// Fixed-point counterpart of Range2
+#[derive(Debug, Default, PartialEq, Eq, Hash, Clone, Copy)]
+pub struct Fx32Range2 {
+    pub min: Fx32Vec2,
+    pub max: Fx32Vec2,
+}
+
+impl Fx32Range2 {
+    pub const ZERO: Self = Self {
+        min: Fx32Vec2::ZERO,
+        max: Fx32Vec2::ZERO,
+    };
+
+    pub const ZERO_TO_ONE: Self = Self {
+        min: Fx32Vec2::ZERO,
+        max: Fx32Vec2::ONE,
+    };
+
+    pub fn new(min: Fx32Vec2, max: Fx32Vec2) -> Self {
+        Self { min, max }
+    }
+
+    pub fn sized(min: Fx32Vec2, size: Fx32Vec2) -> Self {
+        Self {
+            min,
+            max: min + size,
+        }
+    }
+
+    pub fn centered(center: Fx32Vec2, size: Fx32Vec2) -> Self {
+        Self::sized(center - size * Fx32::HALF, size)
+    }
+
+    pub fn from_range2(r: Range2) -> Self {
+        Self::new(Fx32Vec2::from_vec2(r.min), Fx32Vec2::from_vec2(r.max))
+    }
+
+    pub fn to_range2(&self) -> Range2 {
+        Range2::new(self.min.to_vec2(), self.max.to_vec2())
+    }
+
+    pub fn center(&self) -> Fx32Vec2 {
+        (self.min + self.max) * Fx32::HALF
+    }
+
+    pub fn size(&self) -> Fx32Vec2 {
+        self.max - self.min
+    }
+
+    pub fn lerp(&self, t: Fx32Vec2) -> Fx32Vec2 {
+        self.min + self.size() * t
+    }
+
+    pub fn clamp(&self, p: Fx32Vec2) -> Fx32Vec2 {
+        p.clamp(self.min, self.max)
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.min.x >= self.max.x || self.min.y >= self.max.y
+    }
+
+    pub fn contains(&self, p: Fx32Vec2) -> bool {
+        p.x >= self.min.x && p.x < self.max.x && p.y >= self.min.y && p.y < self.max.y
+    }
+
+    pub fn including(&self, p: Fx32Vec2) -> Self {
+        Self::new(self.min.min(p), self.max.max(p))
+    }
+
+    pub fn expand(&self, margin: Fx32Vec2) -> Self {
+        Self {
+            min: self.min - margin,
+            max: self.max + margin,
+        }
+    }
+}
+
+impl ops::BitAnd<Self> for Fx32Range2 {
+    type Output = Self;
+    fn bitand(self, rhs: Self) -> Self {
+        Self {
+            min: self.min.max(rhs.min),
+            max: self.max.min(rhs.max),
+        }
+    }
+}
+
+impl ops::BitOr<Self> for Fx32Range2 {
+    type Output = Self;
+    fn bitor(self, rhs: Self) -> Self {
+        Self {
+            min: self.min.min(rhs.min),
+            max: self.max.max(rhs.max),
+        }
+    }
+}
+
+impl ops::Add<Fx32Vec2> for Fx32Range2 {
+    type Output = Self;
+    fn add(self, rhs: Fx32Vec2) -> Self {
+        Self {
+            min: self.min + rhs,
+            max: self.max + rhs,
+        }
+    }
+}
+
+impl ops::Sub<Fx32Vec2> for Fx32Range2 {
+    type Output = Self;
+    fn sub(self, rhs: Fx32Vec2) -> Self {
+        Self {
+            min: self.min - rhs,
+            max: self.max - rhs,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn assert_near(a: f64, b: f64, eps: f64) {
+        assert!((a - b).abs() < eps, "{} != {}", a, b);
+    }
+
+    #[test]
+    fn arithmetic() {
+        let a = Fx32::from_f32(1.5);
+        let b = Fx32::from_int(-2);
+        assert_eq!((a + b).to_f32(), -0.5);
+        assert_eq!((a * b).to_f32(), -3.0);
+        assert_eq!((a / b).to_f32(), -0.75);
+        assert_eq!(Fx32::from_f32(-1.25).floor().to_f32(), -2.0);
+        assert_eq!(Fx32::from_f32(-1.25).ceil().to_f32(), -1.0);
+        assert_eq!(Fx64::from_ratio(1, 3).to_fx32(), Fx32::from_ratio(1, 3));
+    }
+
+    #[test]
+    fn round_to_int() {
+        for (x, rounded) in [(1.5, 2), (1.25, 1), (-0.5, 0), (-1.5, -1), (-1.75, -2)] {
+            assert_eq!(Fx32::from_f32(x).round_to_int(), rounded, "{x}");
+        }
+        assert_eq!(Fx32::MAX.round_to_int(), 32768);
+        assert_eq!(Fx32::MIN.round_to_int(), -32768);
+        assert_eq!(Fx64::MAX.round_to_int(), 1 << 31);
+    }
+
+    #[test]
+    fn sqrt() {
+        assert_eq!(Fx32::from_int(16).sqrt(), Fx32::from_int(4));
+        assert_eq!(Fx64::from_int(1 << 20).sqrt(), Fx64::from_int(1 << 10));
+        assert_near(Fx32::TWO.sqrt().to_f64(), 2f64.sqrt(), 1e-4);
+        assert_eq!(Fx32::from_int(-1).sqrt(), Fx32::ZERO);
+    }
+
+    #[test]
+    fn trig_matches_float() {
+        for i in -100..100 {
+            let x = i as f64 * 0.1;
+            let a = Fx32::from_f64(x);
+            assert_near(a.sin().to_f64(), x.sin(), 1e-4);
+            assert_near(a.cos().to_f64(), x.cos(), 1e-4);
+            let b = Fx64::from_f64(x);
+            assert_near(b.sin().to_f64(), x.sin(), 1e-5);
+            assert_near(b.cos().to_f64(), x.cos(), 1e-5);
+        }
+    }
+
+    #[test]
+    fn atan2_quadrants() {
+        for i in 0..64 {
+            let angle = ((i as f64 + 0.5) / 64.0 - 0.5) * std::f64::consts::TAU;
+            let y = Fx64::from_f64(angle.sin() * 3.0);
+            let x = Fx64::from_f64(angle.cos() * 3.0);
+            assert_near(y.atan2(x).to_f64(), angle, 1e-4);
+        }
+    }
+
+    #[test]
+    fn exp2() {
+        assert_eq!(Fx32::from_int(3).exp2(), Fx32::from_int(8));
+        assert_eq!(Fx32::from_int(-2).exp2(), Fx32::from_f32(0.25));
+        assert_near(Fx32::HALF.exp2().to_f64(), 2f64.sqrt(), 1e-4);
+        assert_eq!(Fx32::from_int(40).exp2(), Fx32::MAX);
+    }
+
+    #[test]
+    fn vec_length_without_overflow() {
+        let v = Fx32Vec2::from_ivec2(IVec2::new(3000, 4000));
+        assert_eq!(v.length(), Fx32::from_int(5000));
+        let v = Fx32Vec3::from_vec3(Vec3::new(2.0, 3.0, 6.0));
+        assert_eq!(v.length(), Fx32::from_int(7));
+    }
+
+    #[test]
+    fn range_roundtrip() {
+        let r = Range2::from_x0y0x1y1(-1.5, 2.0, 3.25, 8.0);
+        let f = Fx32Range2::from_range2(r);
+        assert_eq!(f.to_range2(), r);
+        assert!(f.contains(Fx32Vec2::from_vec2(Vec2::new(0.0, 4.0))));
+        assert_eq!(f.center().to_vec2(), r.center());
+    }
+}
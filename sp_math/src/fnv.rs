@@ -28,6 +28,17 @@ pub mod fnv1a {
         let h = combine(h, x3);
         h
     }
+
+    /// Standard FNV-1a over bytes
+    pub const fn hash_bytes(bytes: &[u8]) -> u32 {
+        let mut h = SEED;
+        let mut i = 0;
+        while i < bytes.len() {
+            h = (h ^ bytes[i] as u32).wrapping_mul(PRIME);
+            i += 1;
+        }
+        h
+    }
 }
 
 pub mod fnv1a64 {
@@ -60,4 +71,101 @@ pub mod fnv1a64 {
         let h = combine(h, x3);
         h
     }
+
+    /// Standard FNV-1a over bytes, usable in constants
+    pub const fn hash_bytes(bytes: &[u8]) -> u64 {
+        hash_bytes_from(SEED, bytes)
+    }
+
+    pub const fn hash_bytes_from(hash: u64, bytes: &[u8]) -> u64 {
+        let mut h = hash;
+        let mut i = 0;
+        while i < bytes.len() {
+            h = (h ^ bytes[i] as u64).wrapping_mul(PRIME);
+            i += 1;
+        }
+        h
+    }
+}
+
+/// Streaming FNV-1a 64, fast for short keys such as ids and paths
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Fnv1a64Hasher(u64);
+
+impl Fnv1a64Hasher {
+    /// The seed replaces the standard offset basis
+    pub fn with_seed(seed: u64) -> Self {
+        Self(seed)
+    }
+}
+
+impl Default for Fnv1a64Hasher {
+    fn default() -> Self {
+        Self(fnv1a64::SEED)
+    }
+}
+
+impl std::hash::Hasher for Fnv1a64Hasher {
+    fn write(&mut self, bytes: &[u8]) {
+        self.0 = fnv1a64::hash_bytes_from(self.0, bytes);
+    }
+
+    fn finish(&self) -> u64 {
+        self.0
+    }
+}
+
+/// Builds seeded Fnv1a64Hashers for HashMap, IndexMap and the like
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Fnv1a64BuildHasher {
+    pub seed: u64,
+}
+
+impl Default for Fnv1a64BuildHasher {
+    fn default() -> Self {
+        Self {
+            seed: fnv1a64::SEED,
+        }
+    }
+}
+
+impl std::hash::BuildHasher for Fnv1a64BuildHasher {
+    type Hasher = Fnv1a64Hasher;
+
+    fn build_hasher(&self) -> Fnv1a64Hasher {
+        Fnv1a64Hasher::with_seed(self.seed)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::collections::HashMap;
+    use std::hash::{BuildHasher, Hasher};
+
+    #[test]
+    fn reference_vectors() {
+        assert_eq!(fnv1a::hash_bytes(b""), 0x811c9dc5);
+        assert_eq!(fnv1a::hash_bytes(b"a"), 0xe40c292c);
+        assert_eq!(fnv1a::hash_bytes(b"foobar"), 0xbf9cf968);
+        assert_eq!(fnv1a64::hash_bytes(b""), 0xcbf29ce484222325);
+        assert_eq!(fnv1a64::hash_bytes(b"a"), 0xaf63dc4c8601ec8c);
+        assert_eq!(fnv1a64::hash_bytes(b"foobar"), 0x85944171f73967e8);
+    }
+
+    #[test]
+    fn streaming() {
+        let mut h = Fnv1a64Hasher::default();
+        h.write(b"foo");
+        h.write(b"bar");
+        assert_eq!(h.finish(), fnv1a64::hash_bytes(b"foobar"));
+        let seeded = Fnv1a64BuildHasher { seed: 1 };
+        assert_ne!(seeded.hash_one("x"), Fnv1a64BuildHasher::default().hash_one("x"));
+        assert_eq!(seeded.hash_one("x"), seeded.hash_one("x"));
+
+        let mut map: HashMap<&str, u32, Fnv1a64BuildHasher> = HashMap::default();
+        map.insert("a", 1);
+        map.insert("b", 2);
+        assert_eq!(map.get("b"), Some(&2));
+    }
 }
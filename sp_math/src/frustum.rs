@@ -0,0 +1,196 @@
+use glam::{Mat4, Vec3, Vec4};
+
+use crate::range::{Range2, Range3};
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum Containment {
+    Outside,
+    Intersecting,
+    Inside,
+}
+
+/// Points with non-negative distance are on the inner side
+#[derive(Debug, Default, Clone, Copy, PartialEq)]
+pub struct Plane {
+    pub normal: Vec3,
+    pub d: f32,
+}
+
+impl Plane {
+    pub fn new(normal: Vec3, d: f32) -> Self {
+        Self { normal, d }
+    }
+
+    /// Normalizes a plane given as (a, b, c, d) coefficients
+    pub fn from_vec4(v: Vec4) -> Self {
+        let len = v.truncate().length();
+        let inv_len = if len > 1e-9 { 1.0 / len } else { 0.0 };
+        Self::new(v.truncate() * inv_len, v.w * inv_len)
+    }
+
+    pub fn distance(&self, p: Vec3) -> f32 {
+        self.normal.dot(p) + self.d
+    }
+}
+
+/// Planes are ordered left, right, bottom, top, near, far and face inward
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct Frustum {
+    pub planes: [Plane; 6],
+}
+
+impl Frustum {
+    /// Extracts planes from a projection-view matrix using the 0 to 1 depth
+    /// range of glam's projections (as used by wgpu).
+    pub fn from_mat4(m: Mat4) -> Self {
+        let r0 = m.row(0);
+        let r1 = m.row(1);
+        let r2 = m.row(2);
+        let r3 = m.row(3);
+        Self {
+            planes: [
+                Plane::from_vec4(r3 + r0),
+                Plane::from_vec4(r3 - r0),
+                Plane::from_vec4(r3 + r1),
+                Plane::from_vec4(r3 - r1),
+                Plane::from_vec4(r2),
+                Plane::from_vec4(r3 - r2),
+            ],
+        }
+    }
+
+    /// Frustum of a 2D camera showing the given rect, where content lies
+    /// on the z = 0 plane.
+    pub fn from_range2(view: Range2) -> Self {
+        Self::from_mat4(Mat4::orthographic_rh(
+            view.min.x, view.max.x, view.min.y, view.max.y, -1.0, 1.0,
+        ))
+    }
+
+    pub fn contains_point(&self, p: Vec3) -> bool {
+        self.planes.iter().all(|plane| plane.distance(p) >= 0.0)
+    }
+
+    /// Points are never classified as intersecting
+    pub fn classify_point(&self, p: Vec3) -> Containment {
+        if self.contains_point(p) {
+            Containment::Inside
+        } else {
+            Containment::Outside
+        }
+    }
+
+    pub fn classify_sphere(&self, center: Vec3, radius: f32) -> Containment {
+        let mut result = Containment::Inside;
+        for plane in self.planes.iter() {
+            let dist = plane.distance(center);
+            if dist < -radius {
+                return Containment::Outside;
+            }
+            if dist < radius {
+                result = Containment::Intersecting;
+            }
+        }
+        result
+    }
+
+    /// Conservative test which may report intersecting for boxes that lie
+    /// just outside a frustum corner.
+    pub fn classify_range3(&self, range: Range3) -> Containment {
+        let mut result = Containment::Inside;
+        for plane in self.planes.iter() {
+            // Corners furthest along and against the plane normal
+            let p = Vec3::select(plane.normal.cmpge(Vec3::ZERO), range.max, range.min);
+            let n = Vec3::select(plane.normal.cmpge(Vec3::ZERO), range.min, range.max);
+            if plane.distance(p) < 0.0 {
+                return Containment::Outside;
+            }
+            if plane.distance(n) < 0.0 {
+                result = Containment::Intersecting;
+            }
+        }
+        result
+    }
+
+    /// Classifies a rect lying on the z = 0 plane
+    pub fn classify_range2(&self, range: Range2) -> Containment {
+        self.classify_range3(Range3::new(range.min.extend(0.0), range.max.extend(0.0)))
+    }
+
+    pub fn intersects_sphere(&self, center: Vec3, radius: f32) -> bool {
+        self.classify_sphere(center, radius) != Containment::Outside
+    }
+
+    pub fn intersects_range3(&self, range: Range3) -> bool {
+        self.classify_range3(range) != Containment::Outside
+    }
+
+    pub fn intersects_range2(&self, range: Range2) -> bool {
+        self.classify_range2(range) != Containment::Outside
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use glam::Vec2;
+
+    fn perspective() -> Frustum {
+        // Camera at origin looking down -Z with 90 degree vertical fov
+        let proj = Mat4::perspective_rh(90f32.to_radians(), 1.0, 1.0, 100.0);
+        let view = Mat4::look_at_rh(Vec3::ZERO, -Vec3::Z, Vec3::Y);
+        Frustum::from_mat4(proj * view)
+    }
+
+    #[test]
+    fn perspective_planes() {
+        let f = perspective();
+        let near = f.planes[4];
+        let far = f.planes[5];
+        assert!((near.normal - -Vec3::Z).length() < 1e-5);
+        assert!((near.d - -1.0).abs() < 1e-4);
+        assert!((far.normal - Vec3::Z).length() < 1e-5);
+        assert!((far.d - 100.0).abs() < 1e-2);
+    }
+
+    #[test]
+    fn perspective_points() {
+        let f = perspective();
+        assert_eq!(f.classify_point(Vec3::new(0.0, 0.0, -10.0)), Containment::Inside);
+        assert_eq!(f.classify_point(Vec3::new(0.0, 0.0, 10.0)), Containment::Outside);
+        assert_eq!(f.classify_point(Vec3::new(0.0, 0.0, -0.5)), Containment::Outside);
+        assert_eq!(f.classify_point(Vec3::new(0.0, 0.0, -101.0)), Containment::Outside);
+        assert_eq!(f.classify_point(Vec3::new(9.0, 0.0, -10.0)), Containment::Inside);
+        assert_eq!(f.classify_point(Vec3::new(11.0, 0.0, -10.0)), Containment::Outside);
+    }
+
+    #[test]
+    fn perspective_spheres() {
+        let f = perspective();
+        assert_eq!(f.classify_sphere(Vec3::new(0.0, 0.0, -10.0), 1.0), Containment::Inside);
+        assert_eq!(f.classify_sphere(Vec3::new(0.0, 0.0, 10.0), 1.0), Containment::Outside);
+        assert_eq!(f.classify_sphere(Vec3::new(10.0, 0.0, -10.0), 1.0), Containment::Intersecting);
+    }
+
+    #[test]
+    fn perspective_ranges() {
+        let f = perspective();
+        let inside = Range3::centered(Vec3::new(0.0, 0.0, -10.0), Vec3::ONE);
+        let outside = Range3::centered(Vec3::new(0.0, 0.0, 10.0), Vec3::ONE);
+        let crossing = Range3::centered(Vec3::new(0.0, 0.0, -1.0), Vec3::ONE);
+        assert_eq!(f.classify_range3(inside), Containment::Inside);
+        assert_eq!(f.classify_range3(outside), Containment::Outside);
+        assert_eq!(f.classify_range3(crossing), Containment::Intersecting);
+    }
+
+    #[test]
+    fn camera_rect() {
+        let f = Frustum::from_range2(Range2::from_x0y0x1y1(0.0, 0.0, 320.0, 240.0));
+        let inside = Range2::sized(Vec2::new(10.0, 10.0), Vec2::splat(16.0));
+        let edge = Range2::sized(Vec2::new(310.0, 10.0), Vec2::splat(16.0));
+        let outside = Range2::sized(Vec2::new(-20.0, 10.0), Vec2::splat(16.0));
+        assert_eq!(f.classify_range2(inside), Containment::Inside);
+        assert_eq!(f.classify_range2(edge), Containment::Intersecting);
+        assert_eq!(f.classify_range2(outside), Containment::Outside);
+    }
+}
@@ -2,13 +2,29 @@
 
 pub mod bits;
 pub mod color;
+pub mod contour;
+pub mod delaunay;
+pub mod distr;
 pub mod ease;
+pub mod fixed;
 pub mod fnv;
+pub mod frustum;
 pub mod noise;
+pub mod pathfind;
 pub mod pcg;
+pub mod physics;
+pub mod poisson;
+pub mod polygon;
 pub mod query;
 pub mod range;
 pub mod ray;
+pub mod region;
+#[cfg(feature = "serde")]
+mod serde_impl;
+pub mod spline;
+pub mod transform;
+pub mod tween;
 pub mod xxhash;
 pub mod interp;
 pub mod vec;
+pub mod visibility;
@@ -30,6 +30,13 @@
 // simplex noise provided in the file "snoise1234.c". It just returns
 // all partial derivatives in addition to the scalar noise value.
 
+use std::f32::consts::TAU;
+
+use glam::{IVec2, IVec3, Vec2, Vec3};
+
+use crate::pcg::{pcg, PcgRng};
+use crate::xxhash;
+
 const GRAD3: [[f32; 3]; 12] = [
     [1.0f32, 1.0f32, 0.0f32],
     [-1.0f32, 1.0f32, 0.0f32],
@@ -85,6 +92,10 @@ fn dot2(gi: u8, x: f32, y: f32) -> f32 {
 }
 
 pub fn sample2(x: f32, y: f32) -> f32 {
+    simplex2(&PERM, x, y)
+}
+
+fn simplex2(perm: &[u8; 512], x: f32, y: f32) -> f32 {
     //float n0, n1, n2; // Noise contributions from the three corners
     // Skew the input space to determine which simplex cell we're in
     let s = (x + y) * F2; // Hairy factor for 2D
@@ -129,9 +140,9 @@ pub fn sample2(x: f32, y: f32) -> f32 {
     let ii = (i & 255) as usize;
     let jj = (j & 255) as usize;
 
-    let gi0 = PERM[ii + PERM[jj] as usize] % 12;
-    let gi1 = PERM[ii + i1 + PERM[jj + j1] as usize] % 12;
-    let gi2 = PERM[ii + 1 + PERM[jj + 1] as usize] % 12;
+    let gi0 = perm[ii + perm[jj] as usize] % 12;
+    let gi1 = perm[ii + i1 + perm[jj + j1] as usize] % 12;
+    let gi2 = perm[ii + 1 + perm[jj + 1] as usize] % 12;
 
     //(n * (n * n * 15731 + 789221) + 1376312589)
 
@@ -174,6 +185,10 @@ fn dot3(gi: u8, x: f32, y: f32, z: f32) -> f32 {
 }
 
 pub fn sample3(x: f32, y: f32, z: f32) -> f32 {
+    simplex3(&PERM, x, y, z)
+}
+
+fn simplex3(perm: &[u8; 512], x: f32, y: f32, z: f32) -> f32 {
     // Skew the input space to determine which simplex cell we're in
     let s = (x + y + z) * F3; // Very nice and simple skew factor for 3D
     let i = (x + s).floor() as i32;
@@ -233,10 +248,10 @@ pub fn sample3(x: f32, y: f32, z: f32) -> f32 {
     let ii = (i & 255) as usize;
     let jj = (j & 255) as usize;
     let kk = (k & 255) as usize;
-    let gi0 = PERM[ii + PERM[jj + PERM[kk] as usize] as usize] % 12;
-    let gi1 = PERM[ii + i1 + PERM[jj + j1 + PERM[kk + k1] as usize] as usize] % 12;
-    let gi2 = PERM[ii + i2 + PERM[jj + j2 + PERM[kk + k2] as usize] as usize] % 12;
-    let gi3 = PERM[ii + 1 + PERM[jj + 1 + PERM[kk + 1] as usize] as usize] % 12;
+    let gi0 = perm[ii + perm[jj + perm[kk] as usize] as usize] % 12;
+    let gi1 = perm[ii + i1 + perm[jj + j1 + perm[kk + k1] as usize] as usize] % 12;
+    let gi2 = perm[ii + i2 + perm[jj + j2 + perm[kk + k2] as usize] as usize] % 12;
+    let gi3 = perm[ii + 1 + perm[jj + 1 + perm[kk + 1] as usize] as usize] % 12;
     // Calculate the contribution from the four corners
     let t0 = 0.6f32 - x0 * x0 - y0 * y0 - z0 * z0;
     let n0 = if t0 < 0.0f32 {
@@ -275,6 +290,400 @@ pub fn sample3(x: f32, y: f32, z: f32) -> f32 {
     32.0f32 * (n0 + n1 + n2 + n3)
 }
 
+const GRAD4: [[f32; 4]; 32] = [
+    [0.0, 1.0, 1.0, 1.0],
+    [0.0, 1.0, 1.0, -1.0],
+    [0.0, 1.0, -1.0, 1.0],
+    [0.0, 1.0, -1.0, -1.0],
+    [0.0, -1.0, 1.0, 1.0],
+    [0.0, -1.0, 1.0, -1.0],
+    [0.0, -1.0, -1.0, 1.0],
+    [0.0, -1.0, -1.0, -1.0],
+    [1.0, 0.0, 1.0, 1.0],
+    [1.0, 0.0, 1.0, -1.0],
+    [1.0, 0.0, -1.0, 1.0],
+    [1.0, 0.0, -1.0, -1.0],
+    [-1.0, 0.0, 1.0, 1.0],
+    [-1.0, 0.0, 1.0, -1.0],
+    [-1.0, 0.0, -1.0, 1.0],
+    [-1.0, 0.0, -1.0, -1.0],
+    [1.0, 1.0, 0.0, 1.0],
+    [1.0, 1.0, 0.0, -1.0],
+    [1.0, -1.0, 0.0, 1.0],
+    [1.0, -1.0, 0.0, -1.0],
+    [-1.0, 1.0, 0.0, 1.0],
+    [-1.0, 1.0, 0.0, -1.0],
+    [-1.0, -1.0, 0.0, 1.0],
+    [-1.0, -1.0, 0.0, -1.0],
+    [1.0, 1.0, 1.0, 0.0],
+    [1.0, 1.0, -1.0, 0.0],
+    [1.0, -1.0, 1.0, 0.0],
+    [1.0, -1.0, -1.0, 0.0],
+    [-1.0, 1.0, 1.0, 0.0],
+    [-1.0, 1.0, -1.0, 0.0],
+    [-1.0, -1.0, 1.0, 0.0],
+    [-1.0, -1.0, -1.0, 0.0],
+];
+
+const SQRT5: f32 = 2.236068f32;
+const F4: f32 = (SQRT5 - 1.0f32) / 4.0f32;
+const G4: f32 = (5.0f32 - SQRT5) / 20.0f32;
+
+fn contrib4(gi: u8, x: f32, y: f32, z: f32, w: f32) -> f32 {
+    let t = 0.6f32 - x * x - y * y - z * z - w * w;
+    if t < 0.0f32 {
+        0.0f32
+    } else {
+        let g = GRAD4[gi as usize];
+        let t2 = t * t;
+        t2 * t2 * (g[0] * x + g[1] * y + g[2] * z + g[3] * w)
+    }
+}
+
+pub fn sample4(x: f32, y: f32, z: f32, w: f32) -> f32 {
+    simplex4(&PERM, x, y, z, w)
+}
+
+fn simplex4(perm: &[u8; 512], x: f32, y: f32, z: f32, w: f32) -> f32 {
+    // Skew the (x,y,z,w) space to determine which cell of 24 simplices we're in
+    let s = (x + y + z + w) * F4;
+    let i = (x + s).floor() as i32;
+    let j = (y + s).floor() as i32;
+    let k = (z + s).floor() as i32;
+    let l = (w + s).floor() as i32;
+    let t = (i + j + k + l) as f32 * G4;
+    let x0 = x - (i as f32 - t);
+    let y0 = y - (j as f32 - t);
+    let z0 = z - (k as f32 - t);
+    let w0 = w - (l as f32 - t);
+    // Rank the coordinates by magnitude to find the simplex traversal order
+    let mut rank = [0usize; 4];
+    let c = [x0, y0, z0, w0];
+    for a in 0..4 {
+        for b in a + 1..4 {
+            if c[a] > c[b] {
+                rank[a] += 1;
+            } else {
+                rank[b] += 1;
+            }
+        }
+    }
+    let step = |n: usize| {
+        (
+            (rank[0] >= n) as usize,
+            (rank[1] >= n) as usize,
+            (rank[2] >= n) as usize,
+            (rank[3] >= n) as usize,
+        )
+    };
+    let (i1, j1, k1, l1) = step(3);
+    let (i2, j2, k2, l2) = step(2);
+    let (i3, j3, k3, l3) = step(1);
+    let ii = (i & 255) as usize;
+    let jj = (j & 255) as usize;
+    let kk = (k & 255) as usize;
+    let ll = (l & 255) as usize;
+    let hash = |a: usize, b: usize, c: usize, d: usize| {
+        perm[ii + a + perm[jj + b + perm[kk + c + perm[ll + d] as usize] as usize] as usize] % 32
+    };
+    let mut n = contrib4(hash(0, 0, 0, 0), x0, y0, z0, w0);
+    for (o, (a, b, c, d)) in [(i1, j1, k1, l1), (i2, j2, k2, l2), (i3, j3, k3, l3), (1, 1, 1, 1)]
+        .into_iter()
+        .enumerate()
+    {
+        let g = (o + 1) as f32 * G4;
+        n += contrib4(
+            hash(a, b, c, d),
+            x0 - a as f32 + g,
+            y0 - b as f32 + g,
+            z0 - c as f32 + g,
+            w0 - d as f32 + g,
+        );
+    }
+    // Scale to stay just inside [-1,1]
+    27.0f32 * n
+}
+
+/// Simplex noise with a permutation table derived from a seed
+#[derive(Clone)]
+pub struct Simplex {
+    perm: [u8; 512],
+}
+
+impl Default for Simplex {
+    /// Matches the free sample functions
+    fn default() -> Self {
+        Self { perm: PERM }
+    }
+}
+
+impl Simplex {
+    pub fn new(seed: u64) -> Self {
+        let mut table = [0u8; 256];
+        for (i, x) in table.iter_mut().enumerate() {
+            *x = i as u8;
+        }
+        PcgRng::new(seed, 0).shuffle(&mut table);
+        let mut perm = [0u8; 512];
+        perm[..256].copy_from_slice(&table);
+        perm[256..].copy_from_slice(&table);
+        Self { perm }
+    }
+
+    pub fn sample2(&self, x: f32, y: f32) -> f32 {
+        simplex2(&self.perm, x, y)
+    }
+
+    pub fn sample3(&self, x: f32, y: f32, z: f32) -> f32 {
+        simplex3(&self.perm, x, y, z)
+    }
+
+    pub fn sample4(&self, x: f32, y: f32, z: f32, w: f32) -> f32 {
+        simplex4(&self.perm, x, y, z, w)
+    }
+
+    /// Tiles seamlessly with the given period by sampling a 4D torus
+    pub fn sample2_tiled(&self, x: f32, y: f32, period: Vec2) -> f32 {
+        let a = x / period.x * TAU;
+        let b = y / period.y * TAU;
+        let r = period / TAU;
+        self.sample4(a.cos() * r.x, a.sin() * r.x, b.cos() * r.y, b.sin() * r.y)
+    }
+
+    /// Animates 2D noise over time, looping seamlessly every period by
+    /// moving around a circle in the extra two dimensions.
+    pub fn sample2_looped(&self, x: f32, y: f32, t: f32, period: f32) -> f32 {
+        let a = t / period * TAU;
+        let r = period / TAU;
+        self.sample4(x, y, a.cos() * r, a.sin() * r)
+    }
+}
+
+/// Parameters for summing multiple octaves of noise
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct Fractal {
+    pub octaves: u32,
+    pub frequency: f32,
+    pub lacunarity: f32,
+    pub gain: f32,
+}
+
+impl Default for Fractal {
+    fn default() -> Self {
+        Self {
+            octaves: 4,
+            frequency: 1.0,
+            lacunarity: 2.0,
+            gain: 0.5,
+        }
+    }
+}
+
+impl Fractal {
+    fn accumulate(&self, mut sample: impl FnMut(f32, u32) -> f32) -> f32 {
+        let mut sum = 0.0;
+        let mut amp = 1.0;
+        let mut total = 0.0;
+        let mut freq = self.frequency;
+        for octave in 0..self.octaves {
+            sum += sample(freq, octave) * amp;
+            total += amp;
+            amp *= self.gain;
+            freq *= self.lacunarity;
+        }
+        if total > 0.0 {
+            sum / total
+        } else {
+            0.0
+        }
+    }
+
+    // Shift each octave so lattice artifacts don't line up at the origin
+    fn octave_offset(octave: u32) -> f32 {
+        octave as f32 * 19.19
+    }
+
+    /// Fractal Brownian motion in [-1, 1]
+    pub fn fbm2(&self, p: Vec2, noise: impl Fn(Vec2) -> f32) -> f32 {
+        self.accumulate(|freq, octave| noise(p * freq + Self::octave_offset(octave)))
+    }
+
+    /// Sharp ridges where noise crosses zero, in [0, 1]
+    pub fn ridged2(&self, p: Vec2, noise: impl Fn(Vec2) -> f32) -> f32 {
+        self.accumulate(|freq, octave| {
+            let n = 1.0 - noise(p * freq + Self::octave_offset(octave)).abs();
+            n * n
+        })
+    }
+
+    /// Sum of absolute noise values, in [0, 1]
+    pub fn turbulence2(&self, p: Vec2, noise: impl Fn(Vec2) -> f32) -> f32 {
+        self.accumulate(|freq, octave| noise(p * freq + Self::octave_offset(octave)).abs())
+    }
+
+    /// Fractal Brownian motion in [-1, 1]
+    pub fn fbm3(&self, p: Vec3, noise: impl Fn(Vec3) -> f32) -> f32 {
+        self.accumulate(|freq, octave| noise(p * freq + Self::octave_offset(octave)))
+    }
+
+    /// Sharp ridges where noise crosses zero, in [0, 1]
+    pub fn ridged3(&self, p: Vec3, noise: impl Fn(Vec3) -> f32) -> f32 {
+        self.accumulate(|freq, octave| {
+            let n = 1.0 - noise(p * freq + Self::octave_offset(octave)).abs();
+            n * n
+        })
+    }
+
+    /// Sum of absolute noise values, in [0, 1]
+    pub fn turbulence3(&self, p: Vec3, noise: impl Fn(Vec3) -> f32) -> f32 {
+        self.accumulate(|freq, octave| noise(p * freq + Self::octave_offset(octave)).abs())
+    }
+}
+
+/// Offsets p by noise sampled at decorrelated positions, scaled by amount
+pub fn warp2(p: Vec2, amount: f32, noise: impl Fn(Vec2) -> f32) -> Vec2 {
+    let offset = Vec2::new(noise(p), noise(p + Vec2::new(5.2, 1.3)));
+    p + offset * amount
+}
+
+pub fn warp3(p: Vec3, amount: f32, noise: impl Fn(Vec3) -> f32) -> Vec3 {
+    let offset = Vec3::new(
+        noise(p),
+        noise(p + Vec3::new(5.2, 1.3, 2.8)),
+        noise(p + Vec3::new(1.7, 9.2, 4.6)),
+    );
+    p + offset * amount
+}
+
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum Distance {
+    #[default]
+    Euclidean,
+    EuclideanSquared,
+    Manhattan,
+    Chebyshev,
+}
+
+impl Distance {
+    pub fn measure2(&self, d: Vec2) -> f32 {
+        match self {
+            Distance::Euclidean => d.length(),
+            Distance::EuclideanSquared => d.length_squared(),
+            Distance::Manhattan => d.x.abs() + d.y.abs(),
+            Distance::Chebyshev => d.x.abs().max(d.y.abs()),
+        }
+    }
+
+    pub fn measure3(&self, d: Vec3) -> f32 {
+        match self {
+            Distance::Euclidean => d.length(),
+            Distance::EuclideanSquared => d.length_squared(),
+            Distance::Manhattan => d.x.abs() + d.y.abs() + d.z.abs(),
+            Distance::Chebyshev => d.x.abs().max(d.y.abs()).max(d.z.abs()),
+        }
+    }
+}
+
+/// Distances to the nearest and second nearest feature points, plus a hash
+/// identifying the nearest cell which is useful for flat-shaded regions.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct CellSample {
+    pub f1: f32,
+    pub f2: f32,
+    pub id: u32,
+}
+
+/// Worley/cellular noise with one jittered feature point per unit cell
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct Worley {
+    pub seed: u32,
+    pub distance: Distance,
+    /// Fraction of the cell that feature points can move within, in [0, 1]
+    pub jitter: f32,
+}
+
+impl Default for Worley {
+    fn default() -> Self {
+        Self {
+            seed: 0,
+            distance: Distance::Euclidean,
+            jitter: 1.0,
+        }
+    }
+}
+
+fn hash_to_unit(h: u32) -> f32 {
+    (h >> 8) as f32 * (1.0 / 16777216.0)
+}
+
+impl Worley {
+    pub fn new(seed: u32) -> Self {
+        Self {
+            seed,
+            ..Default::default()
+        }
+    }
+
+    fn jittered(&self, h: u32) -> f32 {
+        (1.0 - self.jitter) * 0.5 + hash_to_unit(h) * self.jitter
+    }
+
+    pub fn sample2(&self, x: f32, y: f32) -> CellSample {
+        let p = Vec2::new(x, y);
+        let cell = p.floor().as_ivec2();
+        let mut result = CellSample {
+            f1: f32::MAX,
+            f2: f32::MAX,
+            id: 0,
+        };
+        for dy in -1..=1 {
+            for dx in -1..=1 {
+                let c = cell + IVec2::new(dx, dy);
+                let h = xxhash::hash_ivec2(self.seed, c);
+                let feature = c.as_vec2() + Vec2::new(self.jittered(h), self.jittered(pcg(h)));
+                let d = self.distance.measure2(feature - p);
+                if d < result.f1 {
+                    result.f2 = result.f1;
+                    result.f1 = d;
+                    result.id = h;
+                } else if d < result.f2 {
+                    result.f2 = d;
+                }
+            }
+        }
+        result
+    }
+
+    pub fn sample3(&self, x: f32, y: f32, z: f32) -> CellSample {
+        let p = Vec3::new(x, y, z);
+        let cell = p.floor().as_ivec3();
+        let mut result = CellSample {
+            f1: f32::MAX,
+            f2: f32::MAX,
+            id: 0,
+        };
+        for dz in -1..=1 {
+            for dy in -1..=1 {
+                for dx in -1..=1 {
+                    let c = cell + IVec3::new(dx, dy, dz);
+                    let h = xxhash::hash_ivec3(self.seed, c);
+                    let h2 = pcg(h);
+                    let offset = Vec3::new(self.jittered(h), self.jittered(h2), self.jittered(pcg(h2)));
+                    let d = self.distance.measure3(c.as_vec3() + offset - p);
+                    if d < result.f1 {
+                        result.f2 = result.f1;
+                        result.f1 = d;
+                        result.id = h;
+                    } else if d < result.f2 {
+                        result.f2 = d;
+                    }
+                }
+            }
+        }
+        result
+    }
+}
+
 #[cfg(test)]
 mod tests {
     #[test]
@@ -301,4 +710,100 @@ mod tests {
         let duration = start.elapsed();
         println!("Elapsed: {:?}", duration);
     }
+
+    #[test]
+    fn test_seeded_default_matches_free_fns() {
+        let noise = super::Simplex::default();
+        assert_eq!(noise.sample3(10.0, 10.0, 20.0), super::sample3(10.0, 10.0, 20.0));
+        assert_eq!(noise.sample2(1.5, 2.5), super::sample2(1.5, 2.5));
+        let a = super::Simplex::new(1);
+        let b = super::Simplex::new(2);
+        assert_ne!(a.sample2(1.5, 2.5), b.sample2(1.5, 2.5));
+        assert_eq!(a.sample2(1.5, 2.5), super::Simplex::new(1).sample2(1.5, 2.5));
+    }
+
+    #[test]
+    fn test_simplex4_range() {
+        let noise = super::Simplex::new(7);
+        let mut max = 0.0f32;
+        for i in 0..10000 {
+            let x = i as f32 * 0.137;
+            let n = noise.sample4(x, x * 0.71 + 3.0, x * 0.29 - 1.0, x * 0.53);
+            max = max.max(n.abs());
+        }
+        assert!(max <= 1.0);
+        assert!(max > 0.5);
+    }
+
+    #[test]
+    fn test_tiled_wraps() {
+        use glam::Vec2;
+        let noise = super::Simplex::new(3);
+        let period = Vec2::new(8.0, 4.0);
+        for i in 0..20 {
+            let x = i as f32 * 0.37;
+            let a = noise.sample2_tiled(x, 1.25, period);
+            let b = noise.sample2_tiled(x + period.x, 1.25 + period.y, period);
+            assert!((a - b).abs() < 1e-4);
+        }
+    }
+
+    #[test]
+    fn test_fractal_ranges() {
+        use glam::Vec2;
+        let noise = super::Simplex::new(5);
+        let fractal = super::Fractal {
+            octaves: 6,
+            ..Default::default()
+        };
+        for i in 0..1000 {
+            let p = Vec2::new(i as f32 * 0.173, i as f32 * 0.091);
+            let sample = |p: Vec2| noise.sample2(p.x, p.y);
+            assert!(fractal.fbm2(p, sample).abs() <= 1.0);
+            assert!((0.0..=1.0).contains(&fractal.ridged2(p, sample)));
+            assert!((0.0..=1.0).contains(&fractal.turbulence2(p, sample)));
+        }
+    }
+
+    #[test]
+    fn test_worley_feature_distance() {
+        let worley = super::Worley::new(11);
+        for i in 0..1000 {
+            let s = worley.sample2(i as f32 * 0.173, i as f32 * 0.311);
+            assert!(s.f1 <= s.f2);
+            // Nearest feature is never further than the cell diagonal
+            assert!(s.f1 <= 2f32.sqrt());
+        }
+        let grid = super::Worley {
+            jitter: 0.0,
+            ..Default::default()
+        };
+        assert!(grid.sample3(0.5, 0.5, 0.5).f1 < 1e-6);
+    }
+
+    #[test]
+    fn test_fractal_perf() {
+        use glam::Vec3;
+        use std::time::Instant;
+        let noise = super::Simplex::new(1);
+        let fractal = super::Fractal::default();
+        let start = Instant::now();
+        for z in 0..10 {
+            for y in 0..100 {
+                for x in 0..100 {
+                    let p = Vec3::new(x as f32, y as f32, z as f32) * 0.1;
+                    fractal.fbm3(p, |p| noise.sample3(p.x, p.y, p.z));
+                }
+            }
+        }
+        println!("fBm elapsed: {:?}", start.elapsed());
+        let worley = super::Worley::default();
+        let start = Instant::now();
+        for y in 0..100 {
+            for x in 0..1000 {
+                worley.sample2(x as f32 * 0.1, y as f32 * 0.1);
+            }
+        }
+        println!("Worley elapsed: {:?}", start.elapsed());
+    }
 }
\ No newline at end of file
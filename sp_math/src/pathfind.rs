@@ -0,0 +1,629 @@
+// Grid pathfinding with integer costs, so results are identical on every
+// machine. Ties in the open set are broken by cell position.
+
+use std::cmp::Reverse;
+use std::collections::BinaryHeap;
+
+use glam::IVec2;
+
+use crate::range::IRange2;
+use crate::visibility::supercover;
+
+/// Cost of a straight step into a cell of cost 1
+pub const STRAIGHT_COST: u32 = 100;
+/// Cost of a diagonal step into a cell of cost 1
+pub const DIAGONAL_COST: u32 = 141;
+
+const UNREACHED: u32 = u32::MAX;
+
+const STRAIGHT: [IVec2; 4] = [
+    IVec2::new(1, 0),
+    IVec2::new(0, 1),
+    IVec2::new(-1, 0),
+    IVec2::new(0, -1),
+];
+
+const DIAGONAL: [IVec2; 4] = [
+    IVec2::new(1, 1),
+    IVec2::new(-1, 1),
+    IVec2::new(-1, -1),
+    IVec2::new(1, -1),
+];
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum Neighbors {
+    Four,
+    /// Diagonal moves are only allowed when both adjacent straight cells are
+    /// passable, so paths never cut corners.
+    Eight,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Path {
+    /// Every cell visited, including start and goal
+    pub cells: Vec<IVec2>,
+    /// Sum of step costs, see STRAIGHT_COST and DIAGONAL_COST
+    pub cost: u32,
+}
+
+fn octile(a: IVec2, b: IVec2) -> u32 {
+    let d = (a - b).abs();
+    let (lo, hi) = (d.x.min(d.y) as u32, d.x.max(d.y) as u32);
+    DIAGONAL_COST * lo + STRAIGHT_COST * (hi - lo)
+}
+
+fn manhattan(a: IVec2, b: IVec2) -> u32 {
+    let d = (a - b).abs();
+    STRAIGHT_COST * (d.x + d.y) as u32
+}
+
+/// Grid within bounds where the cost callback gives the cost of entering a
+/// cell, or None if it is blocked. Costs below 1 are treated as 1 so the
+/// distance heuristic stays admissible. Step and path costs saturate, and
+/// cells only reachable at a cost of u32::MAX or more count as unreachable.
+pub struct PathGrid<F> {
+    pub bounds: IRange2,
+    pub neighbors: Neighbors,
+    cost: F,
+}
+
+impl<F: Fn(IVec2) -> Option<u32>> PathGrid<F> {
+    pub fn new(bounds: IRange2, neighbors: Neighbors, cost: F) -> Self {
+        Self {
+            bounds,
+            neighbors,
+            cost,
+        }
+    }
+
+    pub fn cost(&self, p: IVec2) -> Option<u32> {
+        if self.bounds.contains(p) {
+            (self.cost)(p).map(|c| c.max(1))
+        } else {
+            None
+        }
+    }
+
+    pub fn is_passable(&self, p: IVec2) -> bool {
+        self.cost(p).is_some()
+    }
+
+    fn index(&self, p: IVec2) -> usize {
+        let d = p - self.bounds.min;
+        (d.y * self.bounds.size().x + d.x) as usize
+    }
+
+    fn cell_count(&self) -> usize {
+        let size = self.bounds.size().max(IVec2::ZERO);
+        (size.x * size.y) as usize
+    }
+
+    fn heuristic(&self, a: IVec2, b: IVec2) -> u32 {
+        match self.neighbors {
+            Neighbors::Four => manhattan(a, b),
+            Neighbors::Eight => octile(a, b),
+        }
+    }
+
+    /// Cost of moving one step from p in direction dir
+    fn step_cost(&self, p: IVec2, dir: IVec2) -> Option<u32> {
+        let cost = self.cost(p + dir)?;
+        if dir.x != 0 && dir.y != 0 {
+            if !self.is_passable(p + IVec2::new(dir.x, 0)) || !self.is_passable(p + IVec2::new(0, dir.y)) {
+                return None;
+            }
+            Some(cost.saturating_mul(DIAGONAL_COST))
+        } else {
+            Some(cost.saturating_mul(STRAIGHT_COST))
+        }
+    }
+
+    fn directions(&self) -> impl Iterator<Item = IVec2> {
+        let diagonal: &[IVec2] = match self.neighbors {
+            Neighbors::Four => &[],
+            Neighbors::Eight => &DIAGONAL,
+        };
+        STRAIGHT.into_iter().chain(diagonal.iter().copied())
+    }
+
+    /// Shortest path using A*
+    pub fn astar(&self, start: IVec2, goal: IVec2) -> Option<Path> {
+        if !self.is_passable(start) || !self.is_passable(goal) {
+            return None;
+        }
+        let mut dist = vec![UNREACHED; self.cell_count()];
+        let mut parent = vec![start; self.cell_count()];
+        let mut open = BinaryHeap::new();
+        dist[self.index(start)] = 0;
+        open.push(Reverse((self.heuristic(start, goal), self.heuristic(start, goal), start.y, start.x)));
+        while let Some(Reverse((_, _, y, x))) = open.pop() {
+            let p = IVec2::new(x, y);
+            if p == goal {
+                return Some(self.build_path(&parent, start, goal, dist[self.index(goal)]));
+            }
+            let d = dist[self.index(p)];
+            for dir in self.directions() {
+                let Some(step) = self.step_cost(p, dir) else {
+                    continue;
+                };
+                let n = p + dir;
+                let ni = self.index(n);
+                let nd = d.saturating_add(step);
+                if nd < dist[ni] {
+                    dist[ni] = nd;
+                    parent[ni] = p;
+                    let h = self.heuristic(n, goal);
+                    open.push(Reverse((nd.saturating_add(h), h, n.y, n.x)));
+                }
+            }
+        }
+        None
+    }
+
+    fn build_path(&self, parent: &[IVec2], start: IVec2, goal: IVec2, cost: u32) -> Path {
+        let mut cells = vec![goal];
+        let mut p = goal;
+        while p != start {
+            p = parent[self.index(p)];
+            cells.push(p);
+        }
+        cells.reverse();
+        Path { cells, cost }
+    }
+
+    /// Jump point search, which is much faster than A* on open grids. Only
+    /// applies to uniform cost grids with eight neighbors, so every passable
+    /// cell is treated as cost 1. Returns the same cost as astar would on
+    /// such a grid, though it may choose a different path of equal cost.
+    /// https://harablog.wordpress.com/2011/09/07/jump-point-search/
+    pub fn jps(&self, start: IVec2, goal: IVec2) -> Option<Path> {
+        if !self.is_passable(start) || !self.is_passable(goal) {
+            return None;
+        }
+        let mut dist = vec![UNREACHED; self.cell_count()];
+        let mut parent = vec![start; self.cell_count()];
+        let mut open = BinaryHeap::new();
+        dist[self.index(start)] = 0;
+        open.push(Reverse((octile(start, goal), octile(start, goal), start.y, start.x)));
+        while let Some(Reverse((_, _, y, x))) = open.pop() {
+            let p = IVec2::new(x, y);
+            if p == goal {
+                return Some(self.expand_jumps(&parent, start, goal, dist[self.index(goal)]));
+            }
+            let d = dist[self.index(p)];
+            let from = if p == start { None } else { Some(parent[self.index(p)]) };
+            for dir in self.jps_directions(p, from) {
+                let Some(jump) = self.jump(p, dir, goal) else {
+                    continue;
+                };
+                let ji = self.index(jump);
+                let nd = d.saturating_add(octile(p, jump));
+                if nd < dist[ji] {
+                    dist[ji] = nd;
+                    parent[ji] = p;
+                    let h = octile(jump, goal);
+                    open.push(Reverse((nd.saturating_add(h), h, jump.y, jump.x)));
+                }
+            }
+        }
+        None
+    }
+
+    /// Natural and forced neighbors for the no corner cutting variant
+    fn jps_directions(&self, p: IVec2, from: Option<IVec2>) -> Vec<IVec2> {
+        let Some(from) = from else {
+            return STRAIGHT.into_iter().chain(DIAGONAL).collect();
+        };
+        let dir = (p - from).signum();
+        let open = |d: IVec2| self.is_passable(p + d);
+        let mut dirs = Vec::with_capacity(5);
+        if dir.x != 0 && dir.y != 0 {
+            let x = IVec2::new(dir.x, 0);
+            let y = IVec2::new(0, dir.y);
+            dirs.push(y);
+            dirs.push(x);
+            dirs.push(dir);
+        } else {
+            // Sides perpendicular to the direction of travel
+            let side = IVec2::new(dir.y, dir.x);
+            for s in [side, -side] {
+                if open(s) {
+                    dirs.push(s);
+                    dirs.push(dir + s);
+                }
+            }
+            dirs.push(dir);
+        }
+        dirs
+    }
+
+    fn jump(&self, from: IVec2, dir: IVec2, goal: IVec2) -> Option<IVec2> {
+        let mut p = from;
+        loop {
+            self.step_cost(p, dir)?;
+            p += dir;
+            if p == goal {
+                return Some(p);
+            }
+            if dir.x != 0 && dir.y != 0 {
+                if self.jump(p, IVec2::new(dir.x, 0), goal).is_some()
+                    || self.jump(p, IVec2::new(0, dir.y), goal).is_some()
+                {
+                    return Some(p);
+                }
+            } else {
+                let side = IVec2::new(dir.y, dir.x);
+                for s in [side, -side] {
+                    if self.is_passable(p + s) && !self.is_passable(p - dir + s) {
+                        return Some(p);
+                    }
+                }
+            }
+        }
+    }
+
+    fn expand_jumps(&self, parent: &[IVec2], start: IVec2, goal: IVec2, cost: u32) -> Path {
+        let mut jumps = vec![goal];
+        let mut p = goal;
+        while p != start {
+            p = parent[self.index(p)];
+            jumps.push(p);
+        }
+        jumps.reverse();
+        let mut cells = vec![start];
+        for pair in jumps.windows(2) {
+            let mut p = pair[0];
+            while p != pair[1] {
+                p += (pair[1] - p).signum();
+                cells.push(p);
+            }
+        }
+        Path { cells, cost }
+    }
+
+    /// Cost from every cell to the nearest goal
+    pub fn distance_map(&self, goals: &[IVec2]) -> DistanceMap {
+        let mut dist = vec![UNREACHED; self.cell_count()];
+        let mut open = BinaryHeap::new();
+        for goal in goals.iter().copied().filter(|g| self.is_passable(*g)) {
+            dist[self.index(goal)] = 0;
+            open.push(Reverse((0, goal.y, goal.x)));
+        }
+        while let Some(Reverse((d, y, x))) = open.pop() {
+            let p = IVec2::new(x, y);
+            if d > dist[self.index(p)] {
+                continue;
+            }
+            // Searching outward from the goals, so a step from n to p costs
+            // the cost of entering p.
+            for dir in self.directions() {
+                let n = p + dir;
+                if !self.is_passable(n) {
+                    continue;
+                }
+                let Some(step) = self.step_cost(n, -dir) else {
+                    continue;
+                };
+                let ni = self.index(n);
+                let nd = d.saturating_add(step);
+                if nd < dist[ni] {
+                    dist[ni] = nd;
+                    open.push(Reverse((nd, n.y, n.x)));
+                }
+            }
+        }
+        DistanceMap {
+            bounds: self.bounds,
+            neighbors: self.neighbors,
+            dist,
+        }
+    }
+
+    /// Direction towards the nearest goal for every cell, for steering many
+    /// agents to the same goals.
+    pub fn flow_field(&self, goals: &[IVec2]) -> FlowField {
+        let map = self.distance_map(goals);
+        let dirs = self
+            .bounds
+            .iter()
+            .map(|p| match map.get(p) {
+                Some(0) | None => IVec2::ZERO,
+                Some(d) => self
+                    .directions()
+                    .filter_map(|dir| {
+                        let step = self.step_cost(p, dir)?;
+                        let nd = map.get(p + dir)?;
+                        (nd.saturating_add(step) == d).then_some(dir)
+                    })
+                    .next()
+                    .unwrap_or(IVec2::ZERO),
+            })
+            .collect();
+        FlowField {
+            bounds: self.bounds,
+            dirs,
+        }
+    }
+
+    /// True if every cell touched by the straight line between cell centers
+    /// is passable.
+    pub fn has_line_of_sight(&self, a: IVec2, b: IVec2) -> bool {
+        supercover(a, b).all(|p| self.is_passable(p))
+    }
+
+    /// Removes waypoints that can be skipped with a straight unobstructed
+    /// line, keeping the start and goal.
+    pub fn smooth(&self, cells: &[IVec2]) -> Vec<IVec2> {
+        let mut result = Vec::new();
+        let mut anchor = 0;
+        if let Some(first) = cells.first() {
+            result.push(*first);
+        }
+        while anchor + 1 < cells.len() {
+            let mut next = anchor + 1;
+            for i in (anchor + 2..cells.len()).rev() {
+                if self.has_line_of_sight(cells[anchor], cells[i]) {
+                    next = i;
+                    break;
+                }
+            }
+            result.push(cells[next]);
+            anchor = next;
+        }
+        result
+    }
+}
+
+/// Cost to the nearest goal from each cell of a grid
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct DistanceMap {
+    pub bounds: IRange2,
+    pub neighbors: Neighbors,
+    dist: Vec<u32>,
+}
+
+impl DistanceMap {
+    /// None for cells that are blocked, out of bounds or cannot reach a goal
+    pub fn get(&self, p: IVec2) -> Option<u32> {
+        if !self.bounds.contains(p) {
+            return None;
+        }
+        let d = p - self.bounds.min;
+        let dist = self.dist[(d.y * self.bounds.size().x + d.x) as usize];
+        (dist != UNREACHED).then_some(dist)
+    }
+
+    /// Neighbor with the lowest cost, or None at a goal or unreachable cell.
+    /// Only considers the distances, so diagonal steps may cut corners.
+    pub fn descend(&self, p: IVec2) -> Option<IVec2> {
+        let current = self.get(p)?;
+        let diagonal: &[IVec2] = match self.neighbors {
+            Neighbors::Four => &[],
+            Neighbors::Eight => &DIAGONAL,
+        };
+        STRAIGHT
+            .iter()
+            .chain(diagonal)
+            .filter_map(|dir| self.get(p + *dir).map(|d| (d, p + *dir)))
+            .filter(|(d, _)| *d < current)
+            .min_by_key(|(d, n)| (*d, n.y, n.x))
+            .map(|(_, n)| n)
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct FlowField {
+    pub bounds: IRange2,
+    dirs: Vec<IVec2>,
+}
+
+impl FlowField {
+    /// Step towards the nearest goal, which is zero at goals and at cells
+    /// that cannot reach one.
+    pub fn direction(&self, p: IVec2) -> IVec2 {
+        if !self.bounds.contains(p) {
+            return IVec2::ZERO;
+        }
+        let d = p - self.bounds.min;
+        self.dirs[(d.y * self.bounds.size().x + d.x) as usize]
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// '#' is a wall, digits are costs and anything else costs 1. Row 0 is
+    /// the bottom line.
+    fn parse(map: &[&str]) -> (IRange2, Vec<Option<u32>>) {
+        let h = map.len() as i32;
+        let w = map[0].len() as i32;
+        let mut cells = vec![None; (w * h) as usize];
+        for (row, line) in map.iter().enumerate() {
+            let y = h - 1 - row as i32;
+            for (x, c) in line.chars().enumerate() {
+                cells[(y * w + x as i32) as usize] = match c {
+                    '#' => None,
+                    '0'..='9' => Some(c.to_digit(10).unwrap()),
+                    _ => Some(1),
+                };
+            }
+        }
+        (IRange2::new(IVec2::ZERO, IVec2::new(w, h)), cells)
+    }
+
+    fn grid(
+        map: &[&str],
+        neighbors: Neighbors,
+    ) -> PathGrid<impl Fn(IVec2) -> Option<u32>> {
+        let (bounds, cells) = parse(map);
+        let w = bounds.size().x;
+        PathGrid::new(bounds, neighbors, move |p: IVec2| cells[(p.y * w + p.x) as usize])
+    }
+
+    fn assert_connected(grid: &PathGrid<impl Fn(IVec2) -> Option<u32>>, path: &Path) {
+        for pair in path.cells.windows(2) {
+            let d = pair[1] - pair[0];
+            assert!(d.abs().max_element() == 1, "{:?}", pair);
+            assert!(grid.step_cost(pair[0], d).is_some(), "{:?}", pair);
+        }
+    }
+
+    const MAZE: [&str; 7] = [
+        "..........",
+        ".########.",
+        ".#......#.",
+        ".#.####.#.",
+        ".###..#...",
+        "...#..####",
+        "..#.......",
+    ];
+
+    #[test]
+    fn astar_four() {
+        let g = grid(&MAZE, Neighbors::Four);
+        let path = g.astar(IVec2::new(0, 0), IVec2::new(2, 3)).unwrap();
+        assert_connected(&g, &path);
+        assert_eq!(path.cells.first(), Some(&IVec2::new(0, 0)));
+        assert_eq!(path.cells.last(), Some(&IVec2::new(2, 3)));
+        assert_eq!(path.cells.len(), 30);
+        assert_eq!(path.cost, 29 * STRAIGHT_COST);
+        assert_eq!(g.astar(IVec2::new(0, 0), IVec2::new(5, 2)), None);
+        assert_eq!(g.astar(IVec2::new(0, 0), IVec2::new(1, 4)), None);
+    }
+
+    #[test]
+    fn astar_costs() {
+        let g = grid(&[".9.", "...", "..."], Neighbors::Four);
+        let path = g.astar(IVec2::new(0, 2), IVec2::new(2, 2)).unwrap();
+        assert_eq!(path.cost, 4 * STRAIGHT_COST);
+        assert!(!path.cells.contains(&IVec2::new(1, 2)));
+        let g = grid(&[".2.", "...", "..."], Neighbors::Four);
+        let path = g.astar(IVec2::new(0, 2), IVec2::new(2, 2)).unwrap();
+        assert_eq!(path.cost, 3 * STRAIGHT_COST);
+    }
+
+    #[test]
+    fn astar_eight_no_corner_cutting() {
+        let g = grid(&["..", "#."], Neighbors::Eight);
+        let path = g.astar(IVec2::new(1, 0), IVec2::new(0, 1)).unwrap();
+        assert_eq!(path.cells.len(), 3);
+        assert_eq!(path.cost, 2 * STRAIGHT_COST);
+        let g = grid(&["...", "...", "..."], Neighbors::Eight);
+        let path = g.astar(IVec2::new(0, 0), IVec2::new(2, 2)).unwrap();
+        assert_eq!(path.cost, 2 * DIAGONAL_COST);
+    }
+
+    #[test]
+    fn huge_costs_saturate() {
+        let bounds = IRange2::new(IVec2::ZERO, IVec2::new(5, 1));
+        let (start, goal) = (IVec2::ZERO, IVec2::new(4, 0));
+        let row = |wall: u32| PathGrid::new(bounds, Neighbors::Eight, move |p: IVec2| {
+            Some(if p.x == 2 { wall } else { 1 })
+        });
+        let g = row(u32::MAX / 200);
+        let path = g.astar(start, goal).unwrap();
+        assert_eq!(path.cost, 3 * STRAIGHT_COST + u32::MAX / 200 * STRAIGHT_COST);
+        assert_eq!(g.distance_map(&[goal]).get(start), Some(path.cost));
+
+        let g = row(u32::MAX / 50);
+        assert_eq!(g.astar(start, goal), None);
+        let map = g.distance_map(&[goal]);
+        assert_eq!(map.get(start), None);
+        assert_eq!(map.get(IVec2::new(3, 0)), Some(STRAIGHT_COST));
+        assert_eq!(g.flow_field(&[goal]).direction(start), IVec2::ZERO);
+    }
+
+    #[test]
+    fn deterministic_ties() {
+        let g = grid(&["....", "....", "....", "...."], Neighbors::Four);
+        let a = g.astar(IVec2::new(0, 0), IVec2::new(3, 3)).unwrap();
+        for _ in 0..10 {
+            assert_eq!(g.astar(IVec2::new(0, 0), IVec2::new(3, 3)).unwrap(), a);
+        }
+    }
+
+    #[test]
+    fn jps_matches_astar_cost() {
+        let g = grid(&MAZE, Neighbors::Eight);
+        for (start, goal) in [
+            (IVec2::new(0, 0), IVec2::new(2, 3)),
+            (IVec2::new(9, 6), IVec2::new(2, 1)),
+            (IVec2::new(2, 4), IVec2::new(0, 0)),
+            (IVec2::new(0, 6), IVec2::new(9, 2)),
+            (IVec2::new(9, 0), IVec2::new(4, 2)),
+        ] {
+            let a = g.astar(start, goal).unwrap();
+            let j = g.jps(start, goal).unwrap();
+            assert_eq!(a.cost, j.cost, "{start} {goal}");
+            assert_connected(&g, &j);
+            assert_eq!(j.cells.first(), Some(&start));
+            assert_eq!(j.cells.last(), Some(&goal));
+        }
+        assert_eq!(g.jps(IVec2::new(0, 0), IVec2::new(5, 2)), None);
+    }
+
+    #[test]
+    fn jps_matches_astar_on_random_grids() {
+        let mut rng = crate::pcg::PcgRng::new(11, 0);
+        let bounds = IRange2::new(IVec2::ZERO, IVec2::splat(24));
+        for _ in 0..20 {
+            let walls: Vec<bool> = (0..24 * 24).map(|_| rng.next_u32_to(100) < 30).collect();
+            let g = PathGrid::new(bounds, Neighbors::Eight, |p: IVec2| {
+                (!walls[(p.y * 24 + p.x) as usize]).then_some(1)
+            });
+            for _ in 0..10 {
+                let start = IVec2::new(rng.next_i32_to(24), rng.next_i32_to(24));
+                let goal = IVec2::new(rng.next_i32_to(24), rng.next_i32_to(24));
+                let a = g.astar(start, goal);
+                let j = g.jps(start, goal);
+                assert_eq!(a.as_ref().map(|p| p.cost), j.as_ref().map(|p| p.cost));
+                if let Some(j) = j {
+                    assert_connected(&g, &j);
+                }
+            }
+        }
+    }
+
+    #[test]
+    fn distance_map_and_flow() {
+        let g = grid(&MAZE, Neighbors::Four);
+        let goal = IVec2::new(2, 3);
+        let map = g.distance_map(&[goal]);
+        assert_eq!(map.get(goal), Some(0));
+        assert_eq!(map.get(IVec2::new(5, 2)), None);
+        let start = IVec2::new(0, 0);
+        assert_eq!(map.get(start), Some(g.astar(start, goal).unwrap().cost));
+
+        let mut p = start;
+        let mut steps = 0;
+        while let Some(next) = map.descend(p) {
+            p = next;
+            steps += 1;
+        }
+        assert_eq!(p, goal);
+        assert_eq!(steps * STRAIGHT_COST, map.get(start).unwrap());
+
+        let flow = g.flow_field(&[goal]);
+        let mut p = start;
+        while flow.direction(p) != IVec2::ZERO {
+            p += flow.direction(p);
+        }
+        assert_eq!(p, goal);
+        assert_eq!(flow.direction(IVec2::new(5, 2)), IVec2::ZERO);
+    }
+
+    #[test]
+    fn smoothing() {
+        let g = grid(&["......", "......", "..#...", "......"], Neighbors::Four);
+        let path = g.astar(IVec2::new(0, 0), IVec2::new(5, 3)).unwrap();
+        let smooth = g.smooth(&path.cells);
+        assert_eq!(smooth.first(), Some(&IVec2::new(0, 0)));
+        assert_eq!(smooth.last(), Some(&IVec2::new(5, 3)));
+        assert!(smooth.len() < path.cells.len());
+        for pair in smooth.windows(2) {
+            assert!(g.has_line_of_sight(pair[0], pair[1]));
+        }
+        assert!(!g.has_line_of_sight(IVec2::new(1, 1), IVec2::new(3, 1)));
+    }
+}
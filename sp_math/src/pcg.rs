@@ -2,6 +2,8 @@ use std::ops::Range;
 
 use glam::{IVec2, Quat, UVec2, UVec3, UVec4, Vec2, Vec3};
 
+use crate::fixed::{Fx32, Fx32Vec2, Fx64};
+
 // ND variants below are hashes that skip some steps of the original PCG.
 // Sources:
 // https://www.pcg-random.org/
@@ -120,11 +122,38 @@ pub fn pcg4d(mut v: UVec4) -> UVec4 {
     v
 }
 
+/// PCG32 generator. Serializes its full state, so a restored generator
+/// continues the same sequence.
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+#[cfg_attr(feature = "serde", serde(try_from = "PcgRngState"))]
 pub struct PcgRng {
     inc: u64,
     state: u64,
 }
 
+#[cfg(feature = "serde")]
+#[derive(serde::Deserialize)]
+struct PcgRngState {
+    inc: u64,
+    state: u64,
+}
+
+#[cfg(feature = "serde")]
+impl TryFrom<PcgRngState> for PcgRng {
+    type Error = &'static str;
+
+    fn try_from(value: PcgRngState) -> Result<Self, Self::Error> {
+        if value.inc & 1 == 0 {
+            Err("PcgRng increment must be odd")
+        } else {
+            Ok(Self { inc: value.inc, state: value.state })
+        }
+    }
+}
+
+const MULTIPLIER: u64 = 6364136223846793005u64;
+
 fn get_u32(state: u64) -> u32 {
     let xor_shifted = (((state >> 18) ^ state) >> 27) as u32;
     let rot = (state >> 59) as i32;
@@ -132,7 +161,16 @@ fn get_u32(state: u64) -> u32 {
 }
 
 fn next_state(state: u64, inc: u64) -> u64 {
-    state.wrapping_mul(6364136223846793005u64).wrapping_add(inc)
+    state.wrapping_mul(MULTIPLIER).wrapping_add(inc)
+}
+
+/// Mixes a value into a well distributed one:
+/// https://prng.di.unimi.it/splitmix64.c
+fn splitmix64(x: u64) -> u64 {
+    let mut z = x.wrapping_add(0x9e3779b97f4a7c15);
+    z = (z ^ (z >> 30)).wrapping_mul(0xbf58476d1ce4e5b9);
+    z = (z ^ (z >> 27)).wrapping_mul(0x94d049bb133111eb);
+    z ^ (z >> 31)
 }
 
 fn get_increment(seq: u64) -> u64 {
@@ -151,6 +189,45 @@ impl PcgRng {
         Self { inc, state }
     }
 
+    /// Skips delta outputs in O(log delta) steps. Pass a wrapped negative
+    /// delta, such as 1u64.wrapping_neg(), to step backwards.
+    /// https://www.pcg-random.org/ (advance_lcg_64 in the C implementation)
+    pub fn advance(&mut self, delta: u64) {
+        let mut delta = delta;
+        let mut cur_mult = MULTIPLIER;
+        let mut cur_plus = self.inc;
+        let mut acc_mult = 1u64;
+        let mut acc_plus = 0u64;
+        while delta > 0 {
+            if delta & 1 != 0 {
+                acc_mult = acc_mult.wrapping_mul(cur_mult);
+                acc_plus = acc_plus.wrapping_mul(cur_mult).wrapping_add(cur_plus);
+            }
+            cur_plus = cur_mult.wrapping_add(1).wrapping_mul(cur_plus);
+            cur_mult = cur_mult.wrapping_mul(cur_mult);
+            delta /= 2;
+        }
+        self.state = acc_mult.wrapping_mul(self.state).wrapping_add(acc_plus);
+    }
+
+    /// Child generator on its own stream, seeded from this generator's output.
+    /// Advances this generator by four steps.
+    pub fn split(&mut self) -> Self {
+        let seed = self.next_u64();
+        let seq = self.next_u64();
+        Self::new(splitmix64(seed), splitmix64(seq))
+    }
+
+    /// Child generator for a stream id, such as an entity or system index.
+    /// Does not advance this generator, so the same id always gives the same
+    /// child, and different ids give uncorrelated children.
+    pub fn fork(&self, stream: u64) -> Self {
+        let key = splitmix64(self.state ^ splitmix64(self.inc));
+        let seed = splitmix64(key ^ stream);
+        let seq = splitmix64(seed ^ splitmix64(stream.wrapping_add(key)));
+        Self::new(seed, seq)
+    }
+
     pub fn next_u32(&mut self) -> u32 {
         let result = get_u32(self.state);
         self.state = next_state(self.state, self.inc);
@@ -222,6 +299,33 @@ impl PcgRng {
         range.start * (1.0 - t) + range.end * t
     }
 
+    /// Returns value in closed range [0, 1] using integer math only
+    pub fn next_fx32(&mut self) -> Fx32 {
+        let x = self.next_u32() as u64;
+        Fx32::from_bits(((x << 16) / 4294967295) as i32)
+    }
+
+    pub fn next_fx32_in(&mut self, range: Range<Fx32>) -> Fx32 {
+        let t = self.next_fx32();
+        range.start.lerp(range.end, t)
+    }
+
+    /// Returns value in closed range [0, 1] using integer math only
+    pub fn next_fx64(&mut self) -> Fx64 {
+        let x = self.next_u32() as u128;
+        Fx64::from_bits(((x << 32) / 4294967295) as i64)
+    }
+
+    pub fn next_fx64_in(&mut self, range: Range<Fx64>) -> Fx64 {
+        let t = self.next_fx64();
+        range.start.lerp(range.end, t)
+    }
+
+    /// Returns value in closed range [0, 1]
+    pub fn next_fx32_vec2(&mut self) -> Fx32Vec2 {
+        Fx32Vec2::new(self.next_fx32(), self.next_fx32())
+    }
+
     /// Returns value in closed range [0, 1]
     pub fn next_vec2(&mut self) -> Vec2 {
         Vec2::new(self.next_f32(), self.next_f32())
@@ -318,10 +422,55 @@ impl Default for PcgRng {
     }
 }
 
+#[cfg(feature = "rand_core")]
+impl rand_core::RngCore for PcgRng {
+    fn next_u32(&mut self) -> u32 {
+        PcgRng::next_u32(self)
+    }
+
+    fn next_u64(&mut self) -> u64 {
+        PcgRng::next_u64(self)
+    }
+
+    fn fill_bytes(&mut self, dest: &mut [u8]) {
+        rand_core::impls::fill_bytes_via_next(self, dest)
+    }
+
+    fn try_fill_bytes(&mut self, dest: &mut [u8]) -> Result<(), rand_core::Error> {
+        self.fill_bytes(dest);
+        Ok(())
+    }
+}
+
+/// Seed is the little endian seed followed by the little endian sequence
+#[cfg(feature = "rand_core")]
+impl rand_core::SeedableRng for PcgRng {
+    type Seed = [u8; 16];
+
+    fn from_seed(seed: Self::Seed) -> Self {
+        let (seed, seq) = seed.split_at(8);
+        Self::new(
+            u64::from_le_bytes(seed.try_into().unwrap()),
+            u64::from_le_bytes(seq.try_into().unwrap()),
+        )
+    }
+}
+
 #[cfg(test)]
 mod test {
     use super::*;
 
+    #[test]
+    fn test_fx32_in_unit_range() {
+        let mut rng = PcgRng::default();
+        for _ in 0..1000 {
+            let x = rng.next_fx32();
+            assert!(x >= Fx32::ZERO && x <= Fx32::ONE);
+            let x = rng.next_fx64_in(Fx64::from_int(-2)..Fx64::from_int(2));
+            assert!(x >= Fx64::from_int(-2) && x <= Fx64::from_int(2));
+        }
+    }
+
     #[test]
     fn test_ivec2_in_sphere() {
         let mut rng = PcgRng::default();
@@ -336,4 +485,73 @@ mod test {
             }
         }
     }
-}
\ No newline at end of file
+
+    #[test]
+    fn test_advance() {
+        let mut a = PcgRng::new(42, 54);
+        let mut b = a.clone();
+        for _ in 0..1000 {
+            a.next_u32();
+        }
+        b.advance(1000);
+        assert_eq!(a, b);
+        b.advance(0);
+        assert_eq!(a.next_u32(), b.next_u32());
+        b.advance(1u64.wrapping_neg());
+        let mut c = b.clone();
+        assert_eq!(b.next_u32(), c.next_u32());
+        c.advance(1u64.wrapping_neg());
+        let mut d = PcgRng::new(42, 54);
+        d.advance(1000);
+        assert_eq!(c, d);
+    }
+
+    #[test]
+    fn test_split_and_fork() {
+        let mut parent = PcgRng::new(1, 0);
+        let mut a = parent.split();
+        let mut b = parent.split();
+        assert_ne!(a, b);
+        let a_values: Vec<u32> = (0..64).map(|_| a.next_u32()).collect();
+        let b_values: Vec<u32> = (0..64).map(|_| b.next_u32()).collect();
+        assert_ne!(a_values, b_values);
+
+        let root = PcgRng::new(7, 3);
+        assert_eq!(root.fork(5), root.fork(5));
+        assert_ne!(root.fork(5), root.fork(6));
+        assert_ne!(root.fork(5), PcgRng::new(8, 3).fork(5));
+        // Neighbouring ids should not produce related sequences
+        let mut x = root.fork(0);
+        let mut y = root.fork(1);
+        let same_bits: u32 = (0..1000)
+            .map(|_| (x.next_u32() ^ y.next_u32()).count_zeros())
+            .sum();
+        assert!((same_bits as f32 / 32000.0 - 0.5).abs() < 0.02);
+    }
+
+    #[cfg(feature = "serde")]
+    #[test]
+    fn test_serde_roundtrip() {
+        let mut rng = PcgRng::new(9, 9);
+        rng.next_u32();
+        let json = serde_json::to_string(&rng).unwrap();
+        let mut restored: PcgRng = serde_json::from_str(&json).unwrap();
+        assert_eq!(rng.next_u64(), restored.next_u64());
+        assert!(serde_json::from_str::<PcgRng>(r#"{"inc":2,"state":0}"#).is_err());
+    }
+
+    #[cfg(feature = "rand_core")]
+    #[test]
+    fn test_rand_core() {
+        use rand_core::{RngCore, SeedableRng};
+        let mut seed = [0u8; 16];
+        seed[..8].copy_from_slice(&42u64.to_le_bytes());
+        seed[8..].copy_from_slice(&54u64.to_le_bytes());
+        let mut a = PcgRng::from_seed(seed);
+        let mut b = PcgRng::new(42, 54);
+        assert_eq!(RngCore::next_u32(&mut a), b.next_u32());
+        let mut bytes = [0u8; 8];
+        a.fill_bytes(&mut bytes);
+        assert_eq!(u64::from_le_bytes(bytes), b.next_u64());
+    }
+}
@@ -0,0 +1,870 @@
+// Small 2D rigid body simulation stepped with a fixed dt. Bodies are
+// processed in id order and candidate pairs are sorted, so identical inputs
+// replay to identical states on the same build, which lockstep needs.
+
+use std::collections::HashMap;
+use std::f32::consts::PI;
+
+use glam::{IVec2, Vec2};
+
+use crate::polygon::{centroid, convex_hull};
+use crate::range::Range2;
+use crate::vec::rotate_vec2;
+
+/// Penetration left alone by position correction, avoids jitter in resting
+/// contacts
+const SLOP: f32 = 0.005;
+/// Fraction of the remaining penetration removed each step
+const CORRECTION: f32 = 0.4;
+/// Closing speed below which contacts do not bounce
+const RESTITUTION_THRESHOLD: f32 = 1.0;
+
+#[derive(Debug, Clone, PartialEq)]
+pub enum Shape {
+    Circle(f32),
+    /// Convex counter-clockwise vertices around the center of mass
+    Polygon(Vec<Vec2>),
+}
+
+impl Shape {
+    pub fn circle(radius: f32) -> Self {
+        Self::Circle(radius)
+    }
+
+    pub fn rect(half_extents: Vec2) -> Self {
+        let h = half_extents;
+        Self::Polygon(vec![-h, Vec2::new(h.x, -h.y), h, Vec2::new(-h.x, h.y)])
+    }
+
+    /// Convex hull of the points moved so its centroid is at the origin,
+    /// None if the points do not span an area
+    pub fn polygon(points: &[Vec2]) -> Option<Self> {
+        let hull = convex_hull(points);
+        if hull.len() < 3 {
+            return None;
+        }
+        let c = centroid(&hull)?;
+        Some(Self::Polygon(hull.iter().map(|p| *p - c).collect()))
+    }
+
+    /// Area and moment of inertia around the origin at unit density
+    pub fn mass_properties(&self) -> (f32, f32) {
+        match self {
+            Self::Circle(r) => {
+                let area = PI * r * r;
+                (area, area * r * r * 0.5)
+            }
+            Self::Polygon(verts) => {
+                let (mut area, mut inertia) = (0.0, 0.0);
+                for (i, a) in verts.iter().enumerate() {
+                    let b = verts[(i + 1) % verts.len()];
+                    let cross = a.perp_dot(b);
+                    area += cross * 0.5;
+                    inertia += cross / 12.0 * (a.dot(*a) + a.dot(b) + b.dot(b));
+                }
+                (area, inertia)
+            }
+        }
+    }
+
+    pub fn bounds(&self, position: Vec2, rot: Vec2) -> Range2 {
+        match self {
+            Self::Circle(r) => Range2::centered(position, Vec2::splat(r * 2.0)),
+            Self::Polygon(verts) => {
+                Range2::from_vertices(verts.iter().map(|v| position + rotate_vec2(rot, *v)))
+                    .unwrap_or(Range2::new(position, position))
+            }
+        }
+    }
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct Body {
+    pub shape: Shape,
+    pub position: Vec2,
+    pub angle: f32,
+    pub velocity: Vec2,
+    pub angular_velocity: f32,
+    /// Zero for static bodies
+    pub inv_mass: f32,
+    pub inv_inertia: f32,
+    pub restitution: f32,
+    pub friction: f32,
+    /// Layers the body is on
+    pub layer: u32,
+    /// Layers the body collides with, both bodies of a pair have to agree
+    pub mask: u32,
+    /// Sensors report contacts without a collision response
+    pub sensor: bool,
+}
+
+impl Body {
+    pub fn dynamic(shape: Shape, density: f32) -> Self {
+        let (area, inertia) = shape.mass_properties();
+        let inv = |x: f32| if x > 0.0 { 1.0 / x } else { 0.0 };
+        Self {
+            inv_mass: inv(area * density),
+            inv_inertia: inv(inertia * density),
+            ..Self::fixed(shape)
+        }
+    }
+
+    /// Body that never moves from collisions or gravity
+    pub fn fixed(shape: Shape) -> Self {
+        Self {
+            shape,
+            position: Vec2::ZERO,
+            angle: 0.0,
+            velocity: Vec2::ZERO,
+            angular_velocity: 0.0,
+            inv_mass: 0.0,
+            inv_inertia: 0.0,
+            restitution: 0.0,
+            friction: 0.5,
+            layer: 1,
+            mask: u32::MAX,
+            sensor: false,
+        }
+    }
+
+    pub fn with_position(mut self, position: Vec2) -> Self {
+        self.position = position;
+        self
+    }
+
+    pub fn with_angle(mut self, angle: f32) -> Self {
+        self.angle = angle;
+        self
+    }
+
+    pub fn with_velocity(mut self, velocity: Vec2) -> Self {
+        self.velocity = velocity;
+        self
+    }
+
+    pub fn with_angular_velocity(mut self, angular_velocity: f32) -> Self {
+        self.angular_velocity = angular_velocity;
+        self
+    }
+
+    pub fn with_restitution(mut self, restitution: f32) -> Self {
+        self.restitution = restitution;
+        self
+    }
+
+    pub fn with_friction(mut self, friction: f32) -> Self {
+        self.friction = friction;
+        self
+    }
+
+    pub fn with_layers(mut self, layer: u32, mask: u32) -> Self {
+        self.layer = layer;
+        self.mask = mask;
+        self
+    }
+
+    pub fn as_sensor(mut self) -> Self {
+        self.sensor = true;
+        self
+    }
+
+    pub fn is_static(&self) -> bool {
+        self.inv_mass == 0.0 && self.inv_inertia == 0.0
+    }
+
+    /// Rotation as a unit vector, as used by sprites
+    pub fn rot(&self) -> Vec2 {
+        Vec2::from_angle(self.angle)
+    }
+
+    pub fn mass(&self) -> f32 {
+        if self.inv_mass > 0.0 {
+            1.0 / self.inv_mass
+        } else {
+            0.0
+        }
+    }
+
+    pub fn bounds(&self) -> Range2 {
+        self.shape.bounds(self.position, self.rot())
+    }
+
+    pub fn velocity_at(&self, point: Vec2) -> Vec2 {
+        self.velocity + (point - self.position).perp() * self.angular_velocity
+    }
+
+    /// Applies an impulse at a point in world space
+    pub fn apply_impulse(&mut self, impulse: Vec2, point: Vec2) {
+        self.velocity += impulse * self.inv_mass;
+        self.angular_velocity += (point - self.position).perp_dot(impulse) * self.inv_inertia;
+    }
+
+    pub fn can_collide(&self, other: &Body) -> bool {
+        (self.layer & other.mask) != 0 && (other.layer & self.mask) != 0
+    }
+
+    fn world_vertices(&self, verts: &[Vec2]) -> Vec<Vec2> {
+        let rot = self.rot();
+        verts
+            .iter()
+            .map(|v| self.position + rotate_vec2(rot, *v))
+            .collect()
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub struct BodyId(pub u32);
+
+/// Touching pair found during the last step
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct Contact {
+    pub a: BodyId,
+    pub b: BodyId,
+    /// Unit normal pointing from a to b
+    pub normal: Vec2,
+    pub depth: f32,
+    /// True if either body is a sensor
+    pub sensor: bool,
+}
+
+struct ContactPoint {
+    point: Vec2,
+    depth: f32,
+    /// Identifies the features that touch, to match points between steps
+    id: u32,
+}
+
+struct Manifold {
+    a: usize,
+    b: usize,
+    normal: Vec2,
+    points: Vec<ContactPoint>,
+}
+
+/// Normal and tangent impulses of a contact point in the last step
+type ImpulseCache = HashMap<(usize, usize, u32), (f32, f32)>;
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct World {
+    pub gravity: Vec2,
+    /// Velocity solver passes per step
+    pub iterations: u32,
+    /// Broad phase grid size, around the size of a typical body
+    pub cell_size: f32,
+    bodies: Vec<Option<Body>>,
+    contacts: Vec<Contact>,
+    impulses: ImpulseCache,
+}
+
+impl World {
+    pub fn new(gravity: Vec2) -> Self {
+        Self {
+            gravity,
+            iterations: 8,
+            cell_size: 2.0,
+            bodies: Vec::new(),
+            contacts: Vec::new(),
+            impulses: HashMap::new(),
+        }
+    }
+
+    /// Reuses the lowest free id
+    pub fn insert(&mut self, body: Body) -> BodyId {
+        match self.bodies.iter().position(|b| b.is_none()) {
+            Some(i) => {
+                self.bodies[i] = Some(body);
+                BodyId(i as u32)
+            }
+            None => {
+                self.bodies.push(Some(body));
+                BodyId(self.bodies.len() as u32 - 1)
+            }
+        }
+    }
+
+    pub fn remove(&mut self, id: BodyId) -> Option<Body> {
+        self.bodies.get_mut(id.0 as usize)?.take()
+    }
+
+    pub fn get(&self, id: BodyId) -> Option<&Body> {
+        self.bodies.get(id.0 as usize)?.as_ref()
+    }
+
+    pub fn get_mut(&mut self, id: BodyId) -> Option<&mut Body> {
+        self.bodies.get_mut(id.0 as usize)?.as_mut()
+    }
+
+    pub fn iter(&self) -> impl Iterator<Item = (BodyId, &Body)> {
+        self.bodies
+            .iter()
+            .enumerate()
+            .filter_map(|(i, b)| b.as_ref().map(|b| (BodyId(i as u32), b)))
+    }
+
+    /// Contacts found by the last step, sorted by body ids
+    pub fn contacts(&self) -> &[Contact] {
+        &self.contacts
+    }
+
+    pub fn step(&mut self, dt: f32) {
+        for body in self.bodies.iter_mut().flatten() {
+            if body.inv_mass > 0.0 {
+                body.velocity += self.gravity * dt;
+            }
+        }
+
+        let mut manifolds = Vec::new();
+        self.contacts.clear();
+        for (i, j) in self.candidate_pairs() {
+            let (a, b) = (self.body(i), self.body(j));
+            let Some((normal, points)) = collide(a, b) else {
+                continue;
+            };
+            let sensor = a.sensor || b.sensor;
+            self.contacts.push(Contact {
+                a: BodyId(i as u32),
+                b: BodyId(j as u32),
+                normal,
+                depth: points.iter().map(|p| p.depth).fold(0.0, f32::max),
+                sensor,
+            });
+            if !sensor {
+                manifolds.push(Manifold {
+                    a: i,
+                    b: j,
+                    normal,
+                    points,
+                });
+            }
+        }
+
+        self.solve_velocities(&manifolds);
+
+        for body in self.bodies.iter_mut().flatten() {
+            body.position += body.velocity * dt;
+            body.angle += body.angular_velocity * dt;
+        }
+
+        for m in manifolds.iter() {
+            let (ima, imb) = (self.body(m.a).inv_mass, self.body(m.b).inv_mass);
+            let depth = m.points.iter().map(|p| p.depth).fold(0.0, f32::max);
+            if ima + imb == 0.0 {
+                continue;
+            }
+            let correction = m.normal * ((depth - SLOP).max(0.0) / (ima + imb) * CORRECTION);
+            self.body_mut(m.a).position -= correction * ima;
+            self.body_mut(m.b).position += correction * imb;
+        }
+    }
+
+    fn body(&self, i: usize) -> &Body {
+        self.bodies[i].as_ref().unwrap()
+    }
+
+    fn body_mut(&mut self, i: usize) -> &mut Body {
+        self.bodies[i].as_mut().unwrap()
+    }
+
+    /// Pairs sharing a spatial hash cell with overlapping bounds, sorted
+    fn candidate_pairs(&self) -> Vec<(usize, usize)> {
+        let mut cells: HashMap<IVec2, Vec<usize>> = HashMap::new();
+        let mut bounds = vec![Range2::ZERO; self.bodies.len()];
+        for (i, body) in self.bodies.iter().enumerate() {
+            let Some(body) = body else {
+                continue;
+            };
+            bounds[i] = body.bounds();
+            let min = (bounds[i].min / self.cell_size).floor().as_ivec2();
+            let max = (bounds[i].max / self.cell_size).floor().as_ivec2();
+            for y in min.y..=max.y {
+                for x in min.x..=max.x {
+                    cells.entry(IVec2::new(x, y)).or_default().push(i);
+                }
+            }
+        }
+        let mut pairs = Vec::new();
+        for list in cells.values() {
+            for (k, i) in list.iter().copied().enumerate() {
+                for j in list[k + 1..].iter().copied() {
+                    let (a, b) = (self.body(i), self.body(j));
+                    let apart = (bounds[i] & bounds[j]).is_negative();
+                    if (a.is_static() && b.is_static()) || !a.can_collide(b) || apart {
+                        continue;
+                    }
+                    pairs.push((i, j));
+                }
+            }
+        }
+        pairs.sort_unstable();
+        pairs.dedup();
+        pairs
+    }
+
+    /// Sequential impulses with accumulated clamping, restitution and
+    /// Coulomb friction, warm started from the impulses of the last step
+    fn solve_velocities(&mut self, manifolds: &[Manifold]) {
+        struct Point {
+            ra: Vec2,
+            rb: Vec2,
+            normal_mass: f32,
+            tangent_mass: f32,
+            bias: f32,
+            pn: f32,
+            pt: f32,
+        }
+        let mut solver: Vec<Vec<Point>> = Vec::with_capacity(manifolds.len());
+        for m in manifolds.iter() {
+            let (a, b) = (self.body(m.a), self.body(m.b));
+            let tangent = m.normal.perp();
+            let restitution = a.restitution.max(b.restitution);
+            let points = m
+                .points
+                .iter()
+                .map(|c| {
+                    let p = &c.point;
+                    let (ra, rb) = (*p - a.position, *p - b.position);
+                    let k = |axis: Vec2| {
+                        let (rna, rnb) = (ra.perp_dot(axis), rb.perp_dot(axis));
+                        let k = a.inv_mass
+                            + b.inv_mass
+                            + a.inv_inertia * rna * rna
+                            + b.inv_inertia * rnb * rnb;
+                        if k > 0.0 {
+                            1.0 / k
+                        } else {
+                            0.0
+                        }
+                    };
+                    let vn = (b.velocity_at(*p) - a.velocity_at(*p)).dot(m.normal);
+                    Point {
+                        ra,
+                        rb,
+                        normal_mass: k(m.normal),
+                        tangent_mass: k(tangent),
+                        bias: if vn < -RESTITUTION_THRESHOLD {
+                            -restitution * vn
+                        } else {
+                            0.0
+                        },
+                        pn: 0.0,
+                        pt: 0.0,
+                    }
+                })
+                .collect();
+            solver.push(points);
+        }
+
+        for (m, points) in manifolds.iter().zip(solver.iter_mut()) {
+            let tangent = m.normal.perp();
+            for (c, p) in m.points.iter().zip(points.iter_mut()) {
+                let Some((pn, pt)) = self.impulses.get(&(m.a, m.b, c.id)) else {
+                    continue;
+                };
+                (p.pn, p.pt) = (*pn, *pt);
+                let impulse = m.normal * p.pn + tangent * p.pt;
+                let a = self.body_mut(m.a);
+                a.velocity -= impulse * a.inv_mass;
+                a.angular_velocity -= p.ra.perp_dot(impulse) * a.inv_inertia;
+                let b = self.body_mut(m.b);
+                b.velocity += impulse * b.inv_mass;
+                b.angular_velocity += p.rb.perp_dot(impulse) * b.inv_inertia;
+            }
+        }
+
+        for _ in 0..self.iterations {
+            for (m, points) in manifolds.iter().zip(solver.iter_mut()) {
+                let (a, b) = (self.body(m.a), self.body(m.b));
+                let friction = (a.friction * b.friction).sqrt();
+                let (ima, imb, ia, ib) = (a.inv_mass, b.inv_mass, a.inv_inertia, b.inv_inertia);
+                let (mut va, mut wa) = (a.velocity, a.angular_velocity);
+                let (mut vb, mut wb) = (b.velocity, b.angular_velocity);
+                let tangent = m.normal.perp();
+                for p in points.iter_mut() {
+                    let relative = |va: Vec2, wa: f32, vb: Vec2, wb: f32| {
+                        vb + p.rb.perp() * wb - va - p.ra.perp() * wa
+                    };
+
+                    let vn = relative(va, wa, vb, wb).dot(m.normal);
+                    let pn = (p.pn + p.normal_mass * (p.bias - vn)).max(0.0);
+                    let impulse = m.normal * (pn - p.pn);
+                    p.pn = pn;
+                    va -= impulse * ima;
+                    wa -= p.ra.perp_dot(impulse) * ia;
+                    vb += impulse * imb;
+                    wb += p.rb.perp_dot(impulse) * ib;
+
+                    let vt = relative(va, wa, vb, wb).dot(tangent);
+                    let max_pt = friction * p.pn;
+                    let pt = (p.pt - p.tangent_mass * vt).clamp(-max_pt, max_pt);
+                    let impulse = tangent * (pt - p.pt);
+                    p.pt = pt;
+                    va -= impulse * ima;
+                    wa -= p.ra.perp_dot(impulse) * ia;
+                    vb += impulse * imb;
+                    wb += p.rb.perp_dot(impulse) * ib;
+                }
+                let a = self.body_mut(m.a);
+                a.velocity = va;
+                a.angular_velocity = wa;
+                let b = self.body_mut(m.b);
+                b.velocity = vb;
+                b.angular_velocity = wb;
+            }
+        }
+
+        self.impulses.clear();
+        for (m, points) in manifolds.iter().zip(solver.iter()) {
+            for (c, p) in m.points.iter().zip(points.iter()) {
+                self.impulses.insert((m.a, m.b, c.id), (p.pn, p.pt));
+            }
+        }
+    }
+}
+
+/// Normal from a to b and the contact points
+fn collide(a: &Body, b: &Body) -> Option<(Vec2, Vec<ContactPoint>)> {
+    match (&a.shape, &b.shape) {
+        (Shape::Circle(ra), Shape::Circle(rb)) => {
+            let d = b.position - a.position;
+            let dist = d.length();
+            if dist >= ra + rb {
+                return None;
+            }
+            let normal = if dist > 0.0 { d / dist } else { Vec2::X };
+            let point = a.position + normal * (ra - (ra + rb - dist) * 0.5);
+            let depth = ra + rb - dist;
+            Some((
+                normal,
+                vec![ContactPoint {
+                    point,
+                    depth,
+                    id: 0,
+                }],
+            ))
+        }
+        (Shape::Polygon(verts), Shape::Circle(r)) => {
+            collide_polygon_circle(&a.world_vertices(verts), b.position, *r)
+        }
+        (Shape::Circle(r), Shape::Polygon(verts)) => {
+            let (normal, points) =
+                collide_polygon_circle(&b.world_vertices(verts), a.position, *r)?;
+            Some((-normal, points))
+        }
+        (Shape::Polygon(va), Shape::Polygon(vb)) => {
+            collide_polygons(&a.world_vertices(va), &b.world_vertices(vb))
+        }
+    }
+}
+
+fn edge_normal(verts: &[Vec2], i: usize) -> Vec2 {
+    let e = verts[(i + 1) % verts.len()] - verts[i];
+    Vec2::new(e.y, -e.x).normalize()
+}
+
+fn collide_polygon_circle(
+    verts: &[Vec2],
+    center: Vec2,
+    r: f32,
+) -> Option<(Vec2, Vec<ContactPoint>)> {
+    let (edge, separation) = (0..verts.len())
+        .map(|i| (i, edge_normal(verts, i).dot(center - verts[i])))
+        .max_by(|a, b| a.1.total_cmp(&b.1))?;
+    if separation > r {
+        return None;
+    }
+    if separation <= 0.0 {
+        let normal = edge_normal(verts, edge);
+        let point = center - normal * r;
+        let depth = r - separation;
+        return Some((
+            normal,
+            vec![ContactPoint {
+                point,
+                depth,
+                id: 0,
+            }],
+        ));
+    }
+    let (v1, v2) = (verts[edge], verts[(edge + 1) % verts.len()]);
+    let e = v2 - v1;
+    let t = ((center - v1).dot(e) / e.length_squared()).clamp(0.0, 1.0);
+    let closest = v1 + e * t;
+    let d = center - closest;
+    let dist = d.length();
+    if dist > r || dist == 0.0 {
+        return None;
+    }
+    let (point, depth) = (closest, r - dist);
+    Some((
+        d / dist,
+        vec![ContactPoint {
+            point,
+            depth,
+            id: 0,
+        }],
+    ))
+}
+
+/// Edge of a with the largest separation from b along its normal
+fn max_separation(a: &[Vec2], b: &[Vec2]) -> (usize, f32) {
+    let mut best = (0, f32::MIN);
+    for i in 0..a.len() {
+        let n = edge_normal(a, i);
+        let s = b.iter().map(|p| n.dot(*p - a[i])).fold(f32::MAX, f32::min);
+        if s > best.1 {
+            best = (i, s);
+        }
+    }
+    best
+}
+
+/// Keeps the part of a segment where normal . p <= offset
+fn clip_segment(points: [Vec2; 2], normal: Vec2, offset: f32) -> Option<[Vec2; 2]> {
+    let d = points.map(|p| normal.dot(p) - offset);
+    if d[0] > 0.0 && d[1] > 0.0 {
+        return None;
+    }
+    let crossing = || points[0] + (points[1] - points[0]) * (d[0] / (d[0] - d[1]));
+    Some(match (d[0] <= 0.0, d[1] <= 0.0) {
+        (true, true) => points,
+        (true, false) => [points[0], crossing()],
+        _ => [crossing(), points[1]],
+    })
+}
+
+/// Separating axis test, then the incident edge is clipped against the
+/// reference face as in Box2D
+fn collide_polygons(a: &[Vec2], b: &[Vec2]) -> Option<(Vec2, Vec<ContactPoint>)> {
+    let (edge_a, sep_a) = max_separation(a, b);
+    if sep_a > 0.0 {
+        return None;
+    }
+    let (edge_b, sep_b) = max_separation(b, a);
+    if sep_b > 0.0 {
+        return None;
+    }
+    // Prefer a as reference so that the choice does not flicker
+    let (reference, incident, edge, flip) = if sep_b > sep_a + 1e-3 {
+        (b, a, edge_b, true)
+    } else {
+        (a, b, edge_a, false)
+    };
+    let normal = edge_normal(reference, edge);
+    let inc = (0..incident.len()).min_by(|i, j| {
+        edge_normal(incident, *i)
+            .dot(normal)
+            .total_cmp(&edge_normal(incident, *j).dot(normal))
+    })?;
+    let (v1, v2) = (reference[edge], reference[(edge + 1) % reference.len()]);
+    let tangent = (v2 - v1).normalize();
+    let segment = [incident[inc], incident[(inc + 1) % incident.len()]];
+    let segment = clip_segment(segment, -tangent, -tangent.dot(v1))?;
+    let segment = clip_segment(segment, tangent, tangent.dot(v2))?;
+    let feature = (flip as u32) << 16 | (edge as u32) << 8 | inc as u32;
+    let points: Vec<ContactPoint> = segment
+        .iter()
+        .enumerate()
+        .map(|(k, p)| (k, *p, normal.dot(*p - v1)))
+        .filter(|(_, _, s)| *s <= 0.0)
+        .map(|(k, p, s)| ContactPoint {
+            point: p - normal * (s * 0.5),
+            depth: -s,
+            id: feature << 1 | k as u32,
+        })
+        .collect();
+    if points.is_empty() {
+        return None;
+    }
+    Some((if flip { -normal } else { normal }, points))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const DT: f32 = 1.0 / 60.0;
+
+    fn world_with_ground() -> World {
+        let mut world = World::new(Vec2::new(0.0, -10.0));
+        world.insert(
+            Body::fixed(Shape::rect(Vec2::new(20.0, 0.5))).with_position(Vec2::new(0.0, -0.5)),
+        );
+        world
+    }
+
+    fn run(world: &mut World, seconds: f32) {
+        for _ in 0..(seconds / DT) as u32 {
+            world.step(DT);
+        }
+    }
+
+    #[test]
+    fn mass_properties() {
+        let (area, inertia) = Shape::rect(Vec2::new(1.0, 0.5)).mass_properties();
+        assert!((area - 2.0).abs() < 1e-6);
+        assert!((inertia - 2.0 * 5.0 / 12.0).abs() < 1e-6);
+        let body = Body::dynamic(Shape::circle(1.0), 2.0);
+        assert!((body.mass() - 2.0 * PI).abs() < 1e-5);
+        assert!(Body::fixed(Shape::circle(1.0)).is_static());
+
+        let Some(Shape::Polygon(tri)) =
+            Shape::polygon(&[Vec2::ZERO, Vec2::new(3.0, 0.0), Vec2::new(0.0, 3.0)])
+        else {
+            panic!();
+        };
+        assert!(centroid(&tri).unwrap().length() < 1e-6);
+        assert_eq!(Shape::polygon(&[Vec2::ZERO, Vec2::X, Vec2::X * 2.0]), None);
+    }
+
+    #[test]
+    fn circle_comes_to_rest() {
+        let mut world = world_with_ground();
+        let ball =
+            world.insert(Body::dynamic(Shape::circle(0.5), 1.0).with_position(Vec2::new(0.0, 5.0)));
+        run(&mut world, 4.0);
+        let ball = world.get(ball).unwrap();
+        assert!((ball.position.y - 0.5).abs() < 0.02, "{}", ball.position);
+        assert!(ball.velocity.length() < 0.05);
+        assert!(world
+            .contacts()
+            .iter()
+            .any(|c| c.normal.y > 0.99 && !c.sensor));
+    }
+
+    #[test]
+    fn boxes_stack() {
+        let mut world = world_with_ground();
+        let boxes: Vec<BodyId> = (0..4)
+            .map(|i| {
+                let body = Body::dynamic(Shape::rect(Vec2::splat(0.5)), 1.0);
+                world.insert(body.with_position(Vec2::new(0.0, 0.5 + i as f32 * 1.05)))
+            })
+            .collect();
+        run(&mut world, 5.0);
+        for (i, id) in boxes.iter().enumerate() {
+            let body = world.get(*id).unwrap();
+            assert!(body.position.x.abs() < 0.05, "{i} {}", body.position);
+            assert!(
+                (body.position.y - (0.5 + i as f32)).abs() < 0.1,
+                "{i} {}",
+                body.position
+            );
+            assert!(body.angle.abs() < 0.05);
+        }
+    }
+
+    #[test]
+    fn restitution_bounces() {
+        let bounce_height = |restitution: f32| {
+            let mut world = world_with_ground();
+            let body = Body::dynamic(Shape::circle(0.5), 1.0).with_restitution(restitution);
+            let ball = world.insert(body.with_position(Vec2::new(0.0, 4.5)));
+            let mut bounced = false;
+            let mut height: f32 = 0.0;
+            for _ in 0..240 {
+                world.step(DT);
+                let ball = world.get(ball).unwrap();
+                bounced |= ball.velocity.y > 0.0;
+                if bounced {
+                    height = height.max(ball.position.y);
+                }
+            }
+            height
+        };
+        assert!(bounce_height(1.0) > 4.0);
+        assert!(bounce_height(0.5) < 2.0 && bounce_height(0.5) > 1.0);
+        assert!(bounce_height(0.0) < 0.6);
+    }
+
+    #[test]
+    fn friction_stops_sliding() {
+        let slide = |friction: f32| {
+            let mut world = world_with_ground();
+            world.get_mut(BodyId(0)).unwrap().friction = friction;
+            let body = Body::dynamic(Shape::rect(Vec2::splat(0.5)), 1.0).with_friction(friction);
+            let id = world.insert(
+                body.with_position(Vec2::new(0.0, 0.5))
+                    .with_velocity(Vec2::new(4.0, 0.0)),
+            );
+            run(&mut world, 2.0);
+            world.get(id).unwrap().clone()
+        };
+        let rough = slide(0.5);
+        assert!(rough.velocity.length() < 0.01);
+        // v^2 / (2 mu g)
+        assert!((rough.position.x - 1.6).abs() < 0.2, "{}", rough.position);
+        let slick = slide(0.0);
+        assert!((slick.velocity.x - 4.0).abs() < 0.01);
+    }
+
+    #[test]
+    fn sensors_and_layers() {
+        let mut world = world_with_ground();
+        let sensor = world.insert(
+            Body::fixed(Shape::rect(Vec2::splat(1.0)))
+                .with_position(Vec2::new(0.0, 3.0))
+                .as_sensor(),
+        );
+        let ball = world
+            .insert(Body::dynamic(Shape::circle(0.25), 1.0).with_position(Vec2::new(0.0, 6.0)));
+        let mut touched = false;
+        for _ in 0..120 {
+            world.step(DT);
+            touched |= world
+                .contacts()
+                .iter()
+                .any(|c| c.sensor && c.a == sensor && c.b == ball);
+        }
+        assert!(touched);
+        assert!((world.get(ball).unwrap().position.y - 0.25).abs() < 0.02);
+
+        // Layer 2 only collides with layer 2, so it falls through the ground
+        let ghost = Body::dynamic(Shape::circle(0.25), 1.0).with_layers(2, 2);
+        let ghost = world.insert(ghost.with_position(Vec2::new(5.0, 2.0)));
+        run(&mut world, 1.0);
+        assert!(world.get(ghost).unwrap().position.y < -1.0);
+    }
+
+    #[test]
+    fn off_center_impulse_spins() {
+        let mut body = Body::dynamic(Shape::rect(Vec2::new(1.0, 0.25)), 1.0);
+        body.apply_impulse(Vec2::new(0.0, 1.0), Vec2::new(1.0, 0.0));
+        assert!(body.angular_velocity > 0.0);
+        assert!((body.velocity.y - body.inv_mass).abs() < 1e-6);
+        assert_eq!(body.velocity_at(body.position), body.velocity);
+    }
+
+    #[test]
+    fn ids_and_replay() {
+        let build = || {
+            let mut world = world_with_ground();
+            for i in 0..12 {
+                let shape = if i % 2 == 0 {
+                    Shape::circle(0.3)
+                } else {
+                    Shape::rect(Vec2::new(0.4, 0.2))
+                };
+                let x = (i % 4) as f32 * 0.5 - 1.0;
+                let body = Body::dynamic(shape, 1.0).with_restitution(0.3);
+                world.insert(
+                    body.with_position(Vec2::new(x, 1.0 + i as f32 * 0.6))
+                        .with_angle(i as f32),
+                );
+            }
+            world
+        };
+        let mut a = build();
+        let mut b = build();
+        run(&mut a, 3.0);
+        run(&mut b, 3.0);
+        assert_eq!(a, b);
+        for (_, body) in a.iter().filter(|(_, body)| !body.is_static()) {
+            assert!(body.position.y > 0.0 && body.position.is_finite());
+        }
+
+        let removed = a.remove(BodyId(3));
+        assert!(removed.is_some() && a.get(BodyId(3)).is_none());
+        assert_eq!(a.insert(removed.unwrap()), BodyId(3));
+        assert_eq!(a.iter().count(), 13);
+    }
+}
@@ -0,0 +1,384 @@
+// Bridson's algorithm:
+// https://www.cs.ubc.ca/~rbridson/docs/bridson-siggraph07-poissondisk.pdf
+
+use std::f32::consts::TAU;
+
+use glam::{IVec2, IVec3, Vec2, Vec3};
+
+use crate::pcg::PcgRng;
+use crate::polygon::{contains_point, triangulate};
+use crate::range::{Range2, Range3};
+
+/// Number of candidates tried around each active point before retiring it
+pub const DEFAULT_ATTEMPTS: u32 = 30;
+
+const EMPTY: u32 = u32::MAX;
+
+struct Grid2 {
+    origin: Vec2,
+    cell_size: f32,
+    size: IVec2,
+    cells: Vec<u32>,
+}
+
+impl Grid2 {
+    fn new(bounds: Range2, cell_size: f32) -> Self {
+        let size = (bounds.size() / cell_size).ceil().as_ivec2().max(IVec2::ONE);
+        Self {
+            origin: bounds.min,
+            cell_size,
+            size,
+            cells: vec![EMPTY; (size.x * size.y) as usize],
+        }
+    }
+
+    fn cell(&self, p: Vec2) -> IVec2 {
+        ((p - self.origin) / self.cell_size)
+            .floor()
+            .as_ivec2()
+            .clamp(IVec2::ZERO, self.size - IVec2::ONE)
+    }
+
+    fn index(&self, c: IVec2) -> usize {
+        (c.y * self.size.x + c.x) as usize
+    }
+}
+
+struct Samples2 {
+    grid: Grid2,
+    points: Vec<Vec2>,
+    radii: Vec<f32>,
+    active: Vec<u32>,
+}
+
+impl Samples2 {
+    fn insert(&mut self, p: Vec2, radius: f32) {
+        let index = self.points.len() as u32;
+        let ci = self.grid.index(self.grid.cell(p));
+        self.grid.cells[ci] = index;
+        self.points.push(p);
+        self.radii.push(radius);
+        self.active.push(index);
+    }
+
+    fn is_clear(&self, p: Vec2, radius: f32, reach: i32) -> bool {
+        let c = self.grid.cell(p);
+        let size = self.grid.size;
+        for y in (c.y - reach).max(0)..(c.y + reach + 1).min(size.y) {
+            for x in (c.x - reach).max(0)..(c.x + reach + 1).min(size.x) {
+                let qi = self.grid.cells[self.grid.index(IVec2::new(x, y))];
+                if qi != EMPTY {
+                    let min_dist = radius.max(self.radii[qi as usize]);
+                    if self.points[qi as usize].distance_squared(p) < min_dist * min_dist {
+                        return false;
+                    }
+                }
+            }
+        }
+        true
+    }
+}
+
+fn sample_bridson2(
+    rng: &mut PcgRng,
+    bounds: Range2,
+    min_radius: f32,
+    max_radius: f32,
+    seeds: impl IntoIterator<Item = Vec2>,
+    radius_at: impl Fn(Vec2) -> f32,
+    accept: impl Fn(Vec2) -> bool,
+) -> Vec<Vec2> {
+    if bounds.is_empty() || min_radius <= 0.0 {
+        return Vec::new();
+    }
+    let radius_at = |p: Vec2| radius_at(p).clamp(min_radius, max_radius);
+    // Any two points are at least min_radius apart, so each cell holds at most one
+    let mut samples = Samples2 {
+        grid: Grid2::new(bounds, min_radius / 2f32.sqrt()),
+        points: Vec::new(),
+        radii: Vec::new(),
+        active: Vec::new(),
+    };
+    let reach = (max_radius / samples.grid.cell_size).ceil() as i32;
+    // Growth starts from a random point, then restarts from the given seeds
+    // and a jittered point in each empty cell, so parts of the region that
+    // are thin or only connected by narrow necks are covered too
+    let seeds: Vec<Vec2> = std::iter::once(bounds.lerp(rng.next_vec2())).chain(seeds).collect();
+    let seed_count = seeds.len() + samples.grid.cells.len();
+    let mut next_seed = 0;
+    while next_seed < seed_count {
+        let p = match seeds.get(next_seed) {
+            Some(p) => *p,
+            None => {
+                let ci = next_seed - seeds.len();
+                if samples.grid.cells[ci] != EMPTY {
+                    next_seed += 1;
+                    continue;
+                }
+                let grid = &samples.grid;
+                let c = IVec2::new(ci as i32 % grid.size.x, ci as i32 / grid.size.x);
+                grid.origin + (c.as_vec2() + rng.next_vec2()) * grid.cell_size
+            }
+        };
+        next_seed += 1;
+        if bounds.contains(p) && accept(p) {
+            let pr = radius_at(p);
+            if samples.is_clear(p, pr, reach) {
+                samples.insert(p, pr);
+            }
+        }
+        grow2(rng, &mut samples, bounds, reach, &radius_at, &accept);
+    }
+    samples.points
+}
+
+fn grow2(
+    rng: &mut PcgRng,
+    samples: &mut Samples2,
+    bounds: Range2,
+    reach: i32,
+    radius_at: &impl Fn(Vec2) -> f32,
+    accept: &impl Fn(Vec2) -> bool,
+) {
+    while !samples.active.is_empty() {
+        let ai = rng.next_u32_to(samples.active.len() as u32) as usize;
+        let center = samples.points[samples.active[ai] as usize];
+        let r = samples.radii[samples.active[ai] as usize];
+        let mut found = false;
+        for _ in 0..DEFAULT_ATTEMPTS {
+            let a = rng.next_f32() * TAU;
+            let d = rng.next_f32_in(r * r..4.0 * r * r).sqrt();
+            let p = center + Vec2::new(a.cos(), a.sin()) * d;
+            if bounds.contains(p) && accept(p) {
+                let pr = radius_at(p);
+                if samples.is_clear(p, pr, reach) {
+                    samples.insert(p, pr);
+                    found = true;
+                    break;
+                }
+            }
+        }
+        if !found {
+            samples.active.swap_remove(ai);
+        }
+    }
+}
+
+/// Points within bounds where no two are closer than radius
+pub fn sample2(rng: &mut PcgRng, bounds: Range2, radius: f32) -> Vec<Vec2> {
+    sample_bridson2(rng, bounds, radius, radius, [], |_| radius, |_| true)
+}
+
+/// Points inside a closed polygon where no two are closer than radius
+pub fn sample2_in_polygon(rng: &mut PcgRng, polygon: &[Vec2], radius: f32) -> Vec<Vec2> {
+    match Range2::from_vertices(polygon.iter().copied()) {
+        Some(bounds) => sample_bridson2(
+            rng,
+            bounds,
+            radius,
+            radius,
+            triangulate(polygon, &[])
+                .into_iter()
+                .map(|[a, b, c]| (a + b + c) / 3.0),
+            |_| radius,
+            |p| contains_point(polygon, p),
+        ),
+        None => Vec::new(),
+    }
+}
+
+/// Density in [0, 1] maps to spacing, where 1 packs points min_radius apart
+/// and 0 spreads them max_radius apart.
+pub fn sample2_variable(
+    rng: &mut PcgRng,
+    bounds: Range2,
+    min_radius: f32,
+    max_radius: f32,
+    density: impl Fn(Vec2) -> f32,
+) -> Vec<Vec2> {
+    sample_bridson2(
+        rng,
+        bounds,
+        min_radius,
+        max_radius,
+        [],
+        |p| max_radius + (min_radius - max_radius) * density(p).clamp(0.0, 1.0),
+        |_| true,
+    )
+}
+
+/// Points within bounds where no two are closer than radius
+pub fn sample3(rng: &mut PcgRng, bounds: Range3, radius: f32) -> Vec<Vec3> {
+    let mut points = Vec::new();
+    let size = bounds.size();
+    if size.x <= 0.0 || size.y <= 0.0 || size.z <= 0.0 || radius <= 0.0 {
+        return points;
+    }
+    let cell_size = radius / 3f32.sqrt();
+    let dims = (size / cell_size).ceil().as_ivec3().max(IVec3::ONE);
+    let mut cells = vec![EMPTY; (dims.x * dims.y * dims.z) as usize];
+    let cell = |p: Vec3| {
+        ((p - bounds.min) / cell_size)
+            .floor()
+            .as_ivec3()
+            .clamp(IVec3::ZERO, dims - IVec3::ONE)
+    };
+    let index = |c: IVec3| ((c.z * dims.y + c.y) * dims.x + c.x) as usize;
+    let mut active = Vec::new();
+    let first = bounds.lerp(rng.next_vec3()).min(bounds.max - size * 1e-6);
+    cells[index(cell(first))] = 0;
+    points.push(first);
+    active.push(0u32);
+    while !active.is_empty() {
+        let ai = rng.next_u32_to(active.len() as u32) as usize;
+        let center = points[active[ai] as usize];
+        let mut found = false;
+        for _ in 0..DEFAULT_ATTEMPTS {
+            let dir = rng.next_vec3_unit_length();
+            let d = rng.next_f32_in(radius..2.0 * radius);
+            let p = center + dir * d;
+            if !bounds.contains(p) {
+                continue;
+            }
+            let c = cell(p);
+            let lo = (c - IVec3::splat(2)).max(IVec3::ZERO);
+            let hi = (c + IVec3::splat(3)).min(dims);
+            let mut valid = true;
+            'search: for z in lo.z..hi.z {
+                for y in lo.y..hi.y {
+                    for x in lo.x..hi.x {
+                        let qi = cells[index(IVec3::new(x, y, z))];
+                        if qi != EMPTY && points[qi as usize].distance_squared(p) < radius * radius {
+                            valid = false;
+                            break 'search;
+                        }
+                    }
+                }
+            }
+            if valid {
+                cells[index(c)] = points.len() as u32;
+                active.push(points.len() as u32);
+                points.push(p);
+                found = true;
+                break;
+            }
+        }
+        if !found {
+            active.swap_remove(ai);
+        }
+    }
+    points
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn min_distance2(points: &[Vec2]) -> f32 {
+        let mut min = f32::MAX;
+        for i in 0..points.len() {
+            for j in i + 1..points.len() {
+                min = min.min(points[i].distance(points[j]));
+            }
+        }
+        min
+    }
+
+    #[test]
+    fn min_distance_guarantee() {
+        let mut rng = PcgRng::new(1, 0);
+        let bounds = Range2::sized(Vec2::ZERO, Vec2::new(40.0, 30.0));
+        let points = sample2(&mut rng, bounds, 2.0);
+        assert!(points.len() > 100);
+        assert!(points.iter().all(|p| bounds.contains(*p)));
+        assert!(min_distance2(&points) >= 2.0 - 1e-4);
+    }
+
+    #[test]
+    fn reproducible_from_seed() {
+        let bounds = Range2::sized(Vec2::ZERO, Vec2::splat(10.0));
+        let a = sample2(&mut PcgRng::new(7, 0), bounds, 1.0);
+        let b = sample2(&mut PcgRng::new(7, 0), bounds, 1.0);
+        let c = sample2(&mut PcgRng::new(8, 0), bounds, 1.0);
+        assert_eq!(a, b);
+        assert_ne!(a, c);
+    }
+
+    #[test]
+    fn polygon_points_inside() {
+        let mut rng = PcgRng::new(2, 0);
+        let triangle = [Vec2::ZERO, Vec2::new(20.0, 0.0), Vec2::new(0.0, 20.0)];
+        let points = sample2_in_polygon(&mut rng, &triangle, 1.0);
+        assert!(points.len() > 50);
+        assert!(points.iter().all(|p| p.x + p.y <= 20.0));
+        assert!(min_distance2(&points) >= 1.0 - 1e-4);
+    }
+
+    #[test]
+    fn thin_polygon() {
+        // Diagonal strip covering 2% of its bounds
+        let strip = [
+            Vec2::ZERO,
+            Vec2::new(1.0, 0.0),
+            Vec2::new(50.0, 49.0),
+            Vec2::new(50.0, 50.0),
+            Vec2::new(49.0, 50.0),
+            Vec2::new(0.0, 1.0),
+        ];
+        for seed in 0..200 {
+            let points = sample2_in_polygon(&mut PcgRng::new(seed, 0), &strip, 1.0);
+            assert!(points.len() > 20);
+            assert!(points.iter().all(|p| contains_point(&strip, *p)));
+        }
+    }
+
+    #[test]
+    fn polygon_with_narrow_neck() {
+        // Two squares joined by a corridor much narrower than the radius
+        let dumbbell = [
+            Vec2::ZERO,
+            Vec2::new(10.0, 0.0),
+            Vec2::new(10.0, 4.9),
+            Vec2::new(30.0, 4.9),
+            Vec2::new(30.0, 0.0),
+            Vec2::new(40.0, 0.0),
+            Vec2::new(40.0, 10.0),
+            Vec2::new(30.0, 10.0),
+            Vec2::new(30.0, 5.1),
+            Vec2::new(10.0, 5.1),
+            Vec2::new(10.0, 10.0),
+            Vec2::new(0.0, 10.0),
+        ];
+        for seed in 0..20 {
+            let points = sample2_in_polygon(&mut PcgRng::new(seed, 0), &dumbbell, 1.0);
+            assert!(points.iter().filter(|p| p.x < 10.0).count() > 20);
+            assert!(points.iter().filter(|p| p.x > 30.0).count() > 20);
+            assert!(min_distance2(&points) >= 1.0 - 1e-4);
+        }
+    }
+
+    #[test]
+    fn variable_radius() {
+        let mut rng = PcgRng::new(3, 0);
+        let bounds = Range2::sized(Vec2::ZERO, Vec2::new(40.0, 20.0));
+        let points = sample2_variable(&mut rng, bounds, 0.5, 2.0, |p| p.x / 40.0);
+        assert!(min_distance2(&points) >= 0.5 - 1e-4);
+        let left = points.iter().filter(|p| p.x < 20.0).count();
+        let right = points.len() - left;
+        assert!(right > left * 2);
+    }
+
+    #[test]
+    fn min_distance_guarantee_3d() {
+        let mut rng = PcgRng::new(4, 0);
+        let bounds = Range3::sized(Vec3::ZERO, Vec3::splat(10.0));
+        let points = sample3(&mut rng, bounds, 1.5);
+        assert!(points.len() > 50);
+        for i in 0..points.len() {
+            assert!(bounds.contains(points[i]));
+            for j in i + 1..points.len() {
+                assert!(points[i].distance(points[j]) >= 1.5 - 1e-4);
+            }
+        }
+    }
+}
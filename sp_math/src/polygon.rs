@@ -0,0 +1,786 @@
+// Polygons are slices of vertices with an implicit closing edge. Positive
+// area means counter-clockwise winding in a y-up coordinate system.
+
+use std::collections::HashMap;
+
+use glam::Vec2;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum Winding {
+    Ccw,
+    Cw,
+}
+
+/// Shoelace formula, positive when counter-clockwise
+pub fn signed_area(polygon: &[Vec2]) -> f32 {
+    let mut sum = 0.0;
+    let mut j = polygon.len().wrapping_sub(1);
+    for i in 0..polygon.len() {
+        sum += polygon[j].perp_dot(polygon[i]);
+        j = i;
+    }
+    sum * 0.5
+}
+
+/// Area weighted center, None for degenerate polygons with zero area
+pub fn centroid(polygon: &[Vec2]) -> Option<Vec2> {
+    let mut sum = Vec2::ZERO;
+    let mut area = 0.0;
+    let mut j = polygon.len().wrapping_sub(1);
+    for i in 0..polygon.len() {
+        let cross = polygon[j].perp_dot(polygon[i]);
+        sum += (polygon[j] + polygon[i]) * cross;
+        area += cross;
+        j = i;
+    }
+    (area != 0.0).then(|| sum / (3.0 * area))
+}
+
+/// None for degenerate polygons with zero area
+pub fn winding(polygon: &[Vec2]) -> Option<Winding> {
+    let area = signed_area(polygon);
+    if area > 0.0 {
+        Some(Winding::Ccw)
+    } else if area < 0.0 {
+        Some(Winding::Cw)
+    } else {
+        None
+    }
+}
+
+/// Copy of the polygon with the given winding
+pub fn with_winding(polygon: &[Vec2], winding: Winding) -> Vec<Vec2> {
+    let mut result = polygon.to_vec();
+    let is_ccw = signed_area(polygon) >= 0.0;
+    if is_ccw != (winding == Winding::Ccw) {
+        result.reverse();
+    }
+    result
+}
+
+/// Even-odd rule
+pub fn contains_point(polygon: &[Vec2], p: Vec2) -> bool {
+    let mut inside = false;
+    let mut j = polygon.len().wrapping_sub(1);
+    for i in 0..polygon.len() {
+        let a = polygon[i];
+        let b = polygon[j];
+        if (a.y > p.y) != (b.y > p.y) && p.x < (b.x - a.x) * (p.y - a.y) / (b.y - a.y) + a.x {
+            inside = !inside;
+        }
+        j = i;
+    }
+    inside
+}
+
+/// Number of times the polygon winds counter-clockwise around p
+pub fn winding_number(polygon: &[Vec2], p: Vec2) -> i32 {
+    let mut wn = 0;
+    let mut j = polygon.len().wrapping_sub(1);
+    for i in 0..polygon.len() {
+        let a = polygon[j];
+        let b = polygon[i];
+        let side = (b - a).perp_dot(p - a);
+        if a.y <= p.y {
+            if b.y > p.y && side > 0.0 {
+                wn += 1;
+            }
+        } else if b.y <= p.y && side < 0.0 {
+            wn -= 1;
+        }
+        j = i;
+    }
+    wn
+}
+
+/// Andrew's monotone chain. Returns a counter-clockwise hull without
+/// collinear points.
+pub fn convex_hull(points: &[Vec2]) -> Vec<Vec2> {
+    let mut sorted = points.to_vec();
+    sorted.sort_by(|a, b| a.x.total_cmp(&b.x).then(a.y.total_cmp(&b.y)));
+    sorted.dedup();
+    if sorted.len() < 3 {
+        return sorted;
+    }
+    let mut hull: Vec<Vec2> = Vec::with_capacity(sorted.len() * 2);
+    for pass in 0..2 {
+        let start = hull.len();
+        for p in sorted.iter() {
+            while hull.len() >= start + 2 {
+                let a = hull[hull.len() - 2];
+                let b = hull[hull.len() - 1];
+                if (b - a).perp_dot(*p - a) > 0.0 {
+                    break;
+                }
+                hull.pop();
+            }
+            hull.push(*p);
+        }
+        // The last point is the first point of the other chain
+        hull.pop();
+        if pass == 0 {
+            sorted.reverse();
+        }
+    }
+    hull
+}
+
+fn is_convex(a: Vec2, b: Vec2, c: Vec2) -> bool {
+    (b - a).perp_dot(c - b) > 0.0
+}
+
+/// Inclusive of edges, for counter-clockwise triangles
+fn triangle_contains(a: Vec2, b: Vec2, c: Vec2, p: Vec2) -> bool {
+    (b - a).perp_dot(p - a) >= 0.0 && (c - b).perp_dot(p - b) >= 0.0 && (a - c).perp_dot(p - c) >= 0.0
+}
+
+/// Joins a clockwise hole into a counter-clockwise outline through a bridge
+/// from the hole's rightmost vertex to a vertex of the outline it can see:
+/// https://www.geometrictools.com/Documentation/TriangulationByEarClipping.pdf
+fn bridge_hole(outer: &mut Vec<Vec2>, hole: &[Vec2]) {
+    let (mi, m) = match hole
+        .iter()
+        .copied()
+        .enumerate()
+        .max_by(|a, b| a.1.x.total_cmp(&b.1.x).then(b.1.y.total_cmp(&a.1.y)))
+    {
+        Some(found) => found,
+        None => return,
+    };
+    // Closest intersection of a ray towards +x with the outline
+    let n = outer.len();
+    let mut best: Option<(f32, usize)> = None;
+    for i in 0..n {
+        let a = outer[i];
+        let b = outer[(i + 1) % n];
+        // Outline is counter-clockwise, so edges visible from inside go up
+        if a.y <= m.y && b.y >= m.y && a.y != b.y {
+            let x = a.x + (m.y - a.y) / (b.y - a.y) * (b.x - a.x);
+            if x >= m.x && best.is_none_or(|(bx, _)| x < bx) {
+                best = Some((x, i));
+            }
+        }
+    }
+    let (ix, ei) = match best {
+        Some(found) => found,
+        None => return,
+    };
+    let i_point = Vec2::new(ix, m.y);
+    let a = outer[ei];
+    let b = outer[(ei + 1) % n];
+    let mut pi = if a.x > b.x { ei } else { (ei + 1) % n };
+    if outer[pi] != i_point {
+        // A reflex vertex inside the triangle may block the view of P, in
+        // which case the one closest in angle to the ray is visible.
+        let p = outer[pi];
+        let (t0, t1, t2) = if (i_point - m).perp_dot(p - m) >= 0.0 {
+            (m, i_point, p)
+        } else {
+            (m, p, i_point)
+        };
+        let mut best_angle = f32::MAX;
+        let mut best_dist = f32::MAX;
+        for j in 0..n {
+            let v = outer[j];
+            let prev = outer[(j + n - 1) % n];
+            let next = outer[(j + 1) % n];
+            if j == pi || is_convex(prev, v, next) || !triangle_contains(t0, t1, t2, v) {
+                continue;
+            }
+            let d = v - m;
+            let angle = (d.y / d.length()).abs();
+            let dist = d.length_squared();
+            if angle < best_angle || (angle == best_angle && dist < best_dist) {
+                best_angle = angle;
+                best_dist = dist;
+                pi = j;
+            }
+        }
+    }
+    let mut merged = Vec::with_capacity(n + hole.len() + 2);
+    merged.extend_from_slice(&outer[..=pi]);
+    for k in 0..=hole.len() {
+        merged.push(hole[(mi + k) % hole.len()]);
+    }
+    merged.extend_from_slice(&outer[pi..]);
+    *outer = merged;
+}
+
+/// Ear-clipping triangulation of a simple polygon with optional holes. Any
+/// winding is accepted. Triangles are counter-clockwise and can be passed
+/// straight to TriWriter::draw_tri.
+pub fn triangulate(outer: &[Vec2], holes: &[&[Vec2]]) -> Vec<[Vec2; 3]> {
+    let mut polygon = with_winding(outer, Winding::Ccw);
+    let mut holes: Vec<Vec<Vec2>> = holes
+        .iter()
+        .filter(|hole| hole.len() >= 3)
+        .map(|hole| with_winding(hole, Winding::Cw))
+        .collect();
+    holes.sort_by(|a, b| {
+        let max_x = |h: &[Vec2]| h.iter().fold(f32::MIN, |m, p| m.max(p.x));
+        max_x(b).total_cmp(&max_x(a))
+    });
+    for hole in holes.iter() {
+        bridge_hole(&mut polygon, hole);
+    }
+    triangulate_simple(polygon)
+}
+
+fn triangulate_simple(polygon: Vec<Vec2>) -> Vec<[Vec2; 3]> {
+    let mut tris = Vec::new();
+    let mut remaining: Vec<usize> = (0..polygon.len()).collect();
+    let mut i = 0;
+    let mut misses = 0;
+    while remaining.len() > 3 {
+        let n = remaining.len();
+        let a = polygon[remaining[(i + n - 1) % n]];
+        let b = polygon[remaining[i % n]];
+        let c = polygon[remaining[(i + 1) % n]];
+        let cross = (b - a).perp_dot(c - b);
+        let is_ear = cross > 0.0
+            && !remaining.iter().any(|&k| {
+                let p = polygon[k];
+                p != a && p != b && p != c && triangle_contains(a, b, c, p)
+            });
+        // Collinear vertices are dropped without a triangle, and if no ear
+        // can be found the input is not simple so the next vertex is forced.
+        if is_ear || cross == 0.0 || misses > n {
+            if cross != 0.0 {
+                tris.push([a, b, c]);
+            }
+            remaining.remove(i % n);
+            misses = 0;
+            i %= remaining.len();
+        } else {
+            i = (i + 1) % n;
+            misses += 1;
+        }
+    }
+    if remaining.len() == 3 {
+        let [a, b, c] = [0, 1, 2].map(|k| polygon[remaining[k]]);
+        if (b - a).perp_dot(c - b) > 0.0 {
+            tris.push([a, b, c]);
+        }
+    }
+    tris
+}
+
+/// Ramer-Douglas-Peucker simplification of an open polyline, keeping the
+/// end points.
+pub fn simplify(points: &[Vec2], epsilon: f32) -> Vec<Vec2> {
+    if points.len() < 3 {
+        return points.to_vec();
+    }
+    let mut keep = vec![false; points.len()];
+    keep[0] = true;
+    keep[points.len() - 1] = true;
+    let mut stack = vec![(0, points.len() - 1)];
+    while let Some((first, last)) = stack.pop() {
+        let a = points[first];
+        let b = points[last];
+        let mut max_dist = 0.0;
+        let mut max_index = first;
+        for (i, p) in points.iter().enumerate().take(last).skip(first + 1) {
+            let dist = distance_to_segment(*p, a, b);
+            if dist > max_dist {
+                max_dist = dist;
+                max_index = i;
+            }
+        }
+        if max_dist > epsilon {
+            keep[max_index] = true;
+            stack.push((first, max_index));
+            stack.push((max_index, last));
+        }
+    }
+    points
+        .iter()
+        .zip(keep)
+        .filter_map(|(p, k)| k.then_some(*p))
+        .collect()
+}
+
+/// Simplifies a closed polygon, splitting it at the vertex furthest from
+/// the first so that corners are not lost at the seam.
+pub fn simplify_closed(polygon: &[Vec2], epsilon: f32) -> Vec<Vec2> {
+    if polygon.len() < 4 {
+        return polygon.to_vec();
+    }
+    let far = (1..polygon.len())
+        .max_by(|a, b| {
+            let da = polygon[*a].distance_squared(polygon[0]);
+            let db = polygon[*b].distance_squared(polygon[0]);
+            da.total_cmp(&db)
+        })
+        .unwrap_or(1);
+    let first: Vec<Vec2> = polygon[..=far].to_vec();
+    let mut second: Vec<Vec2> = polygon[far..].to_vec();
+    second.push(polygon[0]);
+    let mut result = simplify(&first, epsilon);
+    result.pop();
+    result.extend(simplify(&second, epsilon));
+    result.pop();
+    result
+}
+
+fn distance_to_segment(p: Vec2, a: Vec2, b: Vec2) -> f32 {
+    let ab = b - a;
+    let len_sqr = ab.length_squared();
+    let t = if len_sqr > 0.0 {
+        ((p - a).dot(ab) / len_sqr).clamp(0.0, 1.0)
+    } else {
+        0.0
+    };
+    p.distance(a + ab * t)
+}
+
+/// Moves every edge outward by distance, or inward when negative. Corners
+/// sharper than the miter limit (as a multiple of distance) are bevelled.
+/// Large insets can make the result self-intersect.
+pub fn offset(polygon: &[Vec2], distance: f32, miter_limit: f32) -> Vec<Vec2> {
+    let n = polygon.len();
+    if n < 3 || distance == 0.0 {
+        return polygon.to_vec();
+    }
+    // Outward normals point right of counter-clockwise edges
+    let sign = if signed_area(polygon) >= 0.0 { 1.0 } else { -1.0 };
+    let normal = |a: Vec2, b: Vec2| {
+        let d = (b - a).normalize_or_zero();
+        Vec2::new(d.y, -d.x) * sign
+    };
+    let mut result = Vec::with_capacity(n);
+    for i in 0..n {
+        let prev = polygon[(i + n - 1) % n];
+        let p = polygon[i];
+        let next = polygon[(i + 1) % n];
+        let n0 = normal(prev, p);
+        let n1 = normal(p, next);
+        let bisector = (n0 + n1).normalize_or_zero();
+        let cos_half = bisector.dot(n0);
+        if cos_half > 1.0 / miter_limit.max(1.0) {
+            result.push(p + bisector * (distance / cos_half));
+        } else {
+            result.push(p + n0 * distance);
+            result.push(p + n1 * distance);
+        }
+    }
+    result
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum BoolOp {
+    Union,
+    Intersection,
+    /// Subject minus clip
+    Difference,
+}
+
+type PointKey = (u32, u32);
+type Edge = (Vec2, Vec2);
+
+fn key(p: Vec2) -> PointKey {
+    (p.x.to_bits(), p.y.to_bits())
+}
+
+/// Splits each edge of both polygons where it meets the other polygon. Shared
+/// points are computed once so the resulting edges join exactly.
+fn split_edges(a: &[Vec2], b: &[Vec2]) -> (Vec<Edge>, Vec<Edge>) {
+    const EPS: f32 = 1e-6;
+    let mut splits_a: Vec<Vec<(f32, Vec2)>> = vec![Vec::new(); a.len()];
+    let mut splits_b: Vec<Vec<(f32, Vec2)>> = vec![Vec::new(); b.len()];
+    for i in 0..a.len() {
+        let p0 = a[i];
+        let p1 = a[(i + 1) % a.len()];
+        let r = p1 - p0;
+        for j in 0..b.len() {
+            let q0 = b[j];
+            let q1 = b[(j + 1) % b.len()];
+            let s = q1 - q0;
+            let denom = r.perp_dot(s);
+            let qp = q0 - p0;
+            if denom.abs() > EPS * r.length() * s.length() {
+                let t = qp.perp_dot(s) / denom;
+                let u = qp.perp_dot(r) / denom;
+                if !(-EPS..=1.0 + EPS).contains(&t) || !(-EPS..=1.0 + EPS).contains(&u) {
+                    continue;
+                }
+                // Snap to existing vertices so their keys match
+                let point = if t <= EPS {
+                    p0
+                } else if t >= 1.0 - EPS {
+                    p1
+                } else if u <= EPS {
+                    q0
+                } else if u >= 1.0 - EPS {
+                    q1
+                } else {
+                    p0 + r * t
+                };
+                splits_a[i].push((t, point));
+                splits_b[j].push((u, point));
+            } else if qp.perp_dot(r).abs() <= EPS * r.length().max(1.0) * qp.length().max(1.0) {
+                // Collinear, so split each edge at the other's end points
+                let r_len = r.length_squared();
+                let s_len = s.length_squared();
+                if r_len == 0.0 || s_len == 0.0 {
+                    continue;
+                }
+                for q in [q0, q1] {
+                    let t = (q - p0).dot(r) / r_len;
+                    if t > EPS && t < 1.0 - EPS {
+                        splits_a[i].push((t, q));
+                    }
+                }
+                for p in [p0, p1] {
+                    let u = (p - q0).dot(s) / s_len;
+                    if u > EPS && u < 1.0 - EPS {
+                        splits_b[j].push((u, p));
+                    }
+                }
+            }
+        }
+    }
+    let build = |poly: &[Vec2], splits: &mut [Vec<(f32, Vec2)>]| {
+        let mut edges = Vec::new();
+        for i in 0..poly.len() {
+            let p0 = poly[i];
+            let p1 = poly[(i + 1) % poly.len()];
+            let cuts = &mut splits[i];
+            cuts.sort_by(|x, y| x.0.total_cmp(&y.0));
+            let mut prev = p0;
+            for (_, point) in cuts.iter().chain(std::iter::once(&(1.0, p1))) {
+                if *point != prev {
+                    edges.push((prev, *point));
+                    prev = *point;
+                }
+            }
+        }
+        edges
+    };
+    (build(a, &mut splits_a), build(b, &mut splits_b))
+}
+
+/// Joins directed edges into rings, taking the sharpest right turn where
+/// several edges leave the same point.
+fn link_rings(edges: &[Edge]) -> Vec<Vec<Vec2>> {
+    let mut outgoing: HashMap<PointKey, Vec<usize>> = HashMap::new();
+    for (i, e) in edges.iter().enumerate() {
+        outgoing.entry(key(e.0)).or_default().push(i);
+    }
+    let mut used = vec![false; edges.len()];
+    let mut rings = Vec::new();
+    for start in 0..edges.len() {
+        if used[start] {
+            continue;
+        }
+        let mut ring = vec![edges[start].0];
+        let mut current = start;
+        let closed = loop {
+            used[current] = true;
+            let (from, to) = edges[current];
+            if key(to) == key(ring[0]) {
+                break true;
+            }
+            ring.push(to);
+            let dir = to - from;
+            let next = outgoing.get(&key(to)).and_then(|candidates| {
+                candidates.iter().copied().filter(|c| !used[*c]).min_by(|x, y| {
+                    let turn = |c: usize| {
+                        let out = edges[c].1 - edges[c].0;
+                        dir.perp_dot(out).atan2(dir.dot(out))
+                    };
+                    turn(*x).total_cmp(&turn(*y))
+                })
+            });
+            match next {
+                Some(next) => current = next,
+                None => break false,
+            }
+        };
+        if closed {
+            let ring = remove_collinear(ring);
+            if ring.len() >= 3 {
+                rings.push(ring);
+            }
+        }
+    }
+    rings
+}
+
+fn remove_collinear(ring: Vec<Vec2>) -> Vec<Vec2> {
+    let mut ring = ring;
+    let mut i = 0;
+    while ring.len() >= 3 && i < ring.len() {
+        let n = ring.len();
+        let a = ring[(i + n - 1) % n];
+        let b = ring[i];
+        let c = ring[(i + 1) % n];
+        let ab = b - a;
+        let bc = c - b;
+        if ab.perp_dot(bc).abs() <= 1e-6 * ab.length() * bc.length() && ab.dot(bc) >= 0.0 {
+            ring.remove(i);
+            i = i.saturating_sub(1);
+        } else {
+            i += 1;
+        }
+    }
+    ring
+}
+
+/// Boolean operation on two simple polygons of any winding. Returns rings
+/// where counter-clockwise rings are outlines and clockwise rings are holes
+/// inside them, ready for triangulate.
+pub fn boolean(subject: &[Vec2], clip: &[Vec2], op: BoolOp) -> Vec<Vec<Vec2>> {
+    if subject.len() < 3 || clip.len() < 3 {
+        return match op {
+            BoolOp::Union if subject.len() >= 3 => vec![with_winding(subject, Winding::Ccw)],
+            BoolOp::Union if clip.len() >= 3 => vec![with_winding(clip, Winding::Ccw)],
+            BoolOp::Difference if subject.len() >= 3 => vec![with_winding(subject, Winding::Ccw)],
+            _ => Vec::new(),
+        };
+    }
+    let a = with_winding(subject, Winding::Ccw);
+    let b = with_winding(clip, Winding::Ccw);
+    let (edges_a, edges_b) = split_edges(&a, &b);
+    let keys_a: HashMap<(PointKey, PointKey), ()> =
+        edges_a.iter().map(|e| ((key(e.0), key(e.1)), ())).collect();
+    let keys_b: HashMap<(PointKey, PointKey), ()> =
+        edges_b.iter().map(|e| ((key(e.0), key(e.1)), ())).collect();
+    let mut kept = Vec::new();
+    for e in edges_a.iter() {
+        let same = keys_b.contains_key(&(key(e.0), key(e.1)));
+        let opposite = keys_b.contains_key(&(key(e.1), key(e.0)));
+        let inside = contains_point(&b, (e.0 + e.1) * 0.5);
+        let keep = match op {
+            BoolOp::Union | BoolOp::Intersection if same => true,
+            BoolOp::Union | BoolOp::Intersection if opposite => false,
+            BoolOp::Difference if same => false,
+            BoolOp::Difference if opposite => true,
+            BoolOp::Union | BoolOp::Difference => !inside,
+            BoolOp::Intersection => inside,
+        };
+        if keep {
+            kept.push(*e);
+        }
+    }
+    for e in edges_b.iter() {
+        // Shared edges were already decided by the subject
+        let shared = keys_a.contains_key(&(key(e.0), key(e.1)))
+            || keys_a.contains_key(&(key(e.1), key(e.0)));
+        if shared {
+            continue;
+        }
+        let inside = contains_point(&a, (e.0 + e.1) * 0.5);
+        match op {
+            BoolOp::Union if !inside => kept.push(*e),
+            BoolOp::Intersection if inside => kept.push(*e),
+            BoolOp::Difference if inside => kept.push((e.1, e.0)),
+            _ => {}
+        }
+    }
+    link_rings(&kept)
+}
+
+pub fn union(a: &[Vec2], b: &[Vec2]) -> Vec<Vec<Vec2>> {
+    boolean(a, b, BoolOp::Union)
+}
+
+pub fn intersection(a: &[Vec2], b: &[Vec2]) -> Vec<Vec<Vec2>> {
+    boolean(a, b, BoolOp::Intersection)
+}
+
+pub fn difference(a: &[Vec2], b: &[Vec2]) -> Vec<Vec<Vec2>> {
+    boolean(a, b, BoolOp::Difference)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn square(min: Vec2, size: f32) -> Vec<Vec2> {
+        vec![
+            min,
+            min + Vec2::new(size, 0.0),
+            min + Vec2::splat(size),
+            min + Vec2::new(0.0, size),
+        ]
+    }
+
+    fn tri_area(tris: &[[Vec2; 3]]) -> f32 {
+        tris.iter().map(|t| signed_area(t)).sum()
+    }
+
+    fn total_area(rings: &[Vec<Vec2>]) -> f32 {
+        rings.iter().map(|r| signed_area(r)).sum()
+    }
+
+    #[test]
+    fn area_and_winding() {
+        let mut s = square(Vec2::ZERO, 2.0);
+        assert_eq!(signed_area(&s), 4.0);
+        assert_eq!(winding(&s), Some(Winding::Ccw));
+        s.reverse();
+        assert_eq!(signed_area(&s), -4.0);
+        assert_eq!(winding(&s), Some(Winding::Cw));
+        assert_eq!(winding(&[Vec2::ZERO, Vec2::X, Vec2::X * 2.0]), None);
+        assert_eq!(signed_area(&with_winding(&s, Winding::Ccw)), 4.0);
+        assert_eq!(centroid(&s), Some(Vec2::ONE));
+        let l = [
+            Vec2::ZERO,
+            Vec2::new(4.0, 0.0),
+            Vec2::new(4.0, 1.0),
+            Vec2::new(1.0, 1.0),
+            Vec2::new(1.0, 4.0),
+            Vec2::new(0.0, 4.0),
+        ];
+        let c = centroid(&l).unwrap();
+        assert!((c - Vec2::splat(9.5 / 7.0)).length() < 1e-5, "{c}");
+        assert_eq!(centroid(&[Vec2::ZERO, Vec2::X]), None);
+    }
+
+    #[test]
+    fn point_in_polygon() {
+        let s = square(Vec2::ZERO, 2.0);
+        assert!(contains_point(&s, Vec2::ONE));
+        assert!(!contains_point(&s, Vec2::new(3.0, 1.0)));
+        assert_eq!(winding_number(&s, Vec2::ONE), 1);
+        let mut r = s.clone();
+        r.reverse();
+        assert_eq!(winding_number(&r, Vec2::ONE), -1);
+        assert_eq!(winding_number(&s, Vec2::new(-1.0, 1.0)), 0);
+    }
+
+    #[test]
+    fn hull() {
+        let points = [
+            Vec2::ZERO,
+            Vec2::new(2.0, 0.0),
+            Vec2::new(1.0, 0.0),
+            Vec2::new(1.0, 1.0),
+            Vec2::new(2.0, 2.0),
+            Vec2::new(0.0, 2.0),
+            Vec2::new(0.5, 1.5),
+        ];
+        let hull = convex_hull(&points);
+        assert_eq!(
+            hull,
+            vec![Vec2::ZERO, Vec2::new(2.0, 0.0), Vec2::new(2.0, 2.0), Vec2::new(0.0, 2.0)]
+        );
+        assert_eq!(convex_hull(&[Vec2::ONE, Vec2::ONE]), vec![Vec2::ONE]);
+    }
+
+    #[test]
+    fn triangulate_concave() {
+        // L shape, clockwise input
+        let mut l = vec![
+            Vec2::ZERO,
+            Vec2::new(3.0, 0.0),
+            Vec2::new(3.0, 1.0),
+            Vec2::new(1.0, 1.0),
+            Vec2::new(1.0, 3.0),
+            Vec2::new(0.0, 3.0),
+        ];
+        l.reverse();
+        let tris = triangulate(&l, &[]);
+        assert_eq!(tris.len(), 4);
+        assert!(tris.iter().all(|t| signed_area(t) > 0.0));
+        assert!((tri_area(&tris) - 5.0).abs() < 1e-5);
+    }
+
+    #[test]
+    fn triangulate_with_holes() {
+        let outer = square(Vec2::ZERO, 10.0);
+        let hole_a = square(Vec2::new(1.0, 1.0), 3.0);
+        let hole_b = square(Vec2::new(6.0, 5.0), 2.0);
+        let tris = triangulate(&outer, &[&hole_a, &hole_b]);
+        assert!((tri_area(&tris) - (100.0 - 9.0 - 4.0)).abs() < 1e-3);
+        assert!(tris.iter().all(|t| signed_area(t) > 0.0));
+        for t in tris.iter() {
+            let c = (t[0] + t[1] + t[2]) / 3.0;
+            assert!(!contains_point(&hole_a, c) && !contains_point(&hole_b, c));
+        }
+    }
+
+    #[test]
+    fn boolean_overlapping() {
+        let a = square(Vec2::ZERO, 2.0);
+        let b = square(Vec2::ONE, 2.0);
+        let u = union(&a, &b);
+        assert_eq!(u.len(), 1);
+        assert_eq!(u[0].len(), 8);
+        assert!((total_area(&u) - 7.0).abs() < 1e-5);
+        let i = intersection(&a, &b);
+        assert_eq!(i.len(), 1);
+        assert!((total_area(&i) - 1.0).abs() < 1e-5);
+        let d = difference(&a, &b);
+        assert_eq!(d.len(), 1);
+        assert_eq!(d[0].len(), 6);
+        assert!((total_area(&d) - 3.0).abs() < 1e-5);
+    }
+
+    #[test]
+    fn boolean_contained_and_disjoint() {
+        let outer = square(Vec2::ZERO, 4.0);
+        let inner = square(Vec2::ONE, 2.0);
+        let far = square(Vec2::splat(10.0), 1.0);
+        let d = difference(&outer, &inner);
+        assert_eq!(d.len(), 2);
+        assert!((total_area(&d) - 12.0).abs() < 1e-5);
+        assert!(d.iter().any(|r| winding(r) == Some(Winding::Cw)));
+        assert!((total_area(&union(&outer, &inner)) - 16.0).abs() < 1e-5);
+        assert!((total_area(&intersection(&outer, &inner)) - 4.0).abs() < 1e-5);
+        assert_eq!(union(&outer, &far).len(), 2);
+        assert!(intersection(&outer, &far).is_empty());
+        assert!((total_area(&difference(&inner, &outer))).abs() < 1e-5);
+    }
+
+    #[test]
+    fn boolean_shared_edges() {
+        let a = square(Vec2::ZERO, 2.0);
+        let b = square(Vec2::new(2.0, 0.0), 2.0);
+        let u = union(&a, &b);
+        assert_eq!(u.len(), 1);
+        assert_eq!(u[0].len(), 4);
+        assert!((total_area(&u) - 8.0).abs() < 1e-5);
+        assert!(intersection(&a, &b).is_empty());
+        let d = difference(&a, &b);
+        assert!((total_area(&d) - 4.0).abs() < 1e-5);
+        // Half overlapping along one side
+        let c = square(Vec2::new(1.0, 0.0), 2.0);
+        assert!((total_area(&union(&a, &c)) - 6.0).abs() < 1e-5);
+        assert!((total_area(&intersection(&a, &c)) - 2.0).abs() < 1e-5);
+        assert!((total_area(&difference(&a, &c)) - 2.0).abs() < 1e-5);
+    }
+
+    #[test]
+    fn offset_square() {
+        let s = square(Vec2::ZERO, 2.0);
+        let out = offset(&s, 1.0, 2.0);
+        assert_eq!(out.len(), 4);
+        assert!((signed_area(&out) - 16.0).abs() < 1e-4);
+        let inset = offset(&s, -0.5, 2.0);
+        assert!((signed_area(&inset) - 1.0).abs() < 1e-4);
+        // Bevel when the miter would be too long
+        let bevel = offset(&s, 1.0, 1.2);
+        assert_eq!(bevel.len(), 8);
+        let mut r = s.clone();
+        r.reverse();
+        assert!((signed_area(&offset(&r, 1.0, 2.0)) + 16.0).abs() < 1e-4);
+    }
+
+    #[test]
+    fn rdp() {
+        let line: Vec<Vec2> = (0..=10).map(|i| Vec2::new(i as f32, (i % 2) as f32 * 0.01)).collect();
+        assert_eq!(simplify(&line, 0.1), vec![line[0], line[10]]);
+        assert_eq!(simplify(&line, 0.001).len(), 11);
+        let mut s = Vec::new();
+        for edge in square(Vec2::ZERO, 4.0).windows(2).chain([[Vec2::new(0.0, 4.0), Vec2::ZERO].as_slice()]) {
+            for k in 0..4 {
+                s.push(edge[0].lerp(edge[1], k as f32 / 4.0));
+            }
+        }
+        let simple = simplify_closed(&s, 0.01);
+        assert_eq!(simple.len(), 4);
+        assert!((signed_area(&simple) - 16.0).abs() < 1e-5);
+    }
+}
@@ -482,6 +482,60 @@ impl IRange2 {
             max: self.max + margin,
         }
     }
+
+    /// Number of cells, zero when empty
+    pub fn area(&self) -> i64 {
+        if self.is_empty() {
+            0
+        } else {
+            self.size().x as i64 * self.size().y as i64
+        }
+    }
+
+    pub fn intersects(&self, rhs: &Self) -> bool {
+        !(*self & *rhs).is_empty()
+    }
+
+    /// Overlap of both ranges, None if they do not share a cell
+    pub fn intersection(&self, rhs: &Self) -> Option<Self> {
+        let r = *self & *rhs;
+        (!r.is_empty()).then_some(r)
+    }
+
+    /// Smallest range covering both, ignoring empty ranges
+    pub fn union_bounds(&self, rhs: &Self) -> Self {
+        if self.is_empty() {
+            *rhs
+        } else if rhs.is_empty() {
+            *self
+        } else {
+            *self | *rhs
+        }
+    }
+
+    /// True if every cell of rhs is inside, empty ranges are always contained
+    pub fn contains_range(&self, rhs: &Self) -> bool {
+        rhs.is_empty()
+            || (rhs.min.x >= self.min.x
+                && rhs.min.y >= self.min.y
+                && rhs.max.x <= self.max.x
+                && rhs.max.y <= self.max.y)
+    }
+
+    /// Cells not covered by rhs as up to four disjoint ranges: full width
+    /// bands below and above the overlap, then the pieces left and right of it.
+    pub fn subtract(&self, rhs: &Self) -> impl Iterator<Item = Self> {
+        let pieces = match self.intersection(rhs) {
+            None => [*self, Self::ZERO, Self::ZERO, Self::ZERO],
+            Some(i) => [
+                Self::from_xy(self.min.x, self.min.y, self.max.x, i.min.y),
+                Self::from_xy(self.min.x, i.max.y, self.max.x, self.max.y),
+                Self::from_xy(self.min.x, i.min.y, i.min.x, i.max.y),
+                Self::from_xy(i.max.x, i.min.y, self.max.x, i.max.y),
+            ],
+        };
+        pieces.into_iter().filter(|r| !r.is_empty())
+    }
 }
 
 impl ops::Shl<u32> for IRange2 {
@@ -1310,6 +1364,47 @@ mod tests {
         assert_eq!(results.len(), 0);
     }
 
+    #[test]
+    fn range2_set_ops() {
+        let a = IRange2::from_xy(0, 0, 4, 4);
+        let b = IRange2::from_xy(2, 2, 6, 6);
+        assert_eq!(a.intersection(&b), Some(IRange2::from_xy(2, 2, 4, 4)));
+        assert_eq!(a.intersection(&IRange2::from_xy(4, 0, 5, 4)), None);
+        assert!(!a.intersects(&IRange2::from_xy(4, 0, 5, 4)));
+        assert_eq!(a.union_bounds(&b), IRange2::from_xy(0, 0, 6, 6));
+        assert_eq!(IRange2::ZERO.union_bounds(&b), b);
+        assert!(a.contains_range(&IRange2::from_xy(1, 1, 4, 2)));
+        assert!(!a.contains_range(&b));
+        assert_eq!(a.area(), 16);
+        assert_eq!(IRange2::from_xy(3, 0, 1, 5).area(), 0);
+
+        let hole = IRange2::from_xy(1, 1, 3, 3);
+        let pieces: Vec<IRange2> = a.subtract(&hole).collect();
+        assert_eq!(pieces.len(), 4);
+        assert_eq!(a.subtract(&a).count(), 0);
+        assert_eq!(a.subtract(&IRange2::from_xy(9, 9, 10, 10)).collect::<Vec<_>>(), vec![a]);
+    }
+
+    #[test]
+    fn range2_subtract_conserves_area() {
+        let mut rng = crate::pcg::PcgRng::new(7, 3);
+        let rect = |rng: &mut crate::pcg::PcgRng| {
+            let min = IVec2::new(rng.next_i32_in(-8..8), rng.next_i32_in(-8..8));
+            IRange2::sized(min, IVec2::new(rng.next_i32_in(0..10), rng.next_i32_in(0..10)))
+        };
+        for _ in 0..500 {
+            let a = rect(&mut rng);
+            let b = rect(&mut rng);
+            let pieces: Vec<IRange2> = a.subtract(&b).collect();
+            let overlap = a.intersection(&b).map_or(0, |r| r.area());
+            assert_eq!(pieces.iter().map(|r| r.area()).sum::<i64>() + overlap, a.area());
+            for (i, p) in pieces.iter().enumerate() {
+                assert!(a.contains_range(p) && !p.intersects(&b));
+                assert!(pieces[i + 1..].iter().all(|q| !p.intersects(q)));
+            }
+        }
+    }
+
     #[test]
     fn iter_range3() {
         let r = IRange3::sized(IVec3::ZERO, IVec3::ONE * 2);
@@ -1,4 +1,40 @@
-use glam::Vec3;
+use glam::{Vec2, Vec3};
+
+use crate::{
+    polygon::contains_point,
+    range::{Range2, Range3},
+};
+
+/// Distance along ray and surface normal at the hit point. When the ray
+/// origin starts inside a closed shape, t is zero and normal faces the ray.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct RayHit3 {
+    pub t: f32,
+    pub normal: Vec3,
+}
+
+/// Triangle hit with barycentric weights for each of the three vertices
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct TriangleHit3 {
+    pub t: f32,
+    pub normal: Vec3,
+    pub bary: Vec3,
+}
+
+impl TriangleHit3 {
+    pub fn hit(&self) -> RayHit3 {
+        RayHit3 {
+            t: self.t,
+            normal: self.normal,
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct RayHit2 {
+    pub t: f32,
+    pub normal: Vec2,
+}
 
 #[derive(Debug, Clone, Copy)]
 pub struct Ray3 {
@@ -11,6 +47,10 @@ impl Ray3 {
         Self { origin, dir }
     }
 
+    pub fn at(&self, t: f32) -> Vec3 {
+        self.origin + self.dir * t
+    }
+
     /// Expect ray to have unit length
     pub fn intersect_sphere(&self, center: Vec3, radius: f32) -> Option<f32> {
         let m = self.origin - center;
@@ -29,6 +69,162 @@ impl Ray3 {
         }
     }
 
+    /// Expect ray to have unit length
+    pub fn hit_sphere(&self, center: Vec3, radius: f32) -> Option<RayHit3> {
+        let t = self.intersect_sphere(center, radius)?;
+        let normal = if t > 0.0 {
+            (self.at(t) - center).normalize_or_zero()
+        } else {
+            -self.dir
+        };
+        Some(RayHit3 { t, normal })
+    }
+
+    /// Slab test against an axis-aligned box
+    pub fn hit_range3(&self, range: Range3) -> Option<RayHit3> {
+        let mut t0 = f32::NEG_INFINITY;
+        let mut t1 = f32::INFINITY;
+        let mut axis = 0;
+        for i in 0..3 {
+            let o = self.origin[i];
+            let d = self.dir[i];
+            if d.abs() < 1e-9 {
+                if o < range.min[i] || o > range.max[i] {
+                    return None;
+                }
+            } else {
+                let inv_d = 1.0 / d;
+                let ta = (range.min[i] - o) * inv_d;
+                let tb = (range.max[i] - o) * inv_d;
+                let (ta, tb) = if ta < tb { (ta, tb) } else { (tb, ta) };
+                if ta > t0 {
+                    t0 = ta;
+                    axis = i;
+                }
+                t1 = t1.min(tb);
+                if t0 > t1 {
+                    return None;
+                }
+            }
+        }
+        if t1 < 0.0 {
+            None
+        } else if t0 <= 0.0 {
+            Some(RayHit3 {
+                t: 0.0,
+                normal: -self.dir,
+            })
+        } else {
+            let mut normal = Vec3::ZERO;
+            normal[axis] = -self.dir[axis].signum();
+            Some(RayHit3 { t: t0, normal })
+        }
+    }
+
+    /// Plane is given by a point on it and its normal. The returned normal
+    /// faces the ray origin regardless of which side it starts on.
+    pub fn hit_plane(&self, point: Vec3, normal: Vec3) -> Option<RayHit3> {
+        let denom = normal.dot(self.dir);
+        if denom.abs() < 1e-9 {
+            None
+        } else {
+            let t = normal.dot(point - self.origin) / denom;
+            if t < 0.0 {
+                None
+            } else {
+                let normal = if denom > 0.0 { -normal } else { normal };
+                Some(RayHit3 { t, normal })
+            }
+        }
+    }
+
+    /// Double-sided Möller–Trumbore test. Barycentric weights are ordered
+    /// (p0, p1, p2) and the normal faces the ray origin.
+    pub fn hit_triangle(&self, p0: Vec3, p1: Vec3, p2: Vec3) -> Option<TriangleHit3> {
+        let e1 = p1 - p0;
+        let e2 = p2 - p0;
+        let p = self.dir.cross(e2);
+        let det = e1.dot(p);
+        if det.abs() < 1e-9 {
+            return None;
+        }
+        let inv_det = 1.0 / det;
+        let s = self.origin - p0;
+        let u = s.dot(p) * inv_det;
+        if !(0.0..=1.0).contains(&u) {
+            return None;
+        }
+        let q = s.cross(e1);
+        let v = self.dir.dot(q) * inv_det;
+        if v < 0.0 || u + v > 1.0 {
+            return None;
+        }
+        let t = e2.dot(q) * inv_det;
+        if t < 0.0 {
+            return None;
+        }
+        let normal = e1.cross(e2).normalize_or_zero();
+        let normal = if det < 0.0 { -normal } else { normal };
+        Some(TriangleHit3 {
+            t,
+            normal,
+            bary: Vec3::new(1.0 - u - v, u, v),
+        })
+    }
+
+    /// Capsule is the set of points within radius of segment (a, b)
+    pub fn hit_capsule(&self, a: Vec3, b: Vec3, radius: f32) -> Option<RayHit3> {
+        // https://iquilezles.org/articles/intersectors/
+        let ba = b - a;
+        let oa = self.origin - a;
+        let baba = ba.dot(ba);
+        let bard = ba.dot(self.dir);
+        let baoa = ba.dot(oa);
+        let rdoa = self.dir.dot(oa);
+        let oaoa = oa.dot(oa);
+        let h = (baoa / baba.max(1e-9)).clamp(0.0, 1.0);
+        if (oa - ba * h).length_squared() <= radius * radius {
+            return Some(RayHit3 {
+                t: 0.0,
+                normal: -self.dir,
+            });
+        }
+        let qa = baba - bard * bard;
+        let qb = baba * rdoa - baoa * bard;
+        let qc = baba * oaoa - baoa * baoa - radius * radius * baba;
+        let disc = qb * qb - qa * qc;
+        if qa > 1e-9 && disc >= 0.0 {
+            let t = (-qb - disc.sqrt()) / qa;
+            let y = baoa + t * bard;
+            if t >= 0.0 && y > 0.0 && y < baba {
+                let normal = (oa + self.dir * t - ba * (y / baba)) / radius;
+                return Some(RayHit3 { t, normal });
+            }
+        }
+        // Caps
+        match (self.hit_sphere(a, radius), self.hit_sphere(b, radius)) {
+            (Some(ha), Some(hb)) => Some(if ha.t <= hb.t { ha } else { hb }),
+            (ha, hb) => ha.or(hb),
+        }
+    }
+
+    /// Returns index of nearest triangle hit along with hit details, where
+    /// each consecutive triple of indices forms a triangle.
+    pub fn hit_mesh(&self, positions: &[Vec3], indices: &[u32]) -> Option<(usize, TriangleHit3)> {
+        let mut result: Option<(usize, TriangleHit3)> = None;
+        for (i, tri) in indices.chunks_exact(3).enumerate() {
+            let p0 = positions[tri[0] as usize];
+            let p1 = positions[tri[1] as usize];
+            let p2 = positions[tri[2] as usize];
+            if let Some(hit) = self.hit_triangle(p0, p1, p2) {
+                if result.is_none_or(|(_, best)| hit.t < best.t) {
+                    result = Some((i, hit));
+                }
+            }
+        }
+        result
+    }
+
     // Adapted from:
     // http://geomalgorithms.com/a07-_distance.html
     // Copyright 2001 softSurfer, 2012 Dan Sunday
@@ -81,3 +277,249 @@ impl LineSeg3 {
         LineSeg3::new(Ray3::new(origin, dir), length)
     }
 }
+
+#[derive(Debug, Clone, Copy)]
+pub struct Ray2 {
+    pub origin: Vec2,
+    pub dir: Vec2,
+}
+
+impl Ray2 {
+    pub fn new(origin: Vec2, dir: Vec2) -> Self {
+        Self { origin, dir }
+    }
+
+    pub fn at(&self, t: f32) -> Vec2 {
+        self.origin + self.dir * t
+    }
+
+    /// Returned normal faces the ray origin
+    pub fn hit_segment(&self, a: Vec2, b: Vec2) -> Option<RayHit2> {
+        let edge = b - a;
+        let denom = self.dir.perp_dot(edge);
+        if denom.abs() < 1e-9 {
+            return None;
+        }
+        let w = a - self.origin;
+        let t = w.perp_dot(edge) / denom;
+        let s = w.perp_dot(self.dir) / denom;
+        if t < 0.0 || !(0.0..=1.0).contains(&s) {
+            None
+        } else {
+            let normal = edge.perp().normalize_or_zero();
+            let normal = if denom > 0.0 { normal } else { -normal };
+            Some(RayHit2 { t, normal })
+        }
+    }
+
+    /// Expect ray to have unit length
+    pub fn hit_circle(&self, center: Vec2, radius: f32) -> Option<RayHit2> {
+        let m = self.origin - center;
+        let b = m.dot(self.dir);
+        let c = m.dot(m) - radius * radius;
+        if c <= 0.0 {
+            Some(RayHit2 {
+                t: 0.0,
+                normal: -self.dir,
+            })
+        } else if b > 0.0 {
+            None
+        } else {
+            let d = b * b - c;
+            if d < 0.0 {
+                None
+            } else {
+                let t = -b - d.sqrt();
+                let normal = (self.at(t) - center).normalize_or_zero();
+                Some(RayHit2 { t, normal })
+            }
+        }
+    }
+
+    /// Slab test against an axis-aligned rect
+    pub fn hit_range2(&self, range: Range2) -> Option<RayHit2> {
+        let mut t0 = f32::NEG_INFINITY;
+        let mut t1 = f32::INFINITY;
+        let mut axis = 0;
+        for i in 0..2 {
+            let o = self.origin[i];
+            let d = self.dir[i];
+            if d.abs() < 1e-9 {
+                if o < range.min[i] || o > range.max[i] {
+                    return None;
+                }
+            } else {
+                let inv_d = 1.0 / d;
+                let ta = (range.min[i] - o) * inv_d;
+                let tb = (range.max[i] - o) * inv_d;
+                let (ta, tb) = if ta < tb { (ta, tb) } else { (tb, ta) };
+                if ta > t0 {
+                    t0 = ta;
+                    axis = i;
+                }
+                t1 = t1.min(tb);
+                if t0 > t1 {
+                    return None;
+                }
+            }
+        }
+        if t1 < 0.0 {
+            None
+        } else if t0 <= 0.0 {
+            Some(RayHit2 {
+                t: 0.0,
+                normal: -self.dir,
+            })
+        } else {
+            let mut normal = Vec2::ZERO;
+            normal[axis] = -self.dir[axis].signum();
+            Some(RayHit2 { t: t0, normal })
+        }
+    }
+
+    /// Polygon is a closed loop of vertices in either winding order
+    pub fn hit_polygon(&self, verts: &[Vec2]) -> Option<RayHit2> {
+        if contains_point(verts, self.origin) {
+            return Some(RayHit2 {
+                t: 0.0,
+                normal: -self.dir,
+            });
+        }
+        let mut result: Option<RayHit2> = None;
+        for i in 0..verts.len() {
+            let a = verts[i];
+            let b = verts[(i + 1) % verts.len()];
+            if let Some(hit) = self.hit_segment(a, b) {
+                if result.is_none_or(|best| hit.t < best.t) {
+                    result = Some(hit);
+                }
+            }
+        }
+        result
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn assert_near(a: f32, b: f32) {
+        assert!((a - b).abs() < 1e-5, "{} != {}", a, b);
+    }
+
+    #[test]
+    fn hit_range3_from_outside() {
+        let ray = Ray3::new(Vec3::new(-5.0, 0.5, 0.5), Vec3::X);
+        let hit = ray.hit_range3(Range3::ZERO_TO_ONE).unwrap();
+        assert_near(hit.t, 5.0);
+        assert_eq!(hit.normal, -Vec3::X);
+        let ray = Ray3::new(Vec3::new(-5.0, 2.0, 0.5), Vec3::X);
+        assert!(ray.hit_range3(Range3::ZERO_TO_ONE).is_none());
+    }
+
+    #[test]
+    fn hit_range3_from_inside() {
+        let ray = Ray3::new(Vec3::splat(0.5), Vec3::Y);
+        let hit = ray.hit_range3(Range3::ZERO_TO_ONE).unwrap();
+        assert_eq!(hit.t, 0.0);
+    }
+
+    #[test]
+    fn hit_triangle_bary() {
+        let ray = Ray3::new(Vec3::new(0.25, 0.25, 1.0), -Vec3::Z);
+        let hit = ray.hit_triangle(Vec3::ZERO, Vec3::X, Vec3::Y).unwrap();
+        assert_near(hit.t, 1.0);
+        assert_eq!(hit.normal, Vec3::Z);
+        assert_near(hit.bary.x, 0.5);
+        assert_near(hit.bary.y, 0.25);
+        assert_near(hit.bary.z, 0.25);
+        let ray = Ray3::new(Vec3::new(1.0, 1.0, 1.0), -Vec3::Z);
+        assert!(ray.hit_triangle(Vec3::ZERO, Vec3::X, Vec3::Y).is_none());
+    }
+
+    #[test]
+    fn hit_plane_either_side() {
+        let ray = Ray3::new(Vec3::new(0.0, -3.0, 0.0), Vec3::Y);
+        let hit = ray.hit_plane(Vec3::ZERO, Vec3::Y).unwrap();
+        assert_near(hit.t, 3.0);
+        assert_eq!(hit.normal, -Vec3::Y);
+        let ray = Ray3::new(Vec3::new(0.0, -3.0, 0.0), -Vec3::Y);
+        assert!(ray.hit_plane(Vec3::ZERO, Vec3::Y).is_none());
+    }
+
+    #[test]
+    fn hit_capsule_body_and_cap() {
+        let a = Vec3::new(0.0, -1.0, 0.0);
+        let b = Vec3::new(0.0, 1.0, 0.0);
+        let ray = Ray3::new(Vec3::new(-5.0, 0.0, 0.0), Vec3::X);
+        let hit = ray.hit_capsule(a, b, 0.5).unwrap();
+        assert_near(hit.t, 4.5);
+        assert_near(hit.normal.x, -1.0);
+        let ray = Ray3::new(Vec3::new(0.0, 5.0, 0.0), -Vec3::Y);
+        let hit = ray.hit_capsule(a, b, 0.5).unwrap();
+        assert_near(hit.t, 3.5);
+        assert_near(hit.normal.y, 1.0);
+    }
+
+    #[test]
+    fn hit_mesh_nearest() {
+        let positions = [
+            Vec3::new(-1.0, -1.0, 0.0),
+            Vec3::new(1.0, -1.0, 0.0),
+            Vec3::new(0.0, 1.0, 0.0),
+            Vec3::new(-1.0, -1.0, 2.0),
+            Vec3::new(1.0, -1.0, 2.0),
+            Vec3::new(0.0, 1.0, 2.0),
+        ];
+        let ray = Ray3::new(Vec3::new(0.0, 0.0, 5.0), -Vec3::Z);
+        let (index, hit) = ray.hit_mesh(&positions, &[0, 1, 2, 3, 4, 5]).unwrap();
+        assert_eq!(index, 1);
+        assert_near(hit.t, 3.0);
+    }
+
+    #[test]
+    fn hit_segment_and_circle() {
+        let ray = Ray2::new(Vec2::ZERO, Vec2::X);
+        let hit = ray.hit_segment(Vec2::new(2.0, -1.0), Vec2::new(2.0, 1.0)).unwrap();
+        assert_near(hit.t, 2.0);
+        assert_eq!(hit.normal, -Vec2::X);
+        let hit = ray.hit_circle(Vec2::new(3.0, 0.0), 1.0).unwrap();
+        assert_near(hit.t, 2.0);
+        assert_eq!(hit.normal, -Vec2::X);
+        assert!(ray.hit_circle(Vec2::new(-3.0, 0.0), 1.0).is_none());
+    }
+
+    #[test]
+    fn hit_range2_and_polygon() {
+        let ray = Ray2::new(Vec2::new(0.5, -2.0), Vec2::Y);
+        let hit = ray.hit_range2(Range2::ZERO_TO_ONE).unwrap();
+        assert_near(hit.t, 2.0);
+        assert_eq!(hit.normal, -Vec2::Y);
+        let square = [Vec2::ZERO, Vec2::X, Vec2::ONE, Vec2::Y];
+        let hit = ray.hit_polygon(&square).unwrap();
+        assert_near(hit.t, 2.0);
+        assert_eq!(hit.normal, -Vec2::Y);
+        let inside = Ray2::new(Vec2::splat(0.5), Vec2::X);
+        assert_eq!(inside.hit_polygon(&square).unwrap().t, 0.0);
+    }
+
+    #[test]
+    fn hit_polygon_through_vertex() {
+        let square = [Vec2::ZERO, Vec2::new(2.0, 0.0), Vec2::splat(2.0), Vec2::new(0.0, 2.0)];
+        let inside = Ray2::new(Vec2::ONE, Vec2::ONE.normalize());
+        assert_eq!(inside.hit_polygon(&square).unwrap().t, 0.0);
+        let outside = Ray2::new(Vec2::splat(-1.0), Vec2::ONE.normalize());
+        assert_near(outside.hit_polygon(&square).unwrap().t, 2f32.sqrt());
+    }
+
+    #[test]
+    fn hit_polygon_along_edge() {
+        let square = [Vec2::ZERO, Vec2::new(2.0, 0.0), Vec2::splat(2.0), Vec2::new(0.0, 2.0)];
+        let outside = Ray2::new(Vec2::new(-1.0, 0.0), Vec2::X);
+        assert_near(outside.hit_polygon(&square).unwrap().t, 1.0);
+        let inside = Ray2::new(Vec2::new(0.5, 1.0), Vec2::Y);
+        assert_eq!(inside.hit_polygon(&square).unwrap().t, 0.0);
+        let away = Ray2::new(Vec2::new(3.0, 0.0), Vec2::X);
+        assert!(away.hit_polygon(&square).is_none());
+    }
+}
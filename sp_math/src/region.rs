@@ -0,0 +1,233 @@
+use glam::IVec2;
+
+use crate::range::IRange2;
+
+/// Set of cells stored as disjoint rectangles, for dirty rect tracking and
+/// tile invalidation
+#[derive(Debug, Default, Clone, PartialEq, Eq)]
+pub struct Region {
+    rects: Vec<IRange2>,
+}
+
+impl Region {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    pub fn from_rect(rect: IRange2) -> Self {
+        let mut region = Self::new();
+        region.add(rect);
+        region
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.rects.is_empty()
+    }
+
+    pub fn clear(&mut self) {
+        self.rects.clear();
+    }
+
+    /// Disjoint non-empty rectangles covering the region
+    pub fn rects(&self) -> &[IRange2] {
+        &self.rects
+    }
+
+    pub fn iter(&self) -> std::slice::Iter<'_, IRange2> {
+        self.rects.iter()
+    }
+
+    /// Every cell of the region, rectangle by rectangle
+    pub fn cells(&self) -> impl Iterator<Item = IVec2> + '_ {
+        self.rects.iter().flat_map(|r| r.iter())
+    }
+
+    pub fn area(&self) -> i64 {
+        self.rects.iter().map(|r| r.area()).sum()
+    }
+
+    /// Smallest range covering the region, or None when empty
+    pub fn bounds(&self) -> Option<IRange2> {
+        self.rects.iter().copied().reduce(|a, b| a | b)
+    }
+
+    pub fn contains(&self, p: IVec2) -> bool {
+        self.rects.iter().any(|r| r.contains(p))
+    }
+
+    pub fn intersects(&self, rect: &IRange2) -> bool {
+        self.rects.iter().any(|r| r.intersects(rect))
+    }
+
+    /// Adds the cells of rect that are not already covered
+    pub fn add(&mut self, rect: IRange2) {
+        let mut pieces = Vec::new();
+        if !rect.is_empty() {
+            pieces.push(rect);
+        }
+        for r in self.rects.iter() {
+            if pieces.is_empty() {
+                return;
+            }
+            pieces = pieces.iter().flat_map(|p| p.subtract(r)).collect();
+        }
+        self.rects.extend(pieces);
+        self.coalesce();
+    }
+
+    pub fn remove(&mut self, rect: IRange2) {
+        if !self.intersects(&rect) {
+            return;
+        }
+        self.rects = self.rects.iter().flat_map(|r| r.subtract(&rect)).collect();
+        self.coalesce();
+    }
+
+    /// Keeps only the cells inside rect
+    pub fn clip(&mut self, rect: IRange2) {
+        self.rects = self.rects.iter().filter_map(|r| r.intersection(&rect)).collect();
+    }
+
+    pub fn union(&self, rhs: &Self) -> Self {
+        let mut result = self.clone();
+        for r in rhs.iter() {
+            result.add(*r);
+        }
+        result
+    }
+
+    pub fn difference(&self, rhs: &Self) -> Self {
+        let mut result = self.clone();
+        for r in rhs.iter() {
+            result.remove(*r);
+        }
+        result
+    }
+
+    pub fn intersection(&self, rhs: &Self) -> Self {
+        let mut rects = Vec::new();
+        for a in self.iter() {
+            rects.extend(rhs.iter().filter_map(|b| a.intersection(b)));
+        }
+        let mut result = Self { rects };
+        result.coalesce();
+        result
+    }
+
+    /// Merges rectangles that share a full edge, keeping the list short
+    /// when the same area is invalidated piece by piece.
+    fn coalesce(&mut self) {
+        let mut merged = true;
+        while merged {
+            merged = false;
+            'outer: for i in 0..self.rects.len() {
+                for j in i + 1..self.rects.len() {
+                    let (a, b) = (self.rects[i], self.rects[j]);
+                    let same_x = a.min.x == b.min.x && a.max.x == b.max.x;
+                    let same_y = a.min.y == b.min.y && a.max.y == b.max.y;
+                    let touch_y = a.max.y == b.min.y || b.max.y == a.min.y;
+                    let touch_x = a.max.x == b.min.x || b.max.x == a.min.x;
+                    if (same_x && touch_y) || (same_y && touch_x) {
+                        self.rects[i] = a | b;
+                        self.rects.swap_remove(j);
+                        merged = true;
+                        break 'outer;
+                    }
+                }
+            }
+        }
+    }
+}
+
+impl From<IRange2> for Region {
+    fn from(rect: IRange2) -> Self {
+        Self::from_rect(rect)
+    }
+}
+
+impl<'a> IntoIterator for &'a Region {
+    type Item = &'a IRange2;
+    type IntoIter = std::slice::Iter<'a, IRange2>;
+
+    fn into_iter(self) -> Self::IntoIter {
+        self.rects.iter()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::pcg::PcgRng;
+
+    fn random_rect(rng: &mut PcgRng) -> IRange2 {
+        let min = IVec2::new(rng.next_i32_in(-6..6), rng.next_i32_in(-6..6));
+        IRange2::sized(min, IVec2::new(rng.next_i32_in(0..8), rng.next_i32_in(0..8)))
+    }
+
+    fn assert_disjoint(region: &Region) {
+        let rects = region.rects();
+        for (i, a) in rects.iter().enumerate() {
+            assert!(!a.is_empty());
+            assert!(rects[i + 1..].iter().all(|b| !a.intersects(b)));
+        }
+    }
+
+    #[test]
+    fn add_and_remove() {
+        let mut region = Region::new();
+        region.add(IRange2::from_xy(0, 0, 4, 4));
+        region.add(IRange2::from_xy(2, 2, 6, 6));
+        assert_eq!(region.area(), 28);
+        assert_eq!(region.bounds(), Some(IRange2::from_xy(0, 0, 6, 6)));
+        assert!(region.contains(IVec2::new(5, 5)));
+        assert!(!region.contains(IVec2::new(5, 0)));
+        assert_disjoint(&region);
+
+        region.remove(IRange2::from_xy(1, 1, 5, 5));
+        assert_eq!(region.area(), 14);
+        assert!(!region.contains(IVec2::new(3, 3)));
+        region.clip(IRange2::from_xy(0, 0, 2, 6));
+        assert_eq!(region.area(), 5);
+
+        region.clear();
+        assert!(region.is_empty());
+        assert_eq!(region.bounds(), None);
+    }
+
+    #[test]
+    fn coalesces_neighbors() {
+        let mut region = Region::new();
+        for y in 0..4 {
+            for x in 0..4 {
+                region.add(IRange2::sized(IVec2::new(x, y), IVec2::ONE));
+            }
+        }
+        assert_eq!(region.rects(), &[IRange2::from_xy(0, 0, 4, 4)]);
+    }
+
+    #[test]
+    fn area_conservation() {
+        let mut rng = PcgRng::new(11, 5);
+        for _ in 0..200 {
+            let a: Region = (0..4).fold(Region::new(), |r, _| r.union(&random_rect(&mut rng).into()));
+            let b: Region = (0..4).fold(Region::new(), |r, _| r.union(&random_rect(&mut rng).into()));
+            let union = a.union(&b);
+            let inter = a.intersection(&b);
+            let diff = a.difference(&b);
+            for region in [&a, &b, &union, &inter, &diff] {
+                assert_disjoint(region);
+            }
+            assert_eq!(union.area() + inter.area(), a.area() + b.area());
+            assert_eq!(diff.area() + inter.area(), a.area());
+            assert_eq!(union.difference(&a).area(), b.area() - inter.area());
+
+            let bounds = IRange2::from_xy(-7, -7, 15, 15);
+            for p in bounds.iter() {
+                let (in_a, in_b) = (a.contains(p), b.contains(p));
+                assert_eq!(union.contains(p), in_a || in_b);
+                assert_eq!(inter.contains(p), in_a && in_b);
+                assert_eq!(diff.contains(p), in_a && !in_b);
+            }
+        }
+    }
+}
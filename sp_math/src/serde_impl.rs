@@ -0,0 +1,285 @@
+//! Serde support for ranges and colors. Ranges serialize as flat arrays like
+//! [x0, y0, x1, y1] and also accept { min, max } maps. IRgba serializes as a
+//! hex string; all colors accept hex strings, CSS names, arrays and maps.
+
+use glam::{IVec2, IVec3, Vec2, Vec3};
+use serde::de::{DeserializeOwned, Error};
+use serde::ser::SerializeStruct;
+use serde::{Deserialize, Deserializer, Serialize, Serializer};
+
+use crate::color::{Hsva, IRgba, Rgba};
+use crate::range::{IRange1, IRange2, IRange3, Range1, Range2, Range3};
+
+// -------------------------------
+// Ranges
+
+trait FlatRange: Sized {
+    type Flat: Serialize + DeserializeOwned;
+    type Corner: DeserializeOwned;
+
+    fn to_flat(&self) -> Self::Flat;
+    fn from_flat(flat: Self::Flat) -> Self;
+    fn from_corners(min: Self::Corner, max: Self::Corner) -> Self;
+}
+
+#[derive(Deserialize)]
+#[serde(untagged)]
+enum RangeRepr<F, C> {
+    Flat(F),
+    Corners { min: C, max: C },
+}
+
+macro_rules! impl_range_serde {
+    ($range:ident, $flat:ty, $corner:ty,
+     |$s:ident| $to:expr,
+     |$f:ident| $from:expr,
+     |$min:ident, $max:ident| $corners:expr) => {
+        impl FlatRange for $range {
+            type Flat = $flat;
+            type Corner = $corner;
+
+            fn to_flat(&self) -> $flat {
+                let $s = self;
+                $to
+            }
+
+            fn from_flat($f: $flat) -> Self {
+                $from
+            }
+
+            fn from_corners($min: $corner, $max: $corner) -> Self {
+                $corners
+            }
+        }
+
+        impl Serialize for $range {
+            fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+                self.to_flat().serialize(serializer)
+            }
+        }
+
+        impl<'de> Deserialize<'de> for $range {
+            fn deserialize<D: Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+                match RangeRepr::<$flat, $corner>::deserialize(deserializer).map_err(|_| {
+                    D::Error::custom(concat!(
+                        "expected ",
+                        stringify!($range),
+                        " as a flat array or { min, max }"
+                    ))
+                })? {
+                    RangeRepr::Flat(flat) => Ok(Self::from_flat(flat)),
+                    RangeRepr::Corners { min, max } => Ok(Self::from_corners(min, max)),
+                }
+            }
+        }
+    };
+}
+
+impl_range_serde!(IRange1, [i32; 2], i32,
+    |r| [r.min, r.max],
+    |f| Self::new(f[0], f[1]),
+    |min, max| Self::new(min, max));
+
+impl_range_serde!(Range1, [f32; 2], f32,
+    |r| [r.min, r.max],
+    |f| Self::new(f[0], f[1]),
+    |min, max| Self::new(min, max));
+
+impl_range_serde!(IRange2, [i32; 4], [i32; 2],
+    |r| [r.min.x, r.min.y, r.max.x, r.max.y],
+    |f| Self::from_xy(f[0], f[1], f[2], f[3]),
+    |min, max| Self::new(IVec2::from(min), IVec2::from(max)));
+
+impl_range_serde!(Range2, [f32; 4], [f32; 2],
+    |r| [r.min.x, r.min.y, r.max.x, r.max.y],
+    |f| Self::from_x0y0x1y1(f[0], f[1], f[2], f[3]),
+    |min, max| Self::new(Vec2::from(min), Vec2::from(max)));
+
+impl_range_serde!(IRange3, [i32; 6], [i32; 3],
+    |r| [r.min.x, r.min.y, r.min.z, r.max.x, r.max.y, r.max.z],
+    |f| Self::new(IVec3::new(f[0], f[1], f[2]), IVec3::new(f[3], f[4], f[5])),
+    |min, max| Self::new(IVec3::from(min), IVec3::from(max)));
+
+impl_range_serde!(Range3, [f32; 6], [f32; 3],
+    |r| [r.min.x, r.min.y, r.min.z, r.max.x, r.max.y, r.max.z],
+    |f| Self::new(Vec3::new(f[0], f[1], f[2]), Vec3::new(f[3], f[4], f[5])),
+    |min, max| Self::new(Vec3::from(min), Vec3::from(max)));
+
+// -------------------------------
+// Colors
+
+fn default_alpha_u8() -> u8 {
+    255
+}
+
+fn default_alpha_f32() -> f32 {
+    1.0
+}
+
+#[derive(Deserialize)]
+#[serde(untagged)]
+enum IRgbaRepr {
+    Str(String),
+    Rgba([u8; 4]),
+    Rgb([u8; 3]),
+    Fields {
+        r: u8,
+        g: u8,
+        b: u8,
+        #[serde(default = "default_alpha_u8")]
+        a: u8,
+    },
+}
+
+#[derive(Deserialize)]
+#[serde(untagged)]
+enum RgbaRepr {
+    Str(String),
+    Rgba([f32; 4]),
+    Rgb([f32; 3]),
+    Fields {
+        r: f32,
+        g: f32,
+        b: f32,
+        #[serde(default = "default_alpha_f32")]
+        a: f32,
+    },
+}
+
+#[derive(Deserialize)]
+#[serde(untagged)]
+enum HsvaRepr {
+    Str(String),
+    Hsva([f32; 4]),
+    Hsv([f32; 3]),
+    Fields {
+        h: f32,
+        s: f32,
+        v: f32,
+        #[serde(default = "default_alpha_f32")]
+        a: f32,
+    },
+}
+
+fn parse_color<E: Error>(s: &str) -> Result<IRgba, E> {
+    IRgba::parse(s).ok_or_else(|| E::custom(format!("invalid color: {s:?}")))
+}
+
+impl Serialize for IRgba {
+    fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        serializer.serialize_str(&self.to_hex())
+    }
+}
+
+impl<'de> Deserialize<'de> for IRgba {
+    fn deserialize<D: Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        let repr = IRgbaRepr::deserialize(deserializer)
+            .map_err(|_| D::Error::custom("expected a color string, array or { r, g, b, a }"))?;
+        match repr {
+            IRgbaRepr::Str(s) => parse_color(&s),
+            IRgbaRepr::Rgba([r, g, b, a]) => Ok(Self::new(r, g, b, a)),
+            IRgbaRepr::Rgb([r, g, b]) => Ok(Self::rgb(r, g, b)),
+            IRgbaRepr::Fields { r, g, b, a } => Ok(Self::new(r, g, b, a)),
+        }
+    }
+}
+
+impl Serialize for Rgba {
+    fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        let mut s = serializer.serialize_struct("Rgba", 4)?;
+        s.serialize_field("r", &self.r)?;
+        s.serialize_field("g", &self.g)?;
+        s.serialize_field("b", &self.b)?;
+        s.serialize_field("a", &self.a)?;
+        s.end()
+    }
+}
+
+impl<'de> Deserialize<'de> for Rgba {
+    fn deserialize<D: Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        let repr = RgbaRepr::deserialize(deserializer)
+            .map_err(|_| D::Error::custom("expected a color string, array or { r, g, b, a }"))?;
+        match repr {
+            RgbaRepr::Str(s) => parse_color(&s).map(|c| c.to_rgba()),
+            RgbaRepr::Rgba([r, g, b, a]) => Ok(Self::new(r, g, b, a)),
+            RgbaRepr::Rgb([r, g, b]) => Ok(Self::new(r, g, b, 1.0)),
+            RgbaRepr::Fields { r, g, b, a } => Ok(Self::new(r, g, b, a)),
+        }
+    }
+}
+
+impl Serialize for Hsva {
+    fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        let mut s = serializer.serialize_struct("Hsva", 4)?;
+        s.serialize_field("h", &self.h)?;
+        s.serialize_field("s", &self.s)?;
+        s.serialize_field("v", &self.v)?;
+        s.serialize_field("a", &self.a)?;
+        s.end()
+    }
+}
+
+impl<'de> Deserialize<'de> for Hsva {
+    fn deserialize<D: Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        let repr = HsvaRepr::deserialize(deserializer)
+            .map_err(|_| D::Error::custom("expected a color string, array or { h, s, v, a }"))?;
+        match repr {
+            HsvaRepr::Str(s) => parse_color(&s).map(|c| c.to_rgba().to_hsva()),
+            HsvaRepr::Hsva([h, s, v, a]) => Ok(Self::new(h, s, v, a)),
+            HsvaRepr::Hsv([h, s, v]) => Ok(Self::hsv(h, s, v)),
+            HsvaRepr::Fields { h, s, v, a } => Ok(Self::new(h, s, v, a)),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::ease::Ease;
+
+    #[test]
+    fn ranges_use_flat_arrays() {
+        let r = IRange2::from_xy(1, 2, 3, 4);
+        assert_eq!(serde_json::to_string(&r).unwrap(), "[1,2,3,4]");
+        assert_eq!(serde_json::from_str::<IRange2>("[1,2,3,4]").unwrap(), r);
+        let named = r#"{"min":[1,2],"max":[3,4]}"#;
+        assert_eq!(serde_json::from_str::<IRange2>(named).unwrap(), r);
+        assert!(serde_json::from_str::<IRange2>("[1,2,3]").is_err());
+
+        let r = Range3::new(Vec3::new(0.0, 0.5, 1.0), Vec3::splat(2.0));
+        let json = serde_json::to_string(&r).unwrap();
+        assert_eq!(serde_json::from_str::<Range3>(&json).unwrap(), r);
+        let r = Range1::new(-1.0, 1.0);
+        assert_eq!(serde_json::from_str::<Range1>(r#"{"min":-1,"max":1}"#).unwrap(), r);
+        assert_eq!(serde_json::from_str::<IRange1>("[0,8]").unwrap(), IRange1::new(0, 8));
+    }
+
+    #[test]
+    fn colors_accept_hex() {
+        let c = IRgba::new(0x12, 0x34, 0x56, 0x78);
+        assert_eq!(serde_json::to_string(&c).unwrap(), r##""#12345678""##);
+        assert_eq!(serde_json::from_str::<IRgba>(r##""#12345678""##).unwrap(), c);
+        assert_eq!(serde_json::from_str::<IRgba>(r#""red""#).unwrap(), IRgba::RED);
+        assert_eq!(serde_json::from_str::<IRgba>("[255,0,0]").unwrap(), IRgba::RED);
+        assert!(serde_json::from_str::<IRgba>(r##""#12345""##).is_err());
+
+        let rgba = Rgba::new(0.25, 0.5, 0.75, 1.0);
+        let json = serde_json::to_string(&rgba).unwrap();
+        assert_eq!(serde_json::from_str::<Rgba>(&json).unwrap(), rgba);
+        let red: Rgba = serde_json::from_str(r##""#ff0000""##).unwrap();
+        assert_eq!(red, Rgba::new(1.0, 0.0, 0.0, 1.0));
+        let hsv: Hsva = serde_json::from_str(r#"{"h":120,"s":1,"v":1}"#).unwrap();
+        assert_eq!(hsv, Hsva::hsv(120.0, 1.0, 1.0));
+        let hsv: Hsva = serde_json::from_str(r##""#00ff00""##).unwrap();
+        assert_eq!(hsv.h, 120.0);
+    }
+
+    #[test]
+    fn ease_round_trip() {
+        for ease in [Ease::Linear, Ease::BounceOut, Ease::CubicBezier(0.25, 0.1, 0.25, 1.0)] {
+            let json = serde_json::to_string(&ease).unwrap();
+            assert_eq!(serde_json::from_str::<Ease>(&json).unwrap(), ease);
+        }
+        assert_eq!(serde_json::from_str::<Ease>(r#""QuadIn""#).unwrap(), Ease::QuadIn);
+    }
+}
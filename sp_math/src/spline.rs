@@ -0,0 +1,460 @@
+use std::ops::{Add, Mul, Sub};
+
+use glam::{Vec2, Vec3};
+
+/// Vector types that curves can be built from, where the default is zero
+pub trait SplineVec:
+    Copy + Default + Add<Output = Self> + Sub<Output = Self> + Mul<f32, Output = Self>
+{
+    fn dot_product(self, rhs: Self) -> f32;
+
+    fn length(self) -> f32 {
+        self.dot_product(self).sqrt()
+    }
+
+    fn normalize_or_zero(self) -> Self {
+        let len = self.length();
+        if len > 1e-9 {
+            self * (1.0 / len)
+        } else {
+            self * 0.0
+        }
+    }
+}
+
+impl SplineVec for Vec2 {
+    fn dot_product(self, rhs: Self) -> f32 {
+        self.dot(rhs)
+    }
+}
+
+impl SplineVec for Vec3 {
+    fn dot_product(self, rhs: Self) -> f32 {
+        self.dot(rhs)
+    }
+}
+
+// -------------------------------
+// Segments
+
+pub fn quadratic_bezier<V: SplineVec>(p0: V, p1: V, p2: V, t: f32) -> V {
+    let s = 1.0 - t;
+    p0 * (s * s) + p1 * (2.0 * s * t) + p2 * (t * t)
+}
+
+pub fn quadratic_bezier_derivative<V: SplineVec>(p0: V, p1: V, p2: V, t: f32) -> V {
+    (p1 - p0) * (2.0 * (1.0 - t)) + (p2 - p1) * (2.0 * t)
+}
+
+pub fn cubic_bezier<V: SplineVec>(p0: V, p1: V, p2: V, p3: V, t: f32) -> V {
+    let s = 1.0 - t;
+    p0 * (s * s * s) + p1 * (3.0 * s * s * t) + p2 * (3.0 * s * t * t) + p3 * (t * t * t)
+}
+
+pub fn cubic_bezier_derivative<V: SplineVec>(p0: V, p1: V, p2: V, p3: V, t: f32) -> V {
+    let s = 1.0 - t;
+    (p1 - p0) * (3.0 * s * s) + (p2 - p1) * (6.0 * s * t) + (p3 - p2) * (3.0 * t * t)
+}
+
+/// Cubic Hermite curve from p1 to p2 with tangents scaled by (1 - tension),
+/// where zero tension gives Catmull-Rom.
+pub fn cardinal<V: SplineVec>(p0: V, p1: V, p2: V, p3: V, tension: f32, t: f32) -> V {
+    let k = (1.0 - tension) * 0.5;
+    let m1 = (p2 - p0) * k;
+    let m2 = (p3 - p1) * k;
+    let t2 = t * t;
+    let t3 = t2 * t;
+    p1 * (2.0 * t3 - 3.0 * t2 + 1.0)
+        + m1 * (t3 - 2.0 * t2 + t)
+        + p2 * (-2.0 * t3 + 3.0 * t2)
+        + m2 * (t3 - t2)
+}
+
+pub fn cardinal_derivative<V: SplineVec>(p0: V, p1: V, p2: V, p3: V, tension: f32, t: f32) -> V {
+    let k = (1.0 - tension) * 0.5;
+    let m1 = (p2 - p0) * k;
+    let m2 = (p3 - p1) * k;
+    let t2 = t * t;
+    p1 * (6.0 * t2 - 6.0 * t)
+        + m1 * (3.0 * t2 - 4.0 * t + 1.0)
+        + p2 * (-6.0 * t2 + 6.0 * t)
+        + m2 * (3.0 * t2 - 2.0 * t)
+}
+
+pub fn catmull_rom<V: SplineVec>(p0: V, p1: V, p2: V, p3: V, t: f32) -> V {
+    cardinal(p0, p1, p2, p3, 0.0, t)
+}
+
+pub fn catmull_rom_derivative<V: SplineVec>(p0: V, p1: V, p2: V, p3: V, t: f32) -> V {
+    cardinal_derivative(p0, p1, p2, p3, 0.0, t)
+}
+
+/// Uniform cubic B-spline segment, which approximates rather than passes
+/// through its control points.
+pub fn bspline<V: SplineVec>(p0: V, p1: V, p2: V, p3: V, t: f32) -> V {
+    let s = 1.0 - t;
+    let t2 = t * t;
+    let t3 = t2 * t;
+    (p0 * (s * s * s)
+        + p1 * (3.0 * t3 - 6.0 * t2 + 4.0)
+        + p2 * (-3.0 * t3 + 3.0 * t2 + 3.0 * t + 1.0)
+        + p3 * t3)
+        * (1.0 / 6.0)
+}
+
+pub fn bspline_derivative<V: SplineVec>(p0: V, p1: V, p2: V, p3: V, t: f32) -> V {
+    let s = 1.0 - t;
+    let t2 = t * t;
+    (p0 * (-3.0 * s * s)
+        + p1 * (9.0 * t2 - 12.0 * t)
+        + p2 * (-9.0 * t2 + 6.0 * t + 3.0)
+        + p3 * (3.0 * t2))
+        * (1.0 / 6.0)
+}
+
+// -------------------------------
+// Curves
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum SplineKind {
+    /// Passes through every point
+    CatmullRom,
+    /// Passes through every point, with tension 0 matching Catmull-Rom and
+    /// tension 1 giving straight segments
+    Cardinal(f32),
+    /// Points are end, control, end, control, ..., end
+    QuadraticBezier,
+    /// Points are end, control, control, end, control, control, ..., end
+    CubicBezier,
+    /// Smooth curve near but not through the points
+    BSpline,
+}
+
+/// Piecewise curve parameterized by u in [0, segment_count]. A spline
+/// without points samples as zero.
+#[derive(Debug, Clone, PartialEq)]
+pub struct Spline<V> {
+    pub kind: SplineKind,
+    pub points: Vec<V>,
+    /// Wraps around to the first point. Ignored for Bezier kinds.
+    pub closed: bool,
+}
+
+impl<V: SplineVec> Spline<V> {
+    pub fn new(kind: SplineKind, points: Vec<V>) -> Self {
+        Self {
+            kind,
+            points,
+            closed: false,
+        }
+    }
+
+    pub fn closed(kind: SplineKind, points: Vec<V>) -> Self {
+        Self {
+            kind,
+            points,
+            closed: true,
+        }
+    }
+
+    pub fn segment_count(&self) -> usize {
+        let n = self.points.len();
+        match self.kind {
+            SplineKind::QuadraticBezier => n.saturating_sub(1) / 2,
+            SplineKind::CubicBezier => n.saturating_sub(1) / 3,
+            _ => {
+                if n < 2 {
+                    0
+                } else if self.closed {
+                    n
+                } else {
+                    n - 1
+                }
+            }
+        }
+    }
+
+    /// Ends are clamped by repeating the first and last points
+    fn point(&self, i: isize) -> V {
+        let n = self.points.len() as isize;
+        let i = if self.closed {
+            i.rem_euclid(n)
+        } else {
+            i.clamp(0, n - 1)
+        };
+        self.points[i as usize]
+    }
+
+    fn segment(&self, u: f32) -> (usize, f32) {
+        let count = self.segment_count();
+        let u = u.clamp(0.0, count as f32);
+        let i = (u.floor() as usize).min(count.saturating_sub(1));
+        (i, u - i as f32)
+    }
+
+    fn eval(&self, u: f32, derivative: bool) -> V {
+        if self.segment_count() == 0 {
+            return match self.points.first() {
+                Some(p) if !derivative => *p,
+                _ => V::default(),
+            };
+        }
+        let (i, t) = self.segment(u);
+        match self.kind {
+            SplineKind::QuadraticBezier => {
+                let p = &self.points[i * 2..];
+                if derivative {
+                    quadratic_bezier_derivative(p[0], p[1], p[2], t)
+                } else {
+                    quadratic_bezier(p[0], p[1], p[2], t)
+                }
+            }
+            SplineKind::CubicBezier => {
+                let p = &self.points[i * 3..];
+                if derivative {
+                    cubic_bezier_derivative(p[0], p[1], p[2], p[3], t)
+                } else {
+                    cubic_bezier(p[0], p[1], p[2], p[3], t)
+                }
+            }
+            kind => {
+                let i = i as isize;
+                let p0 = self.point(i - 1);
+                let p1 = self.point(i);
+                let p2 = self.point(i + 1);
+                let p3 = self.point(i + 2);
+                match (kind, derivative) {
+                    (SplineKind::BSpline, false) => bspline(p0, p1, p2, p3, t),
+                    (SplineKind::BSpline, true) => bspline_derivative(p0, p1, p2, p3, t),
+                    (SplineKind::Cardinal(tension), false) => cardinal(p0, p1, p2, p3, tension, t),
+                    (SplineKind::Cardinal(tension), true) => {
+                        cardinal_derivative(p0, p1, p2, p3, tension, t)
+                    }
+                    (_, false) => catmull_rom(p0, p1, p2, p3, t),
+                    (_, true) => catmull_rom_derivative(p0, p1, p2, p3, t),
+                }
+            }
+        }
+    }
+
+    pub fn sample(&self, u: f32) -> V {
+        self.eval(u, false)
+    }
+
+    /// Derivative with respect to u
+    pub fn derivative(&self, u: f32) -> V {
+        self.eval(u, true)
+    }
+
+    pub fn tangent(&self, u: f32) -> V {
+        self.derivative(u).normalize_or_zero()
+    }
+
+    /// Returns parameter of the nearest point on the curve by coarse
+    /// sampling followed by bisection refinement.
+    pub fn closest_param(&self, p: V) -> f32 {
+        const STEPS: usize = 16;
+        let count = self.segment_count();
+        let dist_sqr = |u: f32| {
+            let d = self.sample(u) - p;
+            d.dot_product(d)
+        };
+        let total = count * STEPS;
+        let mut best_u = 0.0;
+        let mut best_d = f32::MAX;
+        for i in 0..=total {
+            let u = i as f32 / STEPS as f32;
+            let d = dist_sqr(u);
+            if d < best_d {
+                best_d = d;
+                best_u = u;
+            }
+        }
+        let mut step = 0.5 / STEPS as f32;
+        for _ in 0..20 {
+            let lo = (best_u - step).max(0.0);
+            let hi = (best_u + step).min(count as f32);
+            let dl = dist_sqr(lo);
+            let dh = dist_sqr(hi);
+            if dl < best_d {
+                best_d = dl;
+                best_u = lo;
+            }
+            if dh < best_d {
+                best_d = dh;
+                best_u = hi;
+            }
+            step *= 0.5;
+        }
+        best_u
+    }
+
+    pub fn closest_point(&self, p: V) -> V {
+        self.sample(self.closest_param(p))
+    }
+}
+
+/// Cumulative chord lengths sampled along a spline, for moving at constant
+/// speed and converting between distance and curve parameter.
+#[derive(Debug, Clone, PartialEq)]
+pub struct ArcLengthTable {
+    params: Vec<f32>,
+    lengths: Vec<f32>,
+}
+
+impl ArcLengthTable {
+    pub fn new<V: SplineVec>(spline: &Spline<V>, samples_per_segment: usize) -> Self {
+        let steps = spline.segment_count() * samples_per_segment.max(1);
+        let mut params = Vec::with_capacity(steps + 1);
+        let mut lengths = Vec::with_capacity(steps + 1);
+        let mut prev = spline.sample(0.0);
+        let mut total = 0.0;
+        params.push(0.0);
+        lengths.push(0.0);
+        for i in 1..=steps {
+            let u = i as f32 / samples_per_segment.max(1) as f32;
+            let p = spline.sample(u);
+            total += (p - prev).length();
+            params.push(u);
+            lengths.push(total);
+            prev = p;
+        }
+        Self { params, lengths }
+    }
+
+    pub fn length(&self) -> f32 {
+        self.lengths.last().copied().unwrap_or(0.0)
+    }
+
+    /// Distance is clamped to the curve length
+    pub fn param_at_distance(&self, distance: f32) -> f32 {
+        let d = distance.clamp(0.0, self.length());
+        let i = self.lengths.partition_point(|x| *x < d);
+        if i == 0 {
+            self.params.first().copied().unwrap_or(0.0)
+        } else {
+            let l0 = self.lengths[i - 1];
+            let l1 = self.lengths[i];
+            let t = if l1 > l0 { (d - l0) / (l1 - l0) } else { 0.0 };
+            crate::interp::lerp(self.params[i - 1], self.params[i], t)
+        }
+    }
+
+    pub fn distance_at_param(&self, u: f32) -> f32 {
+        let i = self.params.partition_point(|x| *x < u);
+        if i == 0 {
+            0.0
+        } else if i >= self.params.len() {
+            self.length()
+        } else {
+            let u0 = self.params[i - 1];
+            let u1 = self.params[i];
+            crate::interp::lerp(self.lengths[i - 1], self.lengths[i], (u - u0) / (u1 - u0))
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn assert_near2(a: Vec2, b: Vec2, eps: f32) {
+        assert!((a - b).length() < eps, "{:?} != {:?}", a, b);
+    }
+
+    #[test]
+    fn catmull_rom_passes_through_points() {
+        let points = vec![Vec2::ZERO, Vec2::new(1.0, 2.0), Vec2::new(3.0, 1.0), Vec2::new(4.0, 4.0)];
+        let spline = Spline::new(SplineKind::CatmullRom, points.clone());
+        assert_eq!(spline.segment_count(), 3);
+        for (i, p) in points.iter().enumerate() {
+            assert_near2(spline.sample(i as f32), *p, 1e-5);
+        }
+    }
+
+    #[test]
+    fn bezier_endpoints_and_tangents() {
+        let points = vec![
+            Vec3::ZERO,
+            Vec3::new(1.0, 0.0, 0.0),
+            Vec3::new(2.0, 1.0, 0.0),
+            Vec3::new(2.0, 2.0, 0.0),
+        ];
+        let spline = Spline::new(SplineKind::CubicBezier, points);
+        assert_eq!(spline.segment_count(), 1);
+        assert_eq!(spline.sample(1.0), Vec3::new(2.0, 2.0, 0.0));
+        assert_eq!(spline.tangent(0.0), Vec3::X);
+        assert_eq!(spline.tangent(1.0), Vec3::Y);
+        let q = quadratic_bezier(Vec2::ZERO, Vec2::new(1.0, 2.0), Vec2::new(2.0, 0.0), 0.5);
+        assert_near2(q, Vec2::new(1.0, 1.0), 1e-6);
+    }
+
+    #[test]
+    fn derivative_matches_finite_difference() {
+        let points = vec![Vec2::ZERO, Vec2::new(1.0, 2.0), Vec2::new(3.0, 1.0), Vec2::new(4.0, 4.0)];
+        for kind in [
+            SplineKind::CatmullRom,
+            SplineKind::Cardinal(0.5),
+            SplineKind::BSpline,
+            SplineKind::CubicBezier,
+        ] {
+            let spline = Spline::new(kind, points.clone());
+            let u = 0.37;
+            let h = 1e-3;
+            let fd = (spline.sample(u + h) - spline.sample(u - h)) / (2.0 * h);
+            assert_near2(spline.derivative(u), fd, 1e-2);
+        }
+    }
+
+    #[test]
+    fn arc_length_constant_speed() {
+        let line = Spline::new(SplineKind::CatmullRom, vec![Vec2::ZERO, Vec2::new(10.0, 0.0)]);
+        let table = ArcLengthTable::new(&line, 32);
+        assert!((table.length() - 10.0).abs() < 1e-3);
+        let circle = Spline::closed(
+            SplineKind::CatmullRom,
+            (0..32)
+                .map(|i| {
+                    let a = i as f32 / 32.0 * std::f32::consts::TAU;
+                    Vec2::new(a.cos(), a.sin())
+                })
+                .collect(),
+        );
+        let table = ArcLengthTable::new(&circle, 16);
+        assert!((table.length() - std::f32::consts::TAU).abs() < 1e-2);
+        let step = table.length() / 10.0;
+        let mut prev = circle.sample(table.param_at_distance(0.0));
+        for i in 1..=10 {
+            let p = circle.sample(table.param_at_distance(step * i as f32));
+            assert!(((p - prev).length() - step).abs() < 0.02);
+            prev = p;
+        }
+        let u = table.param_at_distance(2.0);
+        assert!((table.distance_at_param(u) - 2.0).abs() < 1e-3);
+    }
+
+    #[test]
+    fn closest_point_on_curve() {
+        let spline = Spline::new(
+            SplineKind::CatmullRom,
+            vec![Vec2::ZERO, Vec2::new(5.0, 0.0), Vec2::new(10.0, 0.0)],
+        );
+        let p = spline.closest_point(Vec2::new(3.3, 2.0));
+        assert_near2(p, Vec2::new(3.3, 0.0), 1e-3);
+    }
+
+    #[test]
+    fn degenerate_splines() {
+        for kind in [SplineKind::CatmullRom, SplineKind::CubicBezier, SplineKind::BSpline] {
+            let empty: Spline<Vec3> = Spline::new(kind, Vec::new());
+            assert_eq!(empty.sample(0.5), Vec3::ZERO);
+            assert_eq!(empty.tangent(0.5), Vec3::ZERO);
+            assert_eq!(empty.closest_point(Vec3::ONE), Vec3::ZERO);
+            assert_eq!(ArcLengthTable::new(&empty, 8).length(), 0.0);
+
+            let single = Spline::new(kind, vec![Vec2::ONE]);
+            assert_eq!(single.sample(0.5), Vec2::ONE);
+            assert_eq!(single.derivative(0.5), Vec2::ZERO);
+        }
+    }
+}
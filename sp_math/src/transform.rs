@@ -0,0 +1,274 @@
+use std::ops;
+
+use glam::{Affine2, Mat2, Mat3, Vec2};
+
+use crate::interp::lerp_rads;
+use crate::vec::{inv_rotate_vec2, rotate_vec2};
+
+/// Scale, then rotation, then translation. The rotation is a unit vector as
+/// used by sprites (`Sprite::rot`), see rotate_vec2.
+///
+/// Composition and inverse are exact for uniform scale. With non-uniform
+/// scale under a rotation the result would need shear, which is dropped.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct Transform2 {
+    pub translation: Vec2,
+    pub rot: Vec2,
+    pub scale: Vec2,
+}
+
+impl Default for Transform2 {
+    fn default() -> Self {
+        Self::IDENTITY
+    }
+}
+
+impl Transform2 {
+    pub const IDENTITY: Self = Self {
+        translation: Vec2::ZERO,
+        rot: Vec2::X,
+        scale: Vec2::ONE,
+    };
+
+    pub fn new(translation: Vec2, rot: Vec2, scale: Vec2) -> Self {
+        Self {
+            translation,
+            rot,
+            scale,
+        }
+    }
+
+    pub fn from_translation(translation: Vec2) -> Self {
+        Self {
+            translation,
+            ..Self::IDENTITY
+        }
+    }
+
+    pub fn from_rot(rot: Vec2) -> Self {
+        Self {
+            rot,
+            ..Self::IDENTITY
+        }
+    }
+
+    pub fn from_angle(radians: f32) -> Self {
+        Self::from_rot(Vec2::from_angle(radians))
+    }
+
+    pub fn from_scale(scale: Vec2) -> Self {
+        Self {
+            scale,
+            ..Self::IDENTITY
+        }
+    }
+
+    pub fn with_translation(mut self, translation: Vec2) -> Self {
+        self.translation = translation;
+        self
+    }
+
+    pub fn with_rot(mut self, rot: Vec2) -> Self {
+        self.rot = rot;
+        self
+    }
+
+    pub fn with_angle(mut self, radians: f32) -> Self {
+        self.rot = Vec2::from_angle(radians);
+        self
+    }
+
+    pub fn with_scale(mut self, scale: Vec2) -> Self {
+        self.scale = scale;
+        self
+    }
+
+    /// Rotation in radians
+    pub fn angle(&self) -> f32 {
+        self.rot.to_angle()
+    }
+
+    pub fn transform_point(&self, p: Vec2) -> Vec2 {
+        self.translation + rotate_vec2(self.rot, p * self.scale)
+    }
+
+    /// Ignores translation
+    pub fn transform_vector(&self, v: Vec2) -> Vec2 {
+        rotate_vec2(self.rot, v * self.scale)
+    }
+
+    /// Exact for any scale, unlike inverse().transform_point()
+    pub fn inverse_transform_point(&self, p: Vec2) -> Vec2 {
+        inv_rotate_vec2(self.rot, p - self.translation) / self.scale
+    }
+
+    pub fn inverse_transform_vector(&self, v: Vec2) -> Vec2 {
+        inv_rotate_vec2(self.rot, v) / self.scale
+    }
+
+    pub fn inverse(&self) -> Self {
+        let rot = Vec2::new(self.rot.x, -self.rot.y);
+        let scale = self.scale.recip();
+        Self {
+            translation: rotate_vec2(rot, -self.translation) * scale,
+            rot,
+            scale,
+        }
+    }
+
+    /// Applies rhs first, then self
+    pub fn mul_transform(&self, rhs: &Self) -> Self {
+        Self {
+            translation: self.transform_point(rhs.translation),
+            rot: rotate_vec2(self.rot, rhs.rot),
+            scale: self.scale * rhs.scale,
+        }
+    }
+
+    /// Rotation takes the shortest arc
+    pub fn lerp(&self, rhs: &Self, t: f32) -> Self {
+        Self {
+            translation: self.translation.lerp(rhs.translation, t),
+            rot: Vec2::from_angle(lerp_rads(self.angle(), rhs.angle(), t)),
+            scale: self.scale.lerp(rhs.scale, t),
+        }
+    }
+
+    fn mat2(&self) -> Mat2 {
+        Mat2::from_cols(self.rot * self.scale.x, self.rot.perp() * self.scale.y)
+    }
+
+    pub fn to_mat3(&self) -> Mat3 {
+        Mat3::from_cols(
+            self.mat2().x_axis.extend(0.0),
+            self.mat2().y_axis.extend(0.0),
+            self.translation.extend(1.0),
+        )
+    }
+
+    pub fn to_affine2(&self) -> Affine2 {
+        Affine2::from_mat2_translation(self.mat2(), self.translation)
+    }
+
+    /// Drops shear and keeps a reflection in the y scale
+    pub fn from_affine2(affine: Affine2) -> Self {
+        let m = affine.matrix2;
+        let sx = m.x_axis.length();
+        let rot = if sx > 0.0 { m.x_axis / sx } else { Vec2::X };
+        let sy = m.y_axis.dot(rot.perp());
+        Self {
+            translation: affine.translation,
+            rot,
+            scale: Vec2::new(sx, sy),
+        }
+    }
+
+    /// Drops projection and shear
+    pub fn from_mat3(m: Mat3) -> Self {
+        Self::from_affine2(Affine2::from_mat3(m))
+    }
+}
+
+impl ops::Mul<Transform2> for Transform2 {
+    type Output = Self;
+    fn mul(self, rhs: Self) -> Self {
+        self.mul_transform(&rhs)
+    }
+}
+
+impl ops::Mul<Vec2> for Transform2 {
+    type Output = Vec2;
+    fn mul(self, rhs: Vec2) -> Vec2 {
+        self.transform_point(rhs)
+    }
+}
+
+impl From<Transform2> for Affine2 {
+    fn from(t: Transform2) -> Self {
+        t.to_affine2()
+    }
+}
+
+impl From<Transform2> for Mat3 {
+    fn from(t: Transform2) -> Self {
+        t.to_mat3()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::f32::consts::PI;
+
+    fn close(a: Vec2, b: Vec2) -> bool {
+        (a - b).length() < 1e-4
+    }
+
+    fn sample() -> Transform2 {
+        Transform2::new(
+            Vec2::new(3.0, -2.0),
+            Vec2::from_angle(0.7),
+            Vec2::new(2.0, 0.5),
+        )
+    }
+
+    #[test]
+    fn points_and_matrices() {
+        let t = sample();
+        let p = Vec2::new(1.5, 4.0);
+        assert!(close(t * p, t.to_mat3().transform_point2(p)));
+        assert!(close(t * p, t.to_affine2().transform_point2(p)));
+        assert!(close(
+            t.transform_vector(p),
+            t.to_mat3().transform_vector2(p)
+        ));
+        assert!(close(t.inverse_transform_point(t * p), p));
+        assert!(close(t.inverse_transform_vector(t.transform_vector(p)), p));
+        assert!(close(Transform2::from_angle(PI * 0.5) * Vec2::X, Vec2::Y));
+        assert_eq!(Transform2::default() * p, p);
+    }
+
+    #[test]
+    fn decompose() {
+        let t = sample();
+        let back = Transform2::from_mat3(t.to_mat3());
+        assert!(close(back.translation, t.translation));
+        assert!(close(back.rot, t.rot));
+        assert!(close(back.scale, t.scale));
+
+        let mirrored = sample().with_scale(Vec2::new(1.0, -3.0));
+        let back = Transform2::from_affine2(mirrored.into());
+        assert!(close(back.scale, mirrored.scale) && close(back.rot, mirrored.rot));
+    }
+
+    #[test]
+    fn compose_and_invert() {
+        let parent = Transform2::new(
+            Vec2::new(-1.0, 5.0),
+            Vec2::from_angle(-1.2),
+            Vec2::splat(3.0),
+        );
+        let child = sample();
+        let p = Vec2::new(0.25, -7.0);
+        assert!(close((parent * child) * p, parent * (child * p)));
+        let m = parent.to_mat3() * child.to_mat3();
+        assert!(close((parent * child) * p, m.transform_point2(p)));
+
+        assert!(close(parent.inverse() * (parent * p), p));
+        let identity = parent * parent.inverse();
+        assert!(close(identity.translation, Vec2::ZERO));
+        assert!(close(identity.rot, Vec2::X));
+        assert!(close(identity.scale, Vec2::ONE));
+    }
+
+    #[test]
+    fn lerp_shortest_arc() {
+        let a = Transform2::from_angle(PI - 0.1);
+        let b = Transform2::from_angle(-PI + 0.1).with_translation(Vec2::new(4.0, 0.0));
+        let mid = a.lerp(&b, 0.5);
+        assert!(close(mid.rot, Vec2::new(-1.0, 0.0)));
+        assert!(close(mid.translation, Vec2::new(2.0, 0.0)));
+        assert_eq!(a.lerp(&b, 0.0).translation, a.translation);
+        assert!(close(a.lerp(&b, 1.0).rot, b.rot));
+    }
+}
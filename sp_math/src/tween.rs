@@ -0,0 +1,446 @@
+use glam::{Quat, Vec2, Vec3, Vec4};
+
+use crate::color::{Hsva, Rgba};
+use crate::ease::Ease;
+
+/// Values that can be interpolated by a tween
+pub trait Tweenable: Copy {
+    fn tween_lerp(self, to: Self, t: f32) -> Self;
+}
+
+impl Tweenable for f32 {
+    fn tween_lerp(self, to: Self, t: f32) -> Self {
+        self + (to - self) * t
+    }
+}
+
+impl Tweenable for Vec2 {
+    fn tween_lerp(self, to: Self, t: f32) -> Self {
+        self.lerp(to, t)
+    }
+}
+
+impl Tweenable for Vec3 {
+    fn tween_lerp(self, to: Self, t: f32) -> Self {
+        self.lerp(to, t)
+    }
+}
+
+impl Tweenable for Vec4 {
+    fn tween_lerp(self, to: Self, t: f32) -> Self {
+        self.lerp(to, t)
+    }
+}
+
+impl Tweenable for Rgba {
+    fn tween_lerp(self, to: Self, t: f32) -> Self {
+        self.lerp(to, t)
+    }
+}
+
+impl Tweenable for Hsva {
+    fn tween_lerp(self, to: Self, t: f32) -> Self {
+        self.lerp(to, t)
+    }
+}
+
+impl Tweenable for Quat {
+    fn tween_lerp(self, to: Self, t: f32) -> Self {
+        self.slerp(to, t)
+    }
+}
+
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum Repeat {
+    /// Number of extra cycles after the first
+    #[default]
+    Never,
+    Times(u32),
+    Forever,
+}
+
+/// Animation from one value to another measured in whole ticks
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct Tween<T> {
+    pub from: T,
+    pub to: T,
+    pub duration: u32,
+    pub delay: u32,
+    pub ease: Ease,
+    pub repeat: Repeat,
+    /// Alternate direction on every repeated cycle
+    pub yoyo: bool,
+}
+
+impl<T: Tweenable> Tween<T> {
+    pub fn new(from: T, to: T, duration: u32) -> Self {
+        Self {
+            from,
+            to,
+            duration,
+            delay: 0,
+            ease: Ease::Linear,
+            repeat: Repeat::Never,
+            yoyo: false,
+        }
+    }
+
+    pub fn with_delay(self, delay: u32) -> Self {
+        Self { delay, ..self }
+    }
+
+    pub fn with_ease(self, ease: Ease) -> Self {
+        Self { ease, ..self }
+    }
+
+    pub fn with_repeat(self, repeat: Repeat) -> Self {
+        Self { repeat, ..self }
+    }
+
+    pub fn with_yoyo(self, yoyo: bool) -> Self {
+        Self { yoyo, ..self }
+    }
+
+    /// Number of cycles, or None when repeating forever
+    pub fn cycles(&self) -> Option<u32> {
+        match self.repeat {
+            Repeat::Never => Some(1),
+            Repeat::Times(n) => Some(n.saturating_add(1)),
+            Repeat::Forever => None,
+        }
+    }
+
+    /// Ticks from start to completion including the delay, or None when
+    /// repeating forever.
+    pub fn total_ticks(&self) -> Option<u32> {
+        self.cycles()
+            .map(|cycles| self.delay.saturating_add(self.duration.saturating_mul(cycles)))
+    }
+
+    /// Value at the given number of ticks after the tween was started
+    pub fn sample(&self, tick: u32) -> T {
+        if tick < self.delay {
+            return self.from;
+        }
+        let local = tick - self.delay;
+        let finished = match self.total_ticks() {
+            Some(total) => tick >= total,
+            None => false,
+        };
+        if finished || self.duration == 0 {
+            // Forever tweens of zero duration rest at their end value
+            let last_cycle = self.cycles().unwrap_or(1) - 1;
+            return if self.yoyo && last_cycle % 2 == 1 {
+                self.from
+            } else {
+                self.to
+            };
+        }
+        let cycle = local / self.duration;
+        let mut t = (local % self.duration) as f32 / self.duration as f32;
+        if self.yoyo && cycle % 2 == 1 {
+            t = 1.0 - t;
+        }
+        self.from.tween_lerp(self.to, self.ease.map(t))
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum TweenEventKind {
+    Started,
+    Repeated,
+    Completed,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub struct TweenEvent {
+    pub id: u32,
+    pub kind: TweenEventKind,
+    /// Timeline tick the event happened on
+    pub tick: u32,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+struct TimelineEntry<T> {
+    id: u32,
+    start: u32,
+    tween: Tween<T>,
+}
+
+impl<T: Tweenable> TimelineEntry<T> {
+    fn active_start(&self) -> u32 {
+        self.start.saturating_add(self.tween.delay)
+    }
+
+    fn end(&self) -> Option<u32> {
+        self.tween.total_ticks().map(|total| self.start.saturating_add(total))
+    }
+
+    /// Events in the tick span [prev, now) for starts and (prev, now] for
+    /// repeats and completion, so each fires exactly once.
+    fn events(&self, prev: u32, now: u32, out: &mut Vec<TweenEvent>) {
+        let started = self.active_start();
+        let end = self.end();
+        let event = |kind, tick| TweenEvent { id: self.id, kind, tick };
+        let did_start = prev <= started && started < now;
+        if did_start {
+            out.push(event(TweenEventKind::Started, started));
+        }
+        if self.tween.duration > 0 {
+            let duration = self.tween.duration;
+            // First cycle boundary after prev
+            let first = if prev < started.saturating_add(duration) {
+                1
+            } else {
+                (prev - started) / duration + 1
+            };
+            let mut cycle = first;
+            while let Some(tick) = duration.checked_mul(cycle).and_then(|d| started.checked_add(d)) {
+                if tick > now || end.is_some_and(|end| tick >= end) {
+                    break;
+                }
+                out.push(event(TweenEventKind::Repeated, tick));
+                cycle += 1;
+            }
+        }
+        if let Some(end) = end {
+            let reached = if end == started { did_start } else { prev < end && end <= now };
+            if reached {
+                out.push(event(TweenEventKind::Completed, end));
+            }
+        }
+    }
+}
+
+/// Tweens scheduled on a shared tick clock. Entries are identified by an id
+/// chosen by the caller, and several entries may share an id to animate the
+/// same property in sequence.
+#[derive(Debug, Clone)]
+pub struct Timeline<T> {
+    entries: Vec<TimelineEntry<T>>,
+    events: Vec<TweenEvent>,
+    group_start: u32,
+    group_end: u32,
+    now: u32,
+}
+
+impl<T: Tweenable> Default for Timeline<T> {
+    fn default() -> Self {
+        Self {
+            entries: Vec::new(),
+            events: Vec::new(),
+            group_start: 0,
+            group_end: 0,
+            now: 0,
+        }
+    }
+}
+
+impl<T: Tweenable> Timeline<T> {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Schedules a tween at an explicit tick
+    pub fn insert(&mut self, id: u32, start: u32, tween: Tween<T>) {
+        let entry = TimelineEntry { id, start, tween };
+        self.group_end = self.group_end.max(entry.end().unwrap_or(u32::MAX));
+        self.entries.push(entry);
+    }
+
+    /// Starts a new group once every tween added so far has completed
+    pub fn then(&mut self, id: u32, tween: Tween<T>) -> &mut Self {
+        self.group_start = self.group_end;
+        self.insert(id, self.group_start, tween);
+        self
+    }
+
+    /// Runs alongside the group started by the last call to then
+    pub fn with(&mut self, id: u32, tween: Tween<T>) -> &mut Self {
+        self.insert(id, self.group_start, tween);
+        self
+    }
+
+    pub fn now(&self) -> u32 {
+        self.now
+    }
+
+    /// Total length in ticks, or None if any tween repeats forever
+    pub fn duration(&self) -> Option<u32> {
+        self.entries
+            .iter()
+            .try_fold(0, |acc, entry| entry.end().map(|end| acc.max(end)))
+    }
+
+    pub fn is_finished(&self) -> bool {
+        self.duration().is_some_and(|duration| self.now >= duration)
+    }
+
+    pub fn tick(&mut self) {
+        self.advance(1);
+    }
+
+    /// Moves the clock forward and queues events in tick order, with ties
+    /// broken by insertion order.
+    pub fn advance(&mut self, ticks: u32) {
+        let prev = self.now;
+        let now = prev.saturating_add(ticks);
+        let first = self.events.len();
+        for entry in self.entries.iter() {
+            entry.events(prev, now, &mut self.events);
+        }
+        self.events[first..].sort_by_key(|event| event.tick);
+        self.now = now;
+    }
+
+    /// Removes and returns queued events
+    pub fn drain_events(&mut self) -> impl Iterator<Item = TweenEvent> + '_ {
+        self.events.drain(..)
+    }
+
+    /// Current value of the latest started tween with the given id, or the
+    /// starting value of the earliest one if none have started.
+    pub fn value(&self, id: u32) -> Option<T> {
+        let entries = || self.entries.iter().filter(|entry| entry.id == id);
+        let current = entries()
+            .filter(|entry| entry.start <= self.now)
+            .max_by_key(|entry| entry.start)
+            .or_else(|| entries().min_by_key(|entry| entry.start));
+        current.map(|entry| entry.tween.sample(self.now.saturating_sub(entry.start)))
+    }
+
+    /// Current value of every id in first insertion order
+    pub fn values(&self) -> Vec<(u32, T)> {
+        let mut ids: Vec<u32> = Vec::new();
+        for entry in self.entries.iter() {
+            if !ids.contains(&entry.id) {
+                ids.push(entry.id);
+            }
+        }
+        ids.into_iter()
+            .filter_map(|id| self.value(id).map(|value| (id, value)))
+            .collect()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn basic_tween() {
+        let tween = Tween::new(0.0, 10.0, 10);
+        assert_eq!(tween.sample(0), 0.0);
+        assert_eq!(tween.sample(5), 5.0);
+        assert_eq!(tween.sample(10), 10.0);
+        assert_eq!(tween.sample(100), 10.0);
+        assert_eq!(tween.total_ticks(), Some(10));
+    }
+
+    #[test]
+    fn delay_and_ease() {
+        let tween = Tween::new(Vec2::ZERO, Vec2::ONE, 4)
+            .with_delay(2)
+            .with_ease(Ease::QuadIn);
+        assert_eq!(tween.sample(1), Vec2::ZERO);
+        assert_eq!(tween.sample(2), Vec2::ZERO);
+        assert_eq!(tween.sample(4), Vec2::splat(0.25));
+        assert_eq!(tween.total_ticks(), Some(6));
+    }
+
+    #[test]
+    fn repeat_and_yoyo() {
+        let tween = Tween::new(0.0, 4.0, 4).with_repeat(Repeat::Times(1)).with_yoyo(true);
+        assert_eq!(tween.total_ticks(), Some(8));
+        assert_eq!(tween.sample(3), 3.0);
+        assert_eq!(tween.sample(4), 4.0);
+        assert_eq!(tween.sample(5), 3.0);
+        assert_eq!(tween.sample(8), 0.0);
+        let forever = Tween::new(0.0, 4.0, 4).with_repeat(Repeat::Forever);
+        assert_eq!(forever.total_ticks(), None);
+        assert_eq!(forever.sample(4001), 1.0);
+    }
+
+    #[test]
+    fn timeline_sequence_and_parallel() {
+        let mut timeline = Timeline::new();
+        timeline
+            .then(0, Tween::new(0.0, 1.0, 4))
+            .with(1, Tween::new(0.0, 2.0, 2))
+            .then(0, Tween::new(1.0, 0.0, 4));
+        assert_eq!(timeline.duration(), Some(8));
+        assert_eq!(timeline.values(), vec![(0, 0.0), (1, 0.0)]);
+
+        timeline.advance(2);
+        assert_eq!(timeline.value(0), Some(0.5));
+        assert_eq!(timeline.value(1), Some(2.0));
+        let events: Vec<_> = timeline.drain_events().collect();
+        assert_eq!(
+            events,
+            vec![
+                TweenEvent { id: 0, kind: TweenEventKind::Started, tick: 0 },
+                TweenEvent { id: 1, kind: TweenEventKind::Started, tick: 0 },
+                TweenEvent { id: 1, kind: TweenEventKind::Completed, tick: 2 },
+            ]
+        );
+
+        timeline.advance(4);
+        assert_eq!(timeline.value(0), Some(0.5));
+        let events: Vec<_> = timeline.drain_events().collect();
+        assert_eq!(
+            events,
+            vec![
+                TweenEvent { id: 0, kind: TweenEventKind::Completed, tick: 4 },
+                TweenEvent { id: 0, kind: TweenEventKind::Started, tick: 4 },
+            ]
+        );
+        assert!(!timeline.is_finished());
+        for _ in 0..2 {
+            timeline.tick();
+        }
+        assert!(timeline.is_finished());
+        assert_eq!(timeline.value(0), Some(0.0));
+    }
+
+    #[test]
+    fn repeated_events() {
+        let mut timeline = Timeline::new();
+        timeline.insert(7, 1, Tween::new(Vec3::ZERO, Vec3::X, 3).with_repeat(Repeat::Times(2)));
+        let mut kinds = Vec::new();
+        for _ in 0..12 {
+            timeline.tick();
+            kinds.extend(timeline.drain_events().map(|event| (event.tick, event.kind)));
+        }
+        assert_eq!(
+            kinds,
+            vec![
+                (1, TweenEventKind::Started),
+                (4, TweenEventKind::Repeated),
+                (7, TweenEventKind::Repeated),
+                (10, TweenEventKind::Completed),
+            ]
+        );
+    }
+
+    #[test]
+    fn deterministic_in_any_step_size() {
+        let build = || {
+            let mut timeline = Timeline::new();
+            timeline
+                .then(0, Tween::new(0.0, 1.0, 5).with_repeat(Repeat::Times(3)))
+                .with(1, Tween::new(0.0, 1.0, 7).with_delay(3))
+                .then(2, Tween::new(0.0, 1.0, 0));
+            timeline
+        };
+        let mut a = build();
+        let mut b = build();
+        a.advance(30);
+        for _ in 0..30 {
+            b.tick();
+        }
+        let a_events: Vec<_> = a.drain_events().collect();
+        let b_events: Vec<_> = b.drain_events().collect();
+        assert_eq!(a_events, b_events);
+        assert_eq!(a_events.len(), 9);
+    }
+}
@@ -0,0 +1,466 @@
+use glam::{IVec2, IVec3, Vec3};
+
+use crate::ray::Ray3;
+
+/// Cells of a Bresenham line from a to b, including both ends
+pub fn bresenham(a: IVec2, b: IVec2) -> BresenhamIter {
+    let d = (b - a).abs();
+    BresenhamIter {
+        pos: a,
+        end: b,
+        step: (b - a).signum(),
+        d: IVec2::new(d.x, -d.y),
+        err: d.x - d.y,
+        done: false,
+    }
+}
+
+pub struct BresenhamIter {
+    pos: IVec2,
+    end: IVec2,
+    step: IVec2,
+    d: IVec2,
+    err: i32,
+    done: bool,
+}
+
+impl Iterator for BresenhamIter {
+    type Item = IVec2;
+
+    fn next(&mut self) -> Option<IVec2> {
+        if self.done {
+            return None;
+        }
+        let result = self.pos;
+        if self.pos == self.end {
+            self.done = true;
+            return Some(result);
+        }
+        let e2 = 2 * self.err;
+        if e2 >= self.d.y {
+            self.err += self.d.y;
+            self.pos.x += self.step.x;
+        }
+        if e2 <= self.d.x {
+            self.err += self.d.x;
+            self.pos.y += self.step.y;
+        }
+        Some(result)
+    }
+}
+
+/// Every cell touched by the segment between the centers of a and b,
+/// including both ends. Where the segment passes exactly through a corner,
+/// both side cells are visited before the diagonal one, x side first.
+pub fn supercover(a: IVec2, b: IVec2) -> SupercoverIter {
+    SupercoverIter {
+        pos: a,
+        n: (b - a).abs(),
+        step: (b - a).signum(),
+        i: IVec2::ZERO,
+        queue: [a; 3],
+        queued: 1,
+        taken: 0,
+    }
+}
+
+pub struct SupercoverIter {
+    pos: IVec2,
+    n: IVec2,
+    step: IVec2,
+    i: IVec2,
+    queue: [IVec2; 3],
+    queued: usize,
+    taken: usize,
+}
+
+impl Iterator for SupercoverIter {
+    type Item = IVec2;
+
+    fn next(&mut self) -> Option<IVec2> {
+        if self.taken == self.queued {
+            if self.i.x >= self.n.x && self.i.y >= self.n.y {
+                return None;
+            }
+            // Compares where the segment crosses the next vertical and
+            // horizontal cell boundaries, scaled to stay in integers.
+            let cmp = (1 + 2 * self.i.x) * self.n.y - (1 + 2 * self.i.y) * self.n.x;
+            if cmp == 0 {
+                self.queue[0] = self.pos + IVec2::new(self.step.x, 0);
+                self.queue[1] = self.pos + IVec2::new(0, self.step.y);
+                self.pos += self.step;
+                self.i += IVec2::ONE;
+                self.queue[2] = self.pos;
+                self.queued = 3;
+            } else {
+                if cmp < 0 {
+                    self.pos.x += self.step.x;
+                    self.i.x += 1;
+                } else {
+                    self.pos.y += self.step.y;
+                    self.i.y += 1;
+                }
+                self.queue[0] = self.pos;
+                self.queued = 1;
+            }
+            self.taken = 0;
+        }
+        self.taken += 1;
+        Some(self.queue[self.taken - 1])
+    }
+}
+
+/// True if no cell strictly between a and b blocks, walking the supercover
+/// line so that the test is symmetric and never slips between diagonal walls.
+pub fn has_line_of_sight(a: IVec2, b: IVec2, blocks: impl Fn(IVec2) -> bool) -> bool {
+    supercover(a, b).filter(|p| *p != a && *p != b).all(|p| !blocks(p))
+}
+
+/// Slope as a fraction with a positive denominator
+#[derive(Clone, Copy)]
+struct Slope {
+    num: i32,
+    den: i32,
+}
+
+#[derive(Clone, Copy)]
+struct Row {
+    depth: i32,
+    start: Slope,
+    end: Slope,
+}
+
+impl Row {
+    /// Columns from depth * start rounded half up to depth * end rounded
+    /// half down
+    fn columns(&self) -> std::ops::RangeInclusive<i32> {
+        let min = (2 * self.depth * self.start.num + self.start.den).div_euclid(2 * self.start.den);
+        let max = -(self.end.den - 2 * self.depth * self.end.num).div_euclid(2 * self.end.den);
+        min..=max
+    }
+
+    fn is_symmetric(&self, col: i32) -> bool {
+        col * self.start.den >= self.depth * self.start.num
+            && col * self.end.den <= self.depth * self.end.num
+    }
+}
+
+/// Symmetric shadowcasting, where a cell is visible from the origin exactly
+/// when the origin is visible from it. Walls bordering visible floor are
+/// revealed too. Cells on quadrant borders may be revealed more than once.
+/// https://www.albertford.com/shadowcasting/
+pub fn field_of_view(
+    origin: IVec2,
+    radius: i32,
+    blocks: impl Fn(IVec2) -> bool,
+    mut reveal: impl FnMut(IVec2),
+) {
+    reveal(origin);
+    let radius_sqr = radius * radius;
+    // Maps (depth, column) to a cell for each of the four quadrants
+    let quadrants: [fn(i32, i32) -> IVec2; 4] = [
+        |depth, col| IVec2::new(col, depth),
+        |depth, col| IVec2::new(depth, col),
+        |depth, col| IVec2::new(col, -depth),
+        |depth, col| IVec2::new(-depth, col),
+    ];
+    for transform in quadrants {
+        let cell = |depth: i32, col: i32| origin + transform(depth, col);
+        let mut rows = vec![Row {
+            depth: 1,
+            start: Slope { num: -1, den: 1 },
+            end: Slope { num: 1, den: 1 },
+        }];
+        while let Some(mut row) = rows.pop() {
+            if row.depth > radius {
+                continue;
+            }
+            let mut prev_wall: Option<bool> = None;
+            for col in row.columns() {
+                let p = cell(row.depth, col);
+                let wall = blocks(p);
+                let in_radius = row.depth * row.depth + col * col <= radius_sqr;
+                if in_radius && (wall || row.is_symmetric(col)) {
+                    reveal(p);
+                }
+                let slope = Slope {
+                    num: 2 * col - 1,
+                    den: 2 * row.depth,
+                };
+                if prev_wall == Some(true) && !wall {
+                    row.start = slope;
+                }
+                if prev_wall == Some(false) && wall {
+                    rows.push(Row {
+                        depth: row.depth + 1,
+                        start: row.start,
+                        end: slope,
+                    });
+                }
+                prev_wall = Some(wall);
+            }
+            if prev_wall == Some(false) {
+                rows.push(Row {
+                    depth: row.depth + 1,
+                    ..row
+                });
+            }
+        }
+    }
+}
+
+/// Sorted cells visible from the origin, see field_of_view
+pub fn visible_cells(origin: IVec2, radius: i32, blocks: impl Fn(IVec2) -> bool) -> Vec<IVec2> {
+    let mut cells = Vec::new();
+    field_of_view(origin, radius, blocks, |p| cells.push(p));
+    cells.sort_by_key(|p| (p.y, p.x));
+    cells.dedup();
+    cells
+}
+
+/// Cell entered while walking a ray through a unit voxel grid
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct VoxelStep {
+    pub cell: IVec3,
+    /// Ray parameter where the ray enters the cell
+    pub t: f32,
+    /// Face the ray entered through, or zero for the starting cell
+    pub normal: IVec3,
+}
+
+/// Walks the unit cells crossed by a ray in order, up to max_t. Scale the
+/// ray to walk grids with other cell sizes.
+/// http://www.cse.yorku.ca/~amana/research/grid.pdf
+pub fn voxel_traversal(ray: &Ray3, max_t: f32) -> VoxelIter {
+    let cell = ray.origin.floor().as_ivec3();
+    let step = ray.dir.signum().as_ivec3();
+    let axis = |o: f32, d: f32, c: i32| {
+        if d > 0.0 {
+            ((c + 1) as f32 - o) / d
+        } else if d < 0.0 {
+            (c as f32 - o) / d
+        } else {
+            f32::INFINITY
+        }
+    };
+    let t_max = Vec3::new(
+        axis(ray.origin.x, ray.dir.x, cell.x),
+        axis(ray.origin.y, ray.dir.y, cell.y),
+        axis(ray.origin.z, ray.dir.z, cell.z),
+    );
+    let t_delta = Vec3::new(
+        (1.0 / ray.dir.x).abs(),
+        (1.0 / ray.dir.y).abs(),
+        (1.0 / ray.dir.z).abs(),
+    );
+    VoxelIter {
+        next: Some(VoxelStep {
+            cell,
+            t: 0.0,
+            normal: IVec3::ZERO,
+        }),
+        step: IVec3::new(
+            if ray.dir.x == 0.0 { 0 } else { step.x },
+            if ray.dir.y == 0.0 { 0 } else { step.y },
+            if ray.dir.z == 0.0 { 0 } else { step.z },
+        ),
+        t_max,
+        t_delta,
+        max_t,
+    }
+}
+
+pub struct VoxelIter {
+    next: Option<VoxelStep>,
+    step: IVec3,
+    t_max: Vec3,
+    t_delta: Vec3,
+    max_t: f32,
+}
+
+impl Iterator for VoxelIter {
+    type Item = VoxelStep;
+
+    fn next(&mut self) -> Option<VoxelStep> {
+        let current = self.next.take()?;
+        if current.t > self.max_t {
+            return None;
+        }
+        let mut cell = current.cell;
+        let (t, normal) = if self.t_max.x <= self.t_max.y && self.t_max.x <= self.t_max.z {
+            let t = self.t_max.x;
+            cell.x += self.step.x;
+            self.t_max.x += self.t_delta.x;
+            (t, IVec3::new(-self.step.x, 0, 0))
+        } else if self.t_max.y <= self.t_max.z {
+            let t = self.t_max.y;
+            cell.y += self.step.y;
+            self.t_max.y += self.t_delta.y;
+            (t, IVec3::new(0, -self.step.y, 0))
+        } else {
+            let t = self.t_max.z;
+            cell.z += self.step.z;
+            self.t_max.z += self.t_delta.z;
+            (t, IVec3::new(0, 0, -self.step.z))
+        };
+        if t.is_finite() {
+            self.next = Some(VoxelStep { cell, t, normal });
+        }
+        Some(current)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn cells(iter: impl Iterator<Item = IVec2>) -> Vec<(i32, i32)> {
+        iter.map(|p| (p.x, p.y)).collect()
+    }
+
+    /// '#' blocks, row 0 is the bottom line
+    fn walls<'a>(map: &'a [&'a str]) -> impl Fn(IVec2) -> bool + 'a {
+        move |p: IVec2| {
+            let row = map.len() as i32 - 1 - p.y;
+            if row < 0 || row >= map.len() as i32 || p.x < 0 || p.x >= map[0].len() as i32 {
+                return true;
+            }
+            map[row as usize].as_bytes()[p.x as usize] == b'#'
+        }
+    }
+
+    #[test]
+    fn bresenham_lines() {
+        let o = IVec2::ZERO;
+        assert_eq!(cells(bresenham(o, o)), vec![(0, 0)]);
+        assert_eq!(cells(bresenham(o, IVec2::new(3, 0))), vec![(0, 0), (1, 0), (2, 0), (3, 0)]);
+        assert_eq!(cells(bresenham(o, IVec2::new(-2, -2))), vec![(0, 0), (-1, -1), (-2, -2)]);
+        assert_eq!(
+            cells(bresenham(o, IVec2::new(4, 2))),
+            vec![(0, 0), (1, 1), (2, 1), (3, 2), (4, 2)]
+        );
+        for b in [IVec2::new(7, -3), IVec2::new(-5, 9), IVec2::new(0, -6)] {
+            let line: Vec<IVec2> = bresenham(o, b).collect();
+            assert_eq!(line.len() as i32, b.abs().max_element() + 1);
+            assert!(line.windows(2).all(|w| (w[1] - w[0]).abs().max_element() == 1));
+        }
+    }
+
+    #[test]
+    fn supercover_lines() {
+        let o = IVec2::ZERO;
+        assert_eq!(cells(supercover(o, o)), vec![(0, 0)]);
+        assert_eq!(
+            cells(supercover(o, IVec2::new(2, 2))),
+            vec![(0, 0), (1, 0), (0, 1), (1, 1), (2, 1), (1, 2), (2, 2)]
+        );
+        assert_eq!(
+            cells(supercover(o, IVec2::new(3, 1))),
+            vec![(0, 0), (1, 0), (2, 0), (1, 1), (2, 1), (3, 1)]
+        );
+        assert_eq!(
+            cells(supercover(o, IVec2::new(4, 1))),
+            vec![(0, 0), (1, 0), (2, 0), (2, 1), (3, 1), (4, 1)]
+        );
+        for b in [IVec2::new(7, -3), IVec2::new(-5, 9), IVec2::new(-4, 0)] {
+            let line: Vec<IVec2> = supercover(o, b).collect();
+            assert_eq!(line.last(), Some(&b));
+            assert!(line.windows(2).all(|w| (w[1] - w[0]).abs().max_element() == 1));
+        }
+    }
+
+    #[test]
+    fn line_of_sight() {
+        let map = ["....", ".#..", "....", "#..."];
+        let blocks = walls(&map);
+        assert!(has_line_of_sight(IVec2::new(0, 1), IVec2::new(3, 1), &blocks));
+        assert!(!has_line_of_sight(IVec2::new(0, 2), IVec2::new(2, 2), |p| p == IVec2::new(1, 2)));
+        assert!(!has_line_of_sight(IVec2::new(0, 3), IVec2::new(2, 1), &blocks));
+        // Endpoints may be walls
+        assert!(has_line_of_sight(IVec2::new(0, 1), IVec2::new(1, 2), &blocks));
+        for a in [IVec2::new(0, 3), IVec2::new(3, 0), IVec2::new(2, 3)] {
+            for b in [IVec2::new(0, 1), IVec2::new(3, 3), IVec2::new(2, 0)] {
+                assert_eq!(has_line_of_sight(a, b, &blocks), has_line_of_sight(b, a, &blocks));
+            }
+        }
+    }
+
+    #[test]
+    fn fov_room() {
+        let map = [
+            "#########",
+            "#.......#",
+            "#.......#",
+            "#...#...#",
+            "#.......#",
+            "#.......#",
+            "#########",
+        ];
+        let blocks = walls(&map);
+        let origin = IVec2::new(4, 1);
+        let visible = visible_cells(origin, 20, &blocks);
+        // Entire room except the shadow behind the pillar
+        assert!(visible.contains(&IVec2::new(1, 5)));
+        assert!(visible.contains(&IVec2::new(4, 3)));
+        assert!(!visible.contains(&IVec2::new(4, 4)));
+        assert!(!visible.contains(&IVec2::new(4, 5)));
+        assert!(visible.contains(&IVec2::new(0, 0)));
+        assert!(visible.contains(&IVec2::new(8, 6)));
+        assert!(visible.iter().all(|p| !blocks(*p) || map[6 - p.y as usize].as_bytes()[p.x as usize] == b'#'));
+    }
+
+    #[test]
+    fn fov_symmetric_and_radius() {
+        let map = [
+            "..........",
+            "..#....#..",
+            "....#.....",
+            ".#.....#..",
+            "......#...",
+            "..#.......",
+            "..........",
+        ];
+        let blocks = walls(&map);
+        let floor: Vec<IVec2> = (0..7)
+            .flat_map(|y| (0..10).map(move |x| IVec2::new(x, y)))
+            .filter(|p| !blocks(*p))
+            .collect();
+        for a in floor.iter() {
+            let from_a = visible_cells(*a, 30, &blocks);
+            for b in floor.iter() {
+                let b_sees_a = visible_cells(*b, 30, &blocks).contains(a);
+                assert_eq!(from_a.contains(b), b_sees_a, "{a} {b}");
+            }
+        }
+        let open = visible_cells(IVec2::ZERO, 3, |_| false);
+        assert!(open.iter().all(|p| p.length_squared() <= 9));
+        assert!(open.contains(&IVec2::new(0, 3)) && open.contains(&IVec2::new(-2, -2)));
+        assert!(!open.contains(&IVec2::new(3, 1)));
+    }
+
+    #[test]
+    fn voxel_walk() {
+        let ray = Ray3::new(Vec3::new(0.5, 0.5, 0.5), Vec3::new(1.0, 0.0, 0.0));
+        let steps: Vec<VoxelStep> = voxel_traversal(&ray, 3.0).collect();
+        assert_eq!(
+            steps.iter().map(|s| s.cell).collect::<Vec<_>>(),
+            vec![IVec3::ZERO, IVec3::X, IVec3::new(2, 0, 0), IVec3::new(3, 0, 0)]
+        );
+        assert_eq!(steps[1].t, 0.5);
+        assert_eq!(steps[1].normal, IVec3::new(-1, 0, 0));
+
+        let dir = Vec3::new(-1.0, 2.0, 0.5).normalize();
+        let ray = Ray3::new(Vec3::new(2.2, -0.3, 1.7), dir);
+        let steps: Vec<VoxelStep> = voxel_traversal(&ray, 10.0).collect();
+        for pair in steps.windows(2) {
+            let d = pair[1].cell - pair[0].cell;
+            assert_eq!(d.abs().element_sum(), 1);
+            assert_eq!(d, -pair[1].normal);
+            assert!(pair[1].t >= pair[0].t);
+            // The entry point lies on the shared face
+            let p = ray.at(pair[1].t + 1e-4);
+            assert_eq!(p.floor().as_ivec3(), pair[1].cell);
+        }
+        assert!(steps.last().unwrap().t <= 10.0);
+    }
+}
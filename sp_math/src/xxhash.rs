@@ -2,7 +2,7 @@ use std::num::Wrapping;
 
 use glam::{IVec2, IVec3};
 
-const _PRIME32_1: Wrapping<u32> = Wrapping(2654435761u32);
+const PRIME32_1: Wrapping<u32> = Wrapping(2654435761u32);
 const PRIME32_2: Wrapping<u32> = Wrapping(2246822519u32);
 const PRIME32_3: Wrapping<u32> = Wrapping(3266489917u32);
 const PRIME32_4: Wrapping<u32> = Wrapping(668265263u32);
@@ -89,4 +89,334 @@ pub fn hash_ivec2(seed: u32, v: IVec2) -> u32 {
 
 pub fn hash_ivec3(seed: u32, v: IVec3) -> u32 {
     hash3_i32(seed, v.x, v.y, v.z)
-}
\ No newline at end of file
+}
+
+/// Reference XXH32 of a byte slice
+pub fn xxh32(seed: u32, bytes: &[u8]) -> u32 {
+    let mut h = Xxh32Hasher::with_seed(seed);
+    h.update(bytes);
+    h.digest()
+}
+
+/// Reference XXH64 of a byte slice
+pub fn xxh64(seed: u64, bytes: &[u8]) -> u64 {
+    let mut h = Xxh64Hasher::with_seed(seed);
+    h.update(bytes);
+    h.digest()
+}
+
+fn read_u32(bytes: &[u8]) -> u32 {
+    u32::from_le_bytes(bytes[..4].try_into().unwrap())
+}
+
+fn read_u64(bytes: &[u8]) -> u64 {
+    u64::from_le_bytes(bytes[..8].try_into().unwrap())
+}
+
+/// Streaming XXH32. As a std Hasher the 32-bit digest is widened to u64.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Xxh32Hasher {
+    seed: u32,
+    acc: [u32; 4],
+    buffer: [u8; 16],
+    buffered: usize,
+    len: u64,
+}
+
+impl Xxh32Hasher {
+    pub fn with_seed(seed: u32) -> Self {
+        let (p1, p2) = (PRIME32_1.0, PRIME32_2.0);
+        Self {
+            seed,
+            acc: [
+                seed.wrapping_add(p1).wrapping_add(p2),
+                seed.wrapping_add(p2),
+                seed,
+                seed.wrapping_sub(p1),
+            ],
+            buffer: [0; 16],
+            buffered: 0,
+            len: 0,
+        }
+    }
+
+    fn round(acc: u32, input: u32) -> u32 {
+        acc.wrapping_add(input.wrapping_mul(PRIME32_2.0))
+            .rotate_left(13)
+            .wrapping_mul(PRIME32_1.0)
+    }
+
+    fn stripe(&mut self, stripe: &[u8]) {
+        for (i, acc) in self.acc.iter_mut().enumerate() {
+            *acc = Self::round(*acc, read_u32(&stripe[i * 4..]));
+        }
+    }
+
+    pub fn update(&mut self, mut bytes: &[u8]) {
+        self.len += bytes.len() as u64;
+        if self.buffered > 0 {
+            let take = bytes.len().min(16 - self.buffered);
+            self.buffer[self.buffered..self.buffered + take].copy_from_slice(&bytes[..take]);
+            self.buffered += take;
+            bytes = &bytes[take..];
+            if self.buffered < 16 {
+                return;
+            }
+            let buffer = self.buffer;
+            self.stripe(&buffer);
+            self.buffered = 0;
+        }
+        let mut stripes = bytes.chunks_exact(16);
+        for stripe in stripes.by_ref() {
+            self.stripe(stripe);
+        }
+        let rest = stripes.remainder();
+        self.buffer[..rest.len()].copy_from_slice(rest);
+        self.buffered = rest.len();
+    }
+
+    pub fn digest(&self) -> u32 {
+        let [v1, v2, v3, v4] = self.acc;
+        let mut h = if self.len >= 16 {
+            v1.rotate_left(1)
+                .wrapping_add(v2.rotate_left(7))
+                .wrapping_add(v3.rotate_left(12))
+                .wrapping_add(v4.rotate_left(18))
+        } else {
+            self.seed.wrapping_add(PRIME32_5.0)
+        };
+        h = h.wrapping_add(self.len as u32);
+        let mut rest = &self.buffer[..self.buffered];
+        while rest.len() >= 4 {
+            h = h.wrapping_add(read_u32(rest).wrapping_mul(PRIME32_3.0));
+            h = h.rotate_left(17).wrapping_mul(PRIME32_4.0);
+            rest = &rest[4..];
+        }
+        for byte in rest {
+            h = h.wrapping_add((*byte as u32).wrapping_mul(PRIME32_5.0));
+            h = h.rotate_left(11).wrapping_mul(PRIME32_1.0);
+        }
+        finalize(h)
+    }
+}
+
+impl Default for Xxh32Hasher {
+    fn default() -> Self {
+        Self::with_seed(0)
+    }
+}
+
+impl std::hash::Hasher for Xxh32Hasher {
+    fn write(&mut self, bytes: &[u8]) {
+        self.update(bytes);
+    }
+
+    fn finish(&self) -> u64 {
+        self.digest() as u64
+    }
+}
+
+const PRIME64_1: u64 = 0x9E3779B185EBCA87;
+const PRIME64_2: u64 = 0xC2B2AE3D27D4EB4F;
+const PRIME64_3: u64 = 0x165667B19E3779F9;
+const PRIME64_4: u64 = 0x85EBCA77C2B2AE63;
+const PRIME64_5: u64 = 0x27D4EB2F165667C5;
+
+/// Streaming XXH64
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Xxh64Hasher {
+    seed: u64,
+    acc: [u64; 4],
+    buffer: [u8; 32],
+    buffered: usize,
+    len: u64,
+}
+
+impl Xxh64Hasher {
+    pub fn with_seed(seed: u64) -> Self {
+        Self {
+            seed,
+            acc: [
+                seed.wrapping_add(PRIME64_1).wrapping_add(PRIME64_2),
+                seed.wrapping_add(PRIME64_2),
+                seed,
+                seed.wrapping_sub(PRIME64_1),
+            ],
+            buffer: [0; 32],
+            buffered: 0,
+            len: 0,
+        }
+    }
+
+    fn round(acc: u64, input: u64) -> u64 {
+        acc.wrapping_add(input.wrapping_mul(PRIME64_2))
+            .rotate_left(31)
+            .wrapping_mul(PRIME64_1)
+    }
+
+    fn merge_round(acc: u64, value: u64) -> u64 {
+        (acc ^ Self::round(0, value))
+            .wrapping_mul(PRIME64_1)
+            .wrapping_add(PRIME64_4)
+    }
+
+    fn stripe(&mut self, stripe: &[u8]) {
+        for (i, acc) in self.acc.iter_mut().enumerate() {
+            *acc = Self::round(*acc, read_u64(&stripe[i * 8..]));
+        }
+    }
+
+    pub fn update(&mut self, mut bytes: &[u8]) {
+        self.len += bytes.len() as u64;
+        if self.buffered > 0 {
+            let take = bytes.len().min(32 - self.buffered);
+            self.buffer[self.buffered..self.buffered + take].copy_from_slice(&bytes[..take]);
+            self.buffered += take;
+            bytes = &bytes[take..];
+            if self.buffered < 32 {
+                return;
+            }
+            let buffer = self.buffer;
+            self.stripe(&buffer);
+            self.buffered = 0;
+        }
+        let mut stripes = bytes.chunks_exact(32);
+        for stripe in stripes.by_ref() {
+            self.stripe(stripe);
+        }
+        let rest = stripes.remainder();
+        self.buffer[..rest.len()].copy_from_slice(rest);
+        self.buffered = rest.len();
+    }
+
+    pub fn digest(&self) -> u64 {
+        let [v1, v2, v3, v4] = self.acc;
+        let mut h = if self.len >= 32 {
+            let h = v1
+                .rotate_left(1)
+                .wrapping_add(v2.rotate_left(7))
+                .wrapping_add(v3.rotate_left(12))
+                .wrapping_add(v4.rotate_left(18));
+            self.acc.iter().fold(h, |h, v| Self::merge_round(h, *v))
+        } else {
+            self.seed.wrapping_add(PRIME64_5)
+        };
+        h = h.wrapping_add(self.len);
+        let mut rest = &self.buffer[..self.buffered];
+        while rest.len() >= 8 {
+            h ^= Self::round(0, read_u64(rest));
+            h = h.rotate_left(27).wrapping_mul(PRIME64_1).wrapping_add(PRIME64_4);
+            rest = &rest[8..];
+        }
+        if rest.len() >= 4 {
+            h ^= (read_u32(rest) as u64).wrapping_mul(PRIME64_1);
+            h = h.rotate_left(23).wrapping_mul(PRIME64_2).wrapping_add(PRIME64_3);
+            rest = &rest[4..];
+        }
+        for byte in rest {
+            h ^= (*byte as u64).wrapping_mul(PRIME64_5);
+            h = h.rotate_left(11).wrapping_mul(PRIME64_1);
+        }
+        h ^= h >> 33;
+        h = h.wrapping_mul(PRIME64_2);
+        h ^= h >> 29;
+        h = h.wrapping_mul(PRIME64_3);
+        h ^ (h >> 32)
+    }
+}
+
+impl Default for Xxh64Hasher {
+    fn default() -> Self {
+        Self::with_seed(0)
+    }
+}
+
+impl std::hash::Hasher for Xxh64Hasher {
+    fn write(&mut self, bytes: &[u8]) {
+        self.update(bytes);
+    }
+
+    fn finish(&self) -> u64 {
+        self.digest()
+    }
+}
+
+/// Builds seeded Xxh32Hashers for HashMap, IndexMap and the like
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq)]
+pub struct Xxh32BuildHasher {
+    pub seed: u32,
+}
+
+impl std::hash::BuildHasher for Xxh32BuildHasher {
+    type Hasher = Xxh32Hasher;
+
+    fn build_hasher(&self) -> Xxh32Hasher {
+        Xxh32Hasher::with_seed(self.seed)
+    }
+}
+
+/// Builds seeded Xxh64Hashers for HashMap, IndexMap and the like
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq)]
+pub struct Xxh64BuildHasher {
+    pub seed: u64,
+}
+
+impl std::hash::BuildHasher for Xxh64BuildHasher {
+    type Hasher = Xxh64Hasher;
+
+    fn build_hasher(&self) -> Xxh64Hasher {
+        Xxh64Hasher::with_seed(self.seed)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::collections::HashMap;
+    use std::hash::BuildHasher;
+
+    const SPAM: &[u8] = b"Nobody inspects the spammish repetition";
+
+    #[test]
+    fn reference_vectors() {
+        assert_eq!(xxh32(0, b""), 0x02CC5D05);
+        assert_eq!(xxh32(0, b"a"), 0x550D7456);
+        assert_eq!(xxh32(0, b"abc"), 0x32D153FF);
+        assert_eq!(xxh32(0, SPAM), 0xE2293B2F);
+        assert_eq!(xxh64(0, b""), 0xEF46DB3751D8E999);
+        assert_eq!(xxh64(0, b"a"), 0xD24EC4F1A98C6E5B);
+        assert_eq!(xxh64(0, b"abc"), 0x44BC2CF5AD770999);
+        assert_eq!(xxh64(0, SPAM), 0xFBCEA83C8A378BF1);
+    }
+
+    #[test]
+    fn streaming_matches_one_shot() {
+        let data: Vec<u8> = (0..200u32).map(|i| (i * 7 + 3) as u8).collect();
+        for len in [0, 3, 15, 16, 17, 31, 32, 33, 64, 100, 200] {
+            let bytes = &data[..len];
+            for split in [0, 1, 5, len / 2, len] {
+                let (a, b) = bytes.split_at(split.min(len));
+                let mut h32 = Xxh32Hasher::with_seed(9);
+                h32.update(a);
+                h32.update(b);
+                assert_eq!(h32.digest(), xxh32(9, bytes));
+                let mut h64 = Xxh64Hasher::with_seed(9);
+                h64.update(a);
+                h64.update(b);
+                assert_eq!(h64.digest(), xxh64(9, bytes));
+            }
+        }
+    }
+
+    #[test]
+    fn build_hashers() {
+        assert_ne!(xxh64(1, SPAM), xxh64(0, SPAM));
+        let a = Xxh64BuildHasher { seed: 5 };
+        assert_eq!(a.hash_one((1, "x")), a.hash_one((1, "x")));
+        assert_ne!(a.hash_one((1, "x")), Xxh64BuildHasher::default().hash_one((1, "x")));
+
+        let mut map: HashMap<u32, &str, Xxh32BuildHasher> = HashMap::default();
+        map.insert(7, "seven");
+        assert_eq!(map[&7], "seven");
+    }
+}
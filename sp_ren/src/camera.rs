@@ -1,4 +1,5 @@
 use glam::{Mat4, Vec3};
+use sp_math::frustum::Frustum;
 
 use crate::binding::PodBuffer;
 
@@ -33,6 +34,10 @@ impl CameraParams {
     pub fn proj_view(&self) -> Mat4 {
         self.proj * self.view
     }
+
+    pub fn frustum(&self) -> Frustum {
+        Frustum::from_mat4(self.proj_view())
+    }
 }
 
 #[repr(C)]